@@ -1,8 +1,9 @@
-use std::io::{Error as IoError, Write};
+use std::io::Error as IoError;
 
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BytesMut};
 use imap_codec::{
     decode::{Decoder, GreetingDecodeError, ResponseDecodeError},
+    deflate::DeflateError,
     encode::Encoder,
     imap_types::{
         command::Command,
@@ -15,13 +16,16 @@ use imap_codec::{
 use thiserror::Error;
 use tokio_util::codec::{Decoder as TokioDecoder, Encoder as TokioEncoder};
 
-use super::{find_crlf_inclusive, FramingError, FramingState};
+#[cfg(feature = "starttls")]
+use super::starttls::{self, Upgrade, UpgradeError};
+use super::{find_crlf_inclusive, write_encoded, Compression, FramingError, FramingState};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug)]
 pub struct ImapClientCodec {
     state: FramingState,
     imap_state: ImapState<'static>,
     max_literal_length: u32,
+    compression: Option<Compression>,
 }
 
 impl ImapClientCodec {
@@ -30,8 +34,24 @@ impl ImapClientCodec {
             state: FramingState::ReadLine { to_consume_acc: 0 },
             imap_state: ImapState::Greeting,
             max_literal_length,
+            compression: None,
         }
     }
+
+    /// Compress (and decompress) all following bytes using `COMPRESS=DEFLATE`.
+    ///
+    /// Call this after the tagged `OK` to `COMPRESS DEFLATE` was received.
+    /// Bytes that are already buffered (but not decoded yet) are treated as compressed.
+    pub fn enable_compression(&mut self) {
+        if self.compression.is_none() {
+            self.compression = Some(Compression::default());
+        }
+    }
+
+    /// Returns whether `COMPRESS=DEFLATE` is active.
+    pub fn is_compression_enabled(&self) -> bool {
+        self.compression.is_some()
+    }
 }
 
 #[derive(Debug, Error)]
@@ -40,6 +60,8 @@ pub enum ImapClientCodecError {
     Io(#[from] IoError),
     #[error(transparent)]
     Framing(#[from] FramingError),
+    #[error(transparent)]
+    Deflate(#[from] DeflateError),
     #[error("Parsing failed")]
     ParsingFailed(BytesMut),
 }
@@ -49,6 +71,7 @@ impl PartialEq for ImapClientCodecError {
         match (self, other) {
             (Self::Io(error1), Self::Io(error2)) => error1.kind() == error2.kind(),
             (Self::Framing(kind2), Self::Framing(kind1)) => kind1 == kind2,
+            (Self::Deflate(x), Self::Deflate(y)) => x == y,
             (Self::ParsingFailed(x), Self::ParsingFailed(y)) => x == y,
             _ => false,
         }
@@ -61,11 +84,11 @@ pub enum Event {
     Response(Response<'static>),
 }
 
-impl TokioDecoder for ImapClientCodec {
-    type Item = Event;
-    type Error = ImapClientCodecError;
-
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+impl ImapClientCodec {
+    fn decode_plaintext(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<Event>, ImapClientCodecError> {
         loop {
             if self.imap_state == State::Greeting {
                 match GreetingCodec::default().decode(src) {
@@ -75,7 +98,7 @@ impl TokioDecoder for ImapClientCodec {
                         let to_consume_acc = src.len() - remaining.len();
                         src.advance(to_consume_acc);
 
-                        self.imap_state = ImapState::NotAuthenticated;
+                        self.imap_state = ImapState::from_greeting(&grt);
 
                         return Ok(Some(Event::Greeting(grt)));
                     }
@@ -203,16 +226,42 @@ impl TokioDecoder for ImapClientCodec {
     }
 }
 
+impl TokioDecoder for ImapClientCodec {
+    type Item = Event;
+    type Error = ImapClientCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.compression.take() {
+            Some(mut compression) => {
+                let result = match compression.decompress(src) {
+                    Ok(()) => self.decode_plaintext(&mut compression.plaintext),
+                    Err(error) => Err(error.into()),
+                };
+                self.compression = Some(compression);
+
+                result
+            }
+            None => self.decode_plaintext(src),
+        }
+    }
+}
+
 impl<'a> TokioEncoder<&Command<'a>> for ImapClientCodec {
     type Error = IoError;
 
     fn encode(&mut self, item: &Command, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        //dst.reserve(item.len());
-        let mut writer = dst.writer();
-        // TODO(225): Don't use `dump` here.
-        let data = CommandCodec::default().encode(item).dump();
-        writer.write_all(&data)?;
-        Ok(())
+        write_encoded(
+            CommandCodec::default().encode(item),
+            self.compression.as_mut(),
+            dst,
+        )
+    }
+}
+
+#[cfg(feature = "starttls")]
+impl Upgrade for ImapClientCodec {
+    fn check_upgrade(&self) -> Result<(), UpgradeError> {
+        starttls::check_upgrade(&self.state, self.compression.as_ref())
     }
 }
 
@@ -222,12 +271,16 @@ mod tests {
     use std::num::NonZeroU32;
 
     use bytes::BytesMut;
-    use imap_codec::imap_types::{
-        core::{Literal, NString},
-        fetch::{MessageDataItem, Section},
-        response::{Data, GreetingKind},
+    use imap_codec::{
+        deflate::{Compressor, Decompressor},
+        imap_types::{
+            command::CommandBody,
+            core::{Literal, NString, Tag},
+            fetch::{MessageDataItem, Section},
+            response::{Data, GreetingKind, Status},
+        },
     };
-    use tokio_util::codec::Decoder;
+    use tokio_util::codec::{Decoder, Encoder};
 
     use super::*;
 
@@ -358,4 +411,44 @@ mod tests {
             assert_eq!(expected, got);
         }
     }
+
+    #[test]
+    fn test_compression() {
+        let mut src = BytesMut::from(b"* OK ...\r\nA1 OK done\r\n".as_ref());
+        // The server compresses everything after the tagged `OK`.
+        src.extend_from_slice(&Compressor::new().compress(b"* 1 EXISTS\r\n").unwrap());
+
+        let mut codec = ImapClientCodec::new(1024);
+
+        assert_eq!(
+            codec.decode(&mut src),
+            Ok(Some(Event::Greeting(
+                Greeting::new(GreetingKind::Ok, None, "...").unwrap()
+            )))
+        );
+        assert_eq!(
+            codec.decode(&mut src),
+            Ok(Some(Event::Response(Response::Status(
+                Status::ok(Some(Tag::try_from("A1").unwrap()), None, "done").unwrap()
+            ))))
+        );
+
+        codec.enable_compression();
+        assert!(codec.is_compression_enabled());
+
+        assert_eq!(
+            codec.decode(&mut src),
+            Ok(Some(Event::Response(Response::Data(Data::Exists(1)))))
+        );
+        assert!(src.is_empty());
+
+        let mut dst = BytesMut::new();
+        codec
+            .encode(&Command::new("A2", CommandBody::Noop).unwrap(), &mut dst)
+            .unwrap();
+        assert_eq!(
+            Decompressor::new().decompress(&dst).unwrap(),
+            b"A2 NOOP\r\n"
+        );
+    }
 }
@@ -1,9 +1,18 @@
 //! Support for tokio and (tokio_util::codec).
 
+use std::io::{Error as IoError, ErrorKind};
+
+use bytes::BytesMut;
+use imap_codec::{
+    deflate::{Compressor, Decompressor, DeflateError},
+    encode::Encoded,
+};
 use thiserror::Error;
 
 pub mod client;
 pub mod server;
+#[cfg(feature = "starttls")]
+pub mod starttls;
 
 /// All interactions transmitted by client and server are in the form of
 /// lines, that is, strings that end with a CRLF.
@@ -33,6 +42,54 @@ pub enum FramingError {
     },
 }
 
+/// Raw DEFLATE compression layer (`COMPRESS=DEFLATE`).
+///
+/// Incoming bytes are decompressed into `plaintext` before framing.
+#[derive(Debug, Default)]
+struct Compression {
+    compressor: Compressor,
+    decompressor: Decompressor,
+    plaintext: BytesMut,
+}
+
+impl Compression {
+    /// Decompress (and consume) all bytes from `src`.
+    fn decompress(&mut self, src: &mut BytesMut) -> Result<(), DeflateError> {
+        let decompressed = self.decompressor.decompress(src)?;
+        src.clear();
+        self.plaintext.extend_from_slice(&decompressed);
+
+        Ok(())
+    }
+}
+
+/// Write all fragments of `encoded` to `dst`.
+///
+/// When compression is active, every fragment is compressed and flushed on its own.
+fn write_encoded(
+    encoded: Encoded,
+    compression: Option<&mut Compression>,
+    dst: &mut BytesMut,
+) -> Result<(), IoError> {
+    match compression {
+        Some(compression) => {
+            for fragment in encoded {
+                let compressed = compression
+                    .compressor
+                    .compress_fragment(&fragment)
+                    .map_err(|error| IoError::new(ErrorKind::Other, error))?;
+                dst.extend_from_slice(&compressed);
+            }
+        }
+        None => {
+            // TODO(225): Don't use `dump` here.
+            dst.extend_from_slice(&encoded.dump());
+        }
+    }
+
+    Ok(())
+}
+
 /// Skip the first `skip` bytes of `buf` and count how many more bytes are needed to cover the next `\r\n`.
 ///
 /// This function returns `Ok(None)` when no line was found, `Ok(Some(length))` with
@@ -1,8 +1,9 @@
-use std::io::{Error as IoError, Write};
+use std::io::Error as IoError;
 
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BytesMut};
 use imap_codec::{
     decode::{CommandDecodeError, Decoder},
+    deflate::DeflateError,
     encode::Encoder,
     imap_types::{
         command::Command,
@@ -14,12 +15,15 @@ use imap_codec::{
 use thiserror::Error;
 use tokio_util::codec::{Decoder as TokioDecoder, Encoder as TokioEncoder};
 
-use super::{find_crlf_inclusive, FramingError, FramingState};
+#[cfg(feature = "starttls")]
+use super::starttls::{self, Upgrade, UpgradeError};
+use super::{find_crlf_inclusive, write_encoded, Compression, FramingError, FramingState};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug)]
 pub struct ImapServerCodec {
     state: FramingState,
     max_literal_size: usize,
+    compression: Option<Compression>,
 }
 
 impl ImapServerCodec {
@@ -27,8 +31,24 @@ impl ImapServerCodec {
         Self {
             state: FramingState::ReadLine { to_consume_acc: 0 },
             max_literal_size,
+            compression: None,
         }
     }
+
+    /// Compress (and decompress) all following bytes using `COMPRESS=DEFLATE`.
+    ///
+    /// Call this after the tagged `OK` to `COMPRESS DEFLATE` was sent.
+    /// Bytes that are already buffered (but not decoded yet) are treated as compressed.
+    pub fn enable_compression(&mut self) {
+        if self.compression.is_none() {
+            self.compression = Some(Compression::default());
+        }
+    }
+
+    /// Returns whether `COMPRESS=DEFLATE` is active.
+    pub fn is_compression_enabled(&self) -> bool {
+        self.compression.is_some()
+    }
 }
 
 #[derive(Debug, Error)]
@@ -37,6 +57,8 @@ pub enum ImapServerCodecError {
     Io(#[from] IoError),
     #[error(transparent)]
     Framing(#[from] FramingError),
+    #[error(transparent)]
+    Deflate(#[from] DeflateError),
     #[error("Parsing failed")]
     ParsingFailed(BytesMut),
 }
@@ -46,6 +68,7 @@ impl PartialEq for ImapServerCodecError {
         match (self, other) {
             (Self::Io(error1), Self::Io(error2)) => error1.kind() == error2.kind(),
             (Self::Framing(kind1), Self::Framing(kind2)) => kind1 == kind2,
+            (Self::Deflate(x), Self::Deflate(y)) => x == y,
             (Self::ParsingFailed(x), Self::ParsingFailed(y)) => x == y,
             _ => false,
         }
@@ -65,11 +88,11 @@ pub enum Action {
     SendLiteralReject(u32),
 }
 
-impl TokioDecoder for ImapServerCodec {
-    type Item = Event;
-    type Error = ImapServerCodecError;
-
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+impl ImapServerCodec {
+    fn decode_plaintext(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<Event>, ImapServerCodecError> {
         loop {
             match self.state {
                 FramingState::ReadLine {
@@ -168,16 +191,35 @@ impl TokioDecoder for ImapServerCodec {
     }
 }
 
+impl TokioDecoder for ImapServerCodec {
+    type Item = Event;
+    type Error = ImapServerCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.compression.take() {
+            Some(mut compression) => {
+                let result = match compression.decompress(src) {
+                    Ok(()) => self.decode_plaintext(&mut compression.plaintext),
+                    Err(error) => Err(error.into()),
+                };
+                self.compression = Some(compression);
+
+                result
+            }
+            None => self.decode_plaintext(src),
+        }
+    }
+}
+
 impl TokioEncoder<&Greeting<'_>> for ImapServerCodec {
     type Error = IoError;
 
     fn encode(&mut self, item: &Greeting, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        //dst.reserve(item.len());
-        let mut writer = dst.writer();
-        // TODO(225): Don't use `dump` here.
-        let data = GreetingCodec::default().encode(item).dump();
-        writer.write_all(&data)?;
-        Ok(())
+        write_encoded(
+            GreetingCodec::default().encode(item),
+            self.compression.as_mut(),
+            dst,
+        )
     }
 }
 
@@ -185,26 +227,35 @@ impl TokioEncoder<&Response<'_>> for ImapServerCodec {
     type Error = IoError;
 
     fn encode(&mut self, item: &Response, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        //dst.reserve(item.len());
-        let mut writer = dst.writer();
-        // TODO(225): Don't use `dump` here.
-        let data = ResponseCodec::default().encode(item).dump();
-        writer.write_all(&data)?;
-        Ok(())
+        write_encoded(
+            ResponseCodec::default().encode(item),
+            self.compression.as_mut(),
+            dst,
+        )
+    }
+}
+
+#[cfg(feature = "starttls")]
+impl Upgrade for ImapServerCodec {
+    fn check_upgrade(&self) -> Result<(), UpgradeError> {
+        starttls::check_upgrade(&self.state, self.compression.as_ref())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use bytes::BytesMut;
-    #[cfg(feature = "quirk_crlf_relaxed")]
-    use imap_codec::imap_types::core::Tag;
-    use imap_codec::imap_types::{
-        command::{Command, CommandBody},
-        core::{AString, AtomExt, IString, Literal},
-        secret::Secret,
+    use imap_codec::{
+        deflate::{Compressor, Decompressor},
+        imap_types::{
+            command::{Command, CommandBody},
+            core::{AString, AtomExt, IString, Literal, Tag},
+            extensions::compress::CompressionAlgorithm,
+            response::{Data, Status},
+            secret::Secret,
+        },
     };
-    use tokio_util::codec::Decoder;
+    use tokio_util::codec::{Decoder, Encoder};
 
     use super::*;
 
@@ -357,4 +408,53 @@ mod tests {
             assert_eq!(expected, got);
         }
     }
+
+    #[test]
+    fn test_compression() {
+        let mut src = BytesMut::from(b"a compress deflate\r\n".as_ref());
+        // The client compresses everything after the tagged `OK`.
+        let mut compressed = Compressor::new().compress(b"a noop\r\n").unwrap();
+        let rest = compressed.split_off(compressed.len() / 2);
+        src.extend_from_slice(&compressed);
+
+        let mut codec = ImapServerCodec::new(1024);
+
+        assert_eq!(
+            codec.decode(&mut src),
+            Ok(Some(Event::Command(
+                Command::new("a", CommandBody::compress(CompressionAlgorithm::Deflate)).unwrap()
+            )))
+        );
+
+        let mut dst = BytesMut::new();
+        codec
+            .encode(
+                &Response::Status(
+                    Status::ok(Some(Tag::try_from("a").unwrap()), None, "done").unwrap(),
+                ),
+                &mut dst,
+            )
+            .unwrap();
+        assert_eq!(dst.as_ref(), b"a OK done\r\n");
+
+        codec.enable_compression();
+
+        assert_eq!(codec.decode(&mut src), Ok(None));
+        src.extend_from_slice(&rest);
+        assert_eq!(
+            codec.decode(&mut src),
+            Ok(Some(Event::Command(
+                Command::new("a", CommandBody::Noop).unwrap()
+            )))
+        );
+
+        let mut dst = BytesMut::new();
+        codec
+            .encode(&Response::Data(Data::Exists(1)), &mut dst)
+            .unwrap();
+        assert_eq!(
+            Decompressor::new().decompress(&dst).unwrap(),
+            b"* 1 EXISTS\r\n"
+        );
+    }
 }
@@ -0,0 +1,300 @@
+//! Hand-off of the plaintext transport after `STARTTLS`.
+//!
+//! After the server sent (or the client received) the tagged `OK` to `STARTTLS`, the raw
+//! transport must be handed over to a TLS implementation. Any plaintext byte that was received
+//! but not processed yet at this point was sent *before* the TLS handshake and must not be
+//! interpreted later as if it was protected by TLS (see CVE-2011-0411).
+//!
+//! [`upgrade`] returns the inner transport (and the codec) only when nothing is buffered.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! // Server
+//! framed.send(&Response::Status(Status::ok(Some(tag), None, "Begin TLS")?)).await?;
+//!
+//! let (stream, codec) = upgrade(framed)?;
+//! let stream = acceptor.accept(stream).await?;
+//! let mut framed = Framed::new(stream, codec);
+//! ```
+
+use thiserror::Error;
+use tokio_util::codec::{Framed, FramedParts};
+
+use crate::{Compression, FramingState};
+
+/// Codecs that support [`upgrade`].
+///
+/// This trait is sealed and implemented for [`ImapClientCodec`](crate::client::ImapClientCodec)
+/// and [`ImapServerCodec`](crate::server::ImapServerCodec).
+pub trait Upgrade: private::Sealed {
+    #[doc(hidden)]
+    fn check_upgrade(&self) -> Result<(), UpgradeError>;
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for crate::client::ImapClientCodec {}
+    impl Sealed for crate::server::ImapServerCodec {}
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum UpgradeError {
+    #[error("Refusing to upgrade with {length} plaintext bytes buffered")]
+    PlaintextBuffered { length: usize },
+    #[error("Refusing to upgrade with {length} bytes not written yet")]
+    WriteBuffered { length: usize },
+    #[error("Refusing to upgrade with active compression")]
+    CompressionActive,
+}
+
+/// Return the inner transport and the codec of `framed` to start a TLS handshake.
+///
+/// Call this after the tagged `OK` to `STARTTLS` was sent (server) or received (client).
+/// The codec can be reused for the TLS-protected transport, e.g., with [`Framed::new`].
+///
+/// Upgrading is refused when plaintext bytes are still buffered. The connection should be
+/// closed in this case.
+pub fn upgrade<T, C>(framed: Framed<T, C>) -> Result<(T, C), UpgradeError>
+where
+    C: Upgrade,
+{
+    let FramedParts {
+        io,
+        codec,
+        read_buf,
+        write_buf,
+        ..
+    } = framed.into_parts();
+
+    if !write_buf.is_empty() {
+        return Err(UpgradeError::WriteBuffered {
+            length: write_buf.len(),
+        });
+    }
+
+    codec.check_upgrade()?;
+
+    if !read_buf.is_empty() {
+        return Err(UpgradeError::PlaintextBuffered {
+            length: read_buf.len(),
+        });
+    }
+
+    Ok((io, codec))
+}
+
+pub(crate) fn check_upgrade(
+    state: &FramingState,
+    compression: Option<&Compression>,
+) -> Result<(), UpgradeError> {
+    // RFC 4978: "[...] the COMPRESS command MUST be issued after STARTTLS."
+    if compression.is_some() {
+        return Err(UpgradeError::CompressionActive);
+    }
+
+    match *state {
+        FramingState::ReadLine { to_consume_acc: 0 } => Ok(()),
+        FramingState::ReadLine { to_consume_acc }
+        | FramingState::ReadLiteral { to_consume_acc, .. } => {
+            Err(UpgradeError::PlaintextBuffered {
+                length: to_consume_acc,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::{SinkExt, StreamExt};
+    use imap_codec::imap_types::{
+        command::{Command, CommandBody},
+        core::Tag,
+        response::{Greeting, Response, Status},
+    };
+    use rcgen::CertifiedKey;
+    use tokio::io::{duplex, AsyncWriteExt, DuplexStream};
+    use tokio_rustls::{
+        rustls::{
+            crypto::ring::default_provider,
+            pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer, ServerName},
+            ClientConfig, RootCertStore, ServerConfig,
+        },
+        TlsAcceptor, TlsConnector,
+    };
+    use tokio_util::codec::Decoder;
+
+    use super::*;
+    use crate::{
+        client::{self, ImapClientCodec},
+        server::{self, ImapServerCodec},
+    };
+
+    /// Create a TLS acceptor and connector using a (local) self-signed certificate for `localhost`.
+    fn tls() -> (TlsAcceptor, TlsConnector) {
+        let CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let provider = Arc::new(default_provider());
+
+        let server_config = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der())),
+            )
+            .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.der().clone()).unwrap();
+
+        let client_config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        (
+            TlsAcceptor::from(Arc::new(server_config)),
+            TlsConnector::from(Arc::new(client_config)),
+        )
+    }
+
+    /// Run the server side until the tagged `OK` to `STARTTLS` was sent.
+    async fn server_until_starttls(stream: DuplexStream) -> Framed<DuplexStream, ImapServerCodec> {
+        let mut framed = ImapServerCodec::new(1024).framed(stream);
+
+        framed
+            .send(&Greeting::ok(None, "Hello, World!").unwrap())
+            .await
+            .unwrap();
+
+        let Some(Ok(server::Event::Command(command))) = framed.next().await else {
+            panic!("Expected command");
+        };
+        assert_eq!(command.body, CommandBody::StartTLS);
+
+        framed
+            .send(&Response::Status(
+                Status::ok(Some(command.tag), None, "Begin TLS").unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        framed
+    }
+
+    #[tokio::test]
+    async fn test_upgrade() {
+        let (acceptor, connector) = tls();
+        let (client_stream, server_stream) = duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let framed = server_until_starttls(server_stream).await;
+
+            let (stream, codec) = upgrade(framed).unwrap();
+            let stream = acceptor.accept(stream).await.unwrap();
+            let mut framed = Framed::new(stream, codec);
+
+            let Some(Ok(server::Event::Command(command))) = framed.next().await else {
+                panic!("Expected command");
+            };
+            assert_eq!(command, Command::new("B", CommandBody::Noop).unwrap());
+
+            framed
+                .send(&Response::Status(
+                    Status::ok(Some(command.tag), None, "NOOP done").unwrap(),
+                ))
+                .await
+                .unwrap();
+        });
+
+        let mut framed = ImapClientCodec::new(1024).framed(client_stream);
+
+        let Some(Ok(client::Event::Greeting(_))) = framed.next().await else {
+            panic!("Expected greeting");
+        };
+
+        framed
+            .send(&Command::new("A", CommandBody::StartTLS).unwrap())
+            .await
+            .unwrap();
+
+        let Some(Ok(client::Event::Response(Response::Status(status)))) = framed.next().await
+        else {
+            panic!("Expected status");
+        };
+        assert_eq!(status.tag(), Some(&Tag::try_from("A").unwrap()));
+
+        let (stream, codec) = upgrade(framed).unwrap();
+        let stream = connector
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+        let mut framed = Framed::new(stream, codec);
+
+        framed
+            .send(&Command::new("B", CommandBody::Noop).unwrap())
+            .await
+            .unwrap();
+
+        let Some(Ok(client::Event::Response(Response::Status(status)))) = framed.next().await
+        else {
+            panic!("Expected status");
+        };
+        assert_eq!(status.tag(), Some(&Tag::try_from("B").unwrap()));
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_refused_command_injection() {
+        let (mut client_stream, server_stream) = duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let framed = server_until_starttls(server_stream).await;
+
+            upgrade(framed)
+        });
+
+        // Pipeline a command after `STARTTLS`. It must not be executed after the handshake.
+        client_stream
+            .write_all(b"A STARTTLS\r\nB LOGIN alice password\r\n")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.await.unwrap().err(),
+            Some(UpgradeError::PlaintextBuffered { length: 24 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_refused_response_injection() {
+        let (client_stream, mut server_stream) = duplex(4096);
+
+        // Inject a response after the tagged `OK`.
+        server_stream
+            .write_all(b"* OK Hello, World!\r\nA OK Begin TLS\r\n* CAPABILITY IMAP4REV1\r\n")
+            .await
+            .unwrap();
+
+        let mut framed = ImapClientCodec::new(1024).framed(client_stream);
+
+        let Some(Ok(client::Event::Greeting(_))) = framed.next().await else {
+            panic!("Expected greeting");
+        };
+        let Some(Ok(client::Event::Response(_))) = framed.next().await else {
+            panic!("Expected status");
+        };
+
+        assert_eq!(
+            upgrade(framed).err(),
+            Some(UpgradeError::PlaintextBuffered { length: 24 })
+        );
+    }
+}
@@ -111,6 +111,7 @@ mod tests {
                     "a",
                     CommandBody::Select {
                         mailbox: Mailbox::Inbox,
+                        parameters: vec![],
                     },
                 )
                 .unwrap(),
@@ -122,6 +123,7 @@ mod tests {
                     "a",
                     CommandBody::Select {
                         mailbox: Mailbox::Inbox,
+                        parameters: vec![],
                     },
                 )
                 .unwrap(),
@@ -474,6 +474,7 @@ mod tests {
                         "a",
                         CommandBody::Select {
                             mailbox: Mailbox::Inbox,
+                            parameters: vec![],
                         },
                     )
                     .unwrap(),
@@ -487,6 +488,7 @@ mod tests {
                         "a",
                         CommandBody::Select {
                             mailbox: Mailbox::Inbox,
+                            parameters: vec![],
                         },
                     )
                     .unwrap(),
@@ -70,7 +70,7 @@ use imap_types::{
     mailbox::{ListCharString, ListMailbox, Mailbox, MailboxOther},
     response::{
         Bye, Capability, Code, CodeOther, CommandContinuationRequest, Data, Greeting, GreetingKind,
-        Response, Status, StatusBody, StatusKind, Tagged,
+        Progress, Response, Status, StatusBody, StatusKind, Tagged,
     },
     search::SearchKey,
     sequence::{SeqOrUid, Sequence, SequenceSet},
@@ -79,7 +79,12 @@ use imap_types::{
 };
 use utils::{join_serializable, List1AttributeValueOrNil, List1OrNil};
 
-use crate::{AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, ResponseCodec};
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::encode_gm_label_list;
+use crate::{
+    extensions::{convert::encode_section_convert, tagged_ext::encode_tagged_ext_params},
+    AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, ResponseCodec,
+};
 
 /// Encoder.
 ///
@@ -321,16 +326,25 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
                 ctx.write_all(b" ")?;
                 password.declassify().encode_ctx(ctx)
             }
-            CommandBody::Select { mailbox } => {
+            CommandBody::Select {
+                mailbox,
+                parameters,
+            } => {
                 ctx.write_all(b"SELECT")?;
                 ctx.write_all(b" ")?;
-                mailbox.encode_ctx(ctx)
+                mailbox.encode_ctx(ctx)?;
+                encode_tagged_ext_params(parameters, ctx)
             }
             CommandBody::Unselect => ctx.write_all(b"UNSELECT"),
-            CommandBody::Examine { mailbox } => {
+            CommandBody::Unauthenticate => ctx.write_all(b"UNAUTHENTICATE"),
+            CommandBody::Examine {
+                mailbox,
+                parameters,
+            } => {
                 ctx.write_all(b"EXAMINE")?;
                 ctx.write_all(b" ")?;
-                mailbox.encode_ctx(ctx)
+                mailbox.encode_ctx(ctx)?;
+                encode_tagged_ext_params(parameters, ctx)
             }
             CommandBody::Create { mailbox } => {
                 ctx.write_all(b"CREATE")?;
@@ -345,12 +359,14 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
             CommandBody::Rename {
                 from: mailbox,
                 to: new_mailbox,
+                parameters,
             } => {
                 ctx.write_all(b"RENAME")?;
                 ctx.write_all(b" ")?;
                 mailbox.encode_ctx(ctx)?;
                 ctx.write_all(b" ")?;
-                new_mailbox.encode_ctx(ctx)
+                new_mailbox.encode_ctx(ctx)?;
+                encode_tagged_ext_params(parameters, ctx)
             }
             CommandBody::Subscribe { mailbox } => {
                 ctx.write_all(b"SUBSCRIBE")?;
@@ -363,14 +379,31 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
                 mailbox.encode_ctx(ctx)
             }
             CommandBody::List {
+                select_options,
                 reference,
                 mailbox_wildcard,
+                return_options,
             } => {
                 ctx.write_all(b"LIST")?;
                 ctx.write_all(b" ")?;
+
+                if !select_options.is_empty() {
+                    ctx.write_all(b"(")?;
+                    join_serializable(select_options, b" ", ctx)?;
+                    ctx.write_all(b") ")?;
+                }
+
                 reference.encode_ctx(ctx)?;
                 ctx.write_all(b" ")?;
-                mailbox_wildcard.encode_ctx(ctx)
+                mailbox_wildcard.encode_ctx(ctx)?;
+
+                if !return_options.is_empty() {
+                    ctx.write_all(b" RETURN (")?;
+                    join_serializable(return_options, b" ", ctx)?;
+                    ctx.write_all(b")")?;
+                }
+
+                Ok(())
             }
             CommandBody::Lsub {
                 reference,
@@ -398,6 +431,7 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
                 mailbox,
                 flags,
                 date,
+                extensions,
                 message,
             } => {
                 ctx.write_all(b"APPEND")?;
@@ -416,6 +450,11 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
                     date.encode_ctx(ctx)?;
                 }
 
+                for extension in extensions {
+                    ctx.write_all(b" ")?;
+                    extension.encode_ctx(ctx)?;
+                }
+
                 ctx.write_all(b" ")?;
                 message.encode_ctx(ctx)
             }
@@ -443,6 +482,30 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
                 ctx.write_all(b" ")?;
                 join_serializable(criteria.as_ref(), b" ", ctx)
             }
+            CommandBody::ESearch {
+                source,
+                return_options,
+                charset,
+                criteria,
+            } => {
+                ctx.write_all(b"ESEARCH")?;
+                if let Some(source) = source {
+                    ctx.write_all(b" IN (")?;
+                    join_serializable(source.as_ref(), b" ", ctx)?;
+                    ctx.write_all(b")")?;
+                }
+                if !return_options.is_empty() {
+                    ctx.write_all(b" RETURN (")?;
+                    join_serializable(return_options, b" ", ctx)?;
+                    ctx.write_all(b")")?;
+                }
+                if let Some(charset) = charset {
+                    ctx.write_all(b" CHARSET ")?;
+                    charset.encode_ctx(ctx)?;
+                }
+                ctx.write_all(b" ")?;
+                join_serializable(criteria.as_ref(), b" ", ctx)
+            }
             CommandBody::Sort {
                 sort_criteria,
                 charset,
@@ -480,6 +543,7 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
             CommandBody::Fetch {
                 sequence_set,
                 macro_or_item_names,
+                modifiers,
                 uid,
             } => {
                 if *uid {
@@ -490,10 +554,12 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
 
                 sequence_set.encode_ctx(ctx)?;
                 ctx.write_all(b" ")?;
-                macro_or_item_names.encode_ctx(ctx)
+                macro_or_item_names.encode_ctx(ctx)?;
+                encode_tagged_ext_params(modifiers, ctx)
             }
             CommandBody::Store {
                 sequence_set,
+                modifiers,
                 kind,
                 response,
                 flags,
@@ -506,6 +572,7 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
                 }
 
                 sequence_set.encode_ctx(ctx)?;
+                encode_tagged_ext_params(modifiers, ctx)?;
                 ctx.write_all(b" ")?;
 
                 match kind {
@@ -577,6 +644,87 @@ impl<'a> EncodeIntoContext for CommandBody<'a> {
                 ctx.write_all(b" ")?;
                 mailbox.encode_ctx(ctx)
             }
+            CommandBody::Replace {
+                sequence,
+                mailbox,
+                flags,
+                date,
+                message,
+                uid,
+            } => {
+                if *uid {
+                    ctx.write_all(b"UID REPLACE ")?;
+                } else {
+                    ctx.write_all(b"REPLACE ")?;
+                }
+                sequence.encode_ctx(ctx)?;
+                ctx.write_all(b" ")?;
+                mailbox.encode_ctx(ctx)?;
+
+                if !flags.is_empty() {
+                    ctx.write_all(b" ")?;
+                    ctx.write_all(b"(")?;
+                    join_serializable(flags, b" ", ctx)?;
+                    ctx.write_all(b")")?;
+                }
+
+                if let Some(date) = date {
+                    ctx.write_all(b" ")?;
+                    date.encode_ctx(ctx)?;
+                }
+
+                ctx.write_all(b" ")?;
+                message.encode_ctx(ctx)
+            }
+            CommandBody::Convert {
+                sequence_set,
+                item_names,
+                uid,
+            } => {
+                if *uid {
+                    ctx.write_all(b"UID CONVERT ")?;
+                } else {
+                    ctx.write_all(b"CONVERT ")?;
+                }
+
+                sequence_set.encode_ctx(ctx)?;
+                ctx.write_all(b" (")?;
+                join_serializable(item_names.as_ref(), b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+            #[cfg(feature = "ext_gmail")]
+            CommandBody::StoreGmailLabels {
+                sequence_set,
+                kind,
+                response,
+                labels,
+                uid,
+            } => {
+                if *uid {
+                    ctx.write_all(b"UID STORE ")?;
+                } else {
+                    ctx.write_all(b"STORE ")?;
+                }
+
+                sequence_set.encode_ctx(ctx)?;
+                ctx.write_all(b" ")?;
+
+                match kind {
+                    StoreType::Add => ctx.write_all(b"+")?,
+                    StoreType::Remove => ctx.write_all(b"-")?,
+                    StoreType::Replace => {}
+                }
+
+                ctx.write_all(b"X-GM-LABELS")?;
+
+                match response {
+                    StoreResponse::Answer => {}
+                    StoreResponse::Silent => ctx.write_all(b".SILENT")?,
+                }
+
+                ctx.write_all(b" ")?;
+                encode_gm_label_list(labels, ctx)
+            }
             #[cfg(feature = "ext_id")]
             CommandBody::Id { parameters } => {
                 ctx.write_all(b"ID ")?;
@@ -884,6 +1032,10 @@ impl<'a> EncodeIntoContext for SearchKey<'a> {
                 ctx.write_all(b"NOT ")?;
                 search_key.encode_ctx(ctx)
             }
+            SearchKey::Fuzzy(search_key) => {
+                ctx.write_all(b"FUZZY ")?;
+                search_key.encode_ctx(ctx)
+            }
             SearchKey::Or(search_key_a, search_key_b) => {
                 ctx.write_all(b"OR ")?;
                 search_key_a.encode_ctx(ctx)?;
@@ -914,6 +1066,20 @@ impl<'a> EncodeIntoContext for SearchKey<'a> {
                 join_serializable(search_keys.as_ref(), b" ", ctx)?;
                 ctx.write_all(b")")
             }
+            #[cfg(feature = "ext_gmail")]
+            SearchKey::GmailRaw(astring) => {
+                ctx.write_all(b"X-GM-RAW ")?;
+                astring.encode_ctx(ctx)
+            }
+            #[cfg(feature = "ext_gmail")]
+            SearchKey::GmailMessageId(id) => write!(ctx, "X-GM-MSGID {id}"),
+            #[cfg(feature = "ext_gmail")]
+            SearchKey::GmailThreadId(id) => write!(ctx, "X-GM-THRID {id}"),
+            #[cfg(feature = "ext_gmail")]
+            SearchKey::GmailLabels(label) => {
+                ctx.write_all(b"X-GM-LABELS ")?;
+                label.encode_ctx(ctx)
+            }
         }
     }
 }
@@ -1039,6 +1205,44 @@ impl<'a> EncodeIntoContext for MessageDataItemName<'a> {
                 join_serializable(section, b".", ctx)?;
                 ctx.write_all(b"]")
             }
+            MessageDataItemName::BinaryConvert {
+                section,
+                conversion,
+                partial,
+                peek,
+            } => {
+                ctx.write_all(b"BINARY")?;
+                if *peek {
+                    ctx.write_all(b".PEEK")?;
+                }
+
+                encode_section_convert(section, conversion, ctx)?;
+
+                if let Some((a, b)) = partial {
+                    ctx.write_all(b"<")?;
+                    a.encode_ctx(ctx)?;
+                    ctx.write_all(b".")?;
+                    b.encode_ctx(ctx)?;
+                    ctx.write_all(b">")?;
+                }
+
+                Ok(())
+            }
+            MessageDataItemName::Preview { lazy } => {
+                ctx.write_all(b"PREVIEW")?;
+
+                if *lazy {
+                    ctx.write_all(b" (LAZY)")?;
+                }
+
+                Ok(())
+            }
+            #[cfg(feature = "ext_gmail")]
+            MessageDataItemName::GmailMessageId => ctx.write_all(b"X-GM-MSGID"),
+            #[cfg(feature = "ext_gmail")]
+            MessageDataItemName::GmailThreadId => ctx.write_all(b"X-GM-THRID"),
+            #[cfg(feature = "ext_gmail")]
+            MessageDataItemName::GmailLabels => ctx.write_all(b"X-GM-LABELS"),
         }
     }
 }
@@ -1266,6 +1470,55 @@ impl<'a> EncodeIntoContext for Code<'a> {
                 destination.encode_ctx(ctx)
             }
             Code::UidNotSticky => ctx.write_all(b"UIDNOTSTICKY"),
+            Code::UidRequired => ctx.write_all(b"UIDREQUIRED"),
+            Code::BadParams => ctx.write_all(b"BADPARAMS"),
+            Code::NoConvert => ctx.write_all(b"NOCONVERT"),
+            Code::MaxConvertMessages(number) => write!(ctx, "MAXCONVERTMESSAGES {number}"),
+            Code::MaxConvertParts(number) => write!(ctx, "MAXCONVERTPARTS {number}"),
+            Code::Unavailable => ctx.write_all(b"UNAVAILABLE"),
+            Code::AuthenticationFailed => ctx.write_all(b"AUTHENTICATIONFAILED"),
+            Code::AuthorizationFailed => ctx.write_all(b"AUTHORIZATIONFAILED"),
+            Code::Expired => ctx.write_all(b"EXPIRED"),
+            Code::PrivacyRequired => ctx.write_all(b"PRIVACYREQUIRED"),
+            Code::ContactAdmin => ctx.write_all(b"CONTACTADMIN"),
+            Code::NoPerm => ctx.write_all(b"NOPERM"),
+            Code::InUse => ctx.write_all(b"INUSE"),
+            Code::ExpungeIssued => ctx.write_all(b"EXPUNGEISSUED"),
+            Code::Corruption => ctx.write_all(b"CORRUPTION"),
+            Code::ServerBug => ctx.write_all(b"SERVERBUG"),
+            Code::ClientBug => ctx.write_all(b"CLIENTBUG"),
+            Code::Cannot => ctx.write_all(b"CANNOT"),
+            Code::Limit => ctx.write_all(b"LIMIT"),
+            Code::AlreadyExists => ctx.write_all(b"ALREADYEXISTS"),
+            Code::NonExistent => ctx.write_all(b"NONEXISTENT"),
+            Code::InProgress(progress) => {
+                ctx.write_all(b"INPROGRESS")?;
+
+                if let Some(Progress { tag, done, goal }) = progress {
+                    ctx.write_all(b" (")?;
+                    match tag {
+                        Some(tag) => {
+                            ctx.write_all(b"\"")?;
+                            tag.encode_ctx(ctx)?;
+                            ctx.write_all(b"\"")?;
+                        }
+                        None => ctx.write_all(b"NIL")?,
+                    }
+                    ctx.write_all(b" ")?;
+                    match done {
+                        Some(done) => done.encode_ctx(ctx)?,
+                        None => ctx.write_all(b"NIL")?,
+                    }
+                    ctx.write_all(b" ")?;
+                    match goal {
+                        Some(goal) => goal.encode_ctx(ctx)?,
+                        None => ctx.write_all(b"NIL")?,
+                    }
+                    ctx.write_all(b")")?;
+                }
+
+                Ok(())
+            }
             Code::Other(unknown) => unknown.encode_ctx(ctx),
         }
     }
@@ -1294,6 +1547,7 @@ impl<'a> EncodeIntoContext for Data<'a> {
                 items,
                 delimiter,
                 mailbox,
+                extended_items,
             } => {
                 ctx.write_all(b"* LIST (")?;
                 join_serializable(items, b" ", ctx)?;
@@ -1308,6 +1562,12 @@ impl<'a> EncodeIntoContext for Data<'a> {
                 }
                 ctx.write_all(b" ")?;
                 mailbox.encode_ctx(ctx)?;
+
+                if !extended_items.is_empty() {
+                    ctx.write_all(b" (")?;
+                    join_serializable(extended_items, b" ", ctx)?;
+                    ctx.write_all(b")")?;
+                }
             }
             Data::Lsub {
                 items,
@@ -1351,6 +1611,24 @@ impl<'a> EncodeIntoContext for Data<'a> {
                     join_serializable(seqs, b" ", ctx)?;
                 }
             }
+            Data::ESearch {
+                correlator,
+                uid,
+                data,
+            } => {
+                ctx.write_all(b"* ESEARCH")?;
+                if let Some(correlator) = correlator {
+                    ctx.write_all(b" ")?;
+                    correlator.encode_ctx(ctx)?;
+                }
+                if *uid {
+                    ctx.write_all(b" UID")?;
+                }
+                for item in data {
+                    ctx.write_all(b" ")?;
+                    item.encode_ctx(ctx)?;
+                }
+            }
             Data::Thread(threads) => {
                 if threads.is_empty() {
                     ctx.write_all(b"* THREAD")?;
@@ -1374,6 +1652,11 @@ impl<'a> EncodeIntoContext for Data<'a> {
                 join_serializable(items.as_ref(), b" ", ctx)?;
                 ctx.write_all(b")")?;
             }
+            Data::UidFetch { uid, items } => {
+                write!(ctx, "* {uid} UIDFETCH (")?;
+                join_serializable(items.as_ref(), b" ", ctx)?;
+                ctx.write_all(b")")?;
+            }
             Data::Enabled { capabilities } => {
                 write!(ctx, "* ENABLED")?;
 
@@ -1397,6 +1680,13 @@ impl<'a> EncodeIntoContext for Data<'a> {
                     root.encode_ctx(ctx)?;
                 }
             }
+            Data::Converted { seq, tag, items } => {
+                write!(ctx, "* {seq} CONVERTED (TAG \"")?;
+                tag.encode_ctx(ctx)?;
+                ctx.write_all(b"\") (")?;
+                join_serializable(items.as_ref(), b" ", ctx)?;
+                ctx.write_all(b")")?;
+            }
             #[cfg(feature = "ext_id")]
             Data::Id { parameters } => {
                 ctx.write_all(b"* ID ")?;
@@ -1494,6 +1784,7 @@ impl EncodeIntoContext for StatusDataItem {
                 ctx.write_all(b"DELETED-STORAGE ")?;
                 count.encode_ctx(ctx)
             }
+            Self::Other(other) => other.encode_ctx(ctx),
         }
     }
 }
@@ -1565,6 +1856,30 @@ impl<'a> EncodeIntoContext for MessageDataItem<'a> {
                 ctx.write_all(b"] ")?;
                 size.encode_ctx(ctx)
             }
+            Self::BinaryConvert {
+                section,
+                conversion,
+                value,
+            } => {
+                ctx.write_all(b"BINARY")?;
+                encode_section_convert(section, conversion, ctx)?;
+                ctx.write_all(b" ")?;
+                value.encode_ctx(ctx)
+            }
+            Self::Preview(nstring) => {
+                ctx.write_all(b"PREVIEW ")?;
+                nstring.encode_ctx(ctx)
+            }
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailMessageId(id) => write!(ctx, "X-GM-MSGID {id}"),
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailThreadId(id) => write!(ctx, "X-GM-THRID {id}"),
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailLabels(labels) => {
+                ctx.write_all(b"X-GM-LABELS ")?;
+                encode_gm_label_list(labels, ctx)
+            }
+            Self::Other(other) => other.encode_ctx(ctx),
         }
     }
 }
@@ -18,10 +18,12 @@ use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     combinator::{map, opt, value},
-    multi::{separated_list0, separated_list1},
+    multi::{many0, separated_list0, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
 };
 
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::store_gmail_labels;
 #[cfg(feature = "ext_id")]
 use crate::extensions::id::id;
 #[cfg(feature = "ext_metadata")]
@@ -34,11 +36,15 @@ use crate::{
     extensions::{
         binary::literal8,
         compress::compress,
+        convert::convert,
         enable::enable,
         idle::idle,
+        multisearch::esearch,
         quota::{getquota, getquotaroot, setquota},
         r#move::r#move,
+        replace::replace,
         sort::sort,
+        tagged_ext::{list_options, tagged_ext, tagged_ext_params},
         thread::thread,
         uidplus::uid_expunge,
     },
@@ -122,14 +128,16 @@ pub(crate) fn command_any(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 ///                status /
 ///                subscribe /
 ///                unsubscribe /
-///                idle /         ; RFC 2177
-///                enable /       ; RFC 5161
-///                compress /     ; RFC 4978
-///                getquota /     ; RFC 9208
-///                getquotaroot / ; RFC 9208
-///                setquota /     ; RFC 9208
-///                setmetadata /  ; RFC 5464
-///                getmetadata    ; RFC 5464
+///                idle /           ; RFC 2177
+///                enable /         ; RFC 5161
+///                compress /       ; RFC 4978
+///                getquota /       ; RFC 9208
+///                getquotaroot /   ; RFC 9208
+///                setquota /       ; RFC 9208
+///                setmetadata /    ; RFC 5464
+///                getmetadata /    ; RFC 5464
+///                "UNAUTHENTICATE" / ; RFC 8437
+///                esearch          ; RFC 7377
 /// ```
 ///
 /// Note: Valid only in Authenticated or Selected state
@@ -156,16 +164,23 @@ pub(crate) fn command_auth(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
         setmetadata,
         #[cfg(feature = "ext_metadata")]
         getmetadata,
+        value(CommandBody::Unauthenticate, tag_no_case(b"UNAUTHENTICATE")),
+        esearch,
     ))(input)
 }
 
-/// `append = "APPEND" SP mailbox [SP flag-list] [SP date-time] SP literal`
+/// ```abnf
+/// append = "APPEND" SP mailbox [SP flag-list] [SP date-time] *(SP append-ext) SP literal
+///
+/// append-ext = append-ext-name SP append-ext-value ; RFC 4466
+/// ```
 pub(crate) fn append(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     let mut parser = tuple((
         tag_no_case(b"APPEND "),
         mailbox,
         opt(preceded(sp, flag_list)),
         opt(preceded(sp, date_time)),
+        many0(preceded(sp, tagged_ext)),
         sp,
         alt((
             map(literal, LiteralOrLiteral8::Literal),
@@ -173,7 +188,7 @@ pub(crate) fn append(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
         )),
     ));
 
-    let (remaining, (_, mailbox, flags, date, _, message)) = parser(input)?;
+    let (remaining, (_, mailbox, flags, date, extensions, _, message)) = parser(input)?;
 
     Ok((
         remaining,
@@ -181,6 +196,7 @@ pub(crate) fn append(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
             mailbox,
             flags: flags.unwrap_or_default(),
             date,
+            extensions,
             message,
         },
     ))
@@ -208,26 +224,50 @@ pub(crate) fn delete(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     Ok((remaining, CommandBody::Delete { mailbox }))
 }
 
-/// `examine = "EXAMINE" SP mailbox`
+/// `examine = "EXAMINE" SP mailbox [select-params]`
 pub(crate) fn examine(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    let mut parser = preceded(tag_no_case(b"EXAMINE "), mailbox);
+    let mut parser = tuple((
+        tag_no_case(b"EXAMINE "),
+        mailbox,
+        opt(preceded(sp, tagged_ext_params)),
+    ));
 
-    let (remaining, mailbox) = parser(input)?;
+    let (remaining, (_, mailbox, parameters)) = parser(input)?;
 
-    Ok((remaining, CommandBody::Examine { mailbox }))
+    Ok((
+        remaining,
+        CommandBody::Examine {
+            mailbox,
+            parameters: parameters.unwrap_or_default(),
+        },
+    ))
 }
 
-/// `list = "LIST" SP mailbox SP list-mailbox`
+/// ```abnf
+/// list = "LIST" [SP list-select-opts] SP mailbox SP list-mailbox [SP list-return-opts]
+/// ```
+///
+/// Note: Multiple mailbox patterns (RFC 5258) are not supported.
 pub(crate) fn list(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"LIST "), mailbox, sp, list_mailbox));
+    let mut parser = tuple((
+        tag_no_case(b"LIST "),
+        opt(terminated(list_options, sp)),
+        mailbox,
+        sp,
+        list_mailbox,
+        opt(preceded(tag_no_case(b" RETURN "), list_options)),
+    ));
 
-    let (remaining, (_, reference, _, mailbox_wildcard)) = parser(input)?;
+    let (remaining, (_, select_options, reference, _, mailbox_wildcard, return_options)) =
+        parser(input)?;
 
     Ok((
         remaining,
         CommandBody::List {
+            select_options: select_options.unwrap_or_default(),
             reference,
             mailbox_wildcard,
+            return_options: return_options.unwrap_or_default(),
         },
     ))
 }
@@ -247,30 +287,47 @@ pub(crate) fn lsub(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     ))
 }
 
-/// `rename = "RENAME" SP mailbox SP mailbox`
+/// `rename = "RENAME" SP mailbox SP mailbox [rename-params]`
 ///
 /// Note: Use of INBOX as a destination gives a NO error
 pub(crate) fn rename(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"RENAME "), mailbox, sp, mailbox));
+    let mut parser = tuple((
+        tag_no_case(b"RENAME "),
+        mailbox,
+        sp,
+        mailbox,
+        opt(preceded(sp, tagged_ext_params)),
+    ));
 
-    let (remaining, (_, mailbox, _, new_mailbox)) = parser(input)?;
+    let (remaining, (_, mailbox, _, new_mailbox, parameters)) = parser(input)?;
 
     Ok((
         remaining,
         CommandBody::Rename {
             from: mailbox,
             to: new_mailbox,
+            parameters: parameters.unwrap_or_default(),
         },
     ))
 }
 
-/// `select = "SELECT" SP mailbox`
+/// `select = "SELECT" SP mailbox [select-params]`
 pub(crate) fn select(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    let mut parser = preceded(tag_no_case(b"SELECT "), mailbox);
+    let mut parser = tuple((
+        tag_no_case(b"SELECT "),
+        mailbox,
+        opt(preceded(sp, tagged_ext_params)),
+    ));
 
-    let (remaining, mailbox) = parser(input)?;
+    let (remaining, (_, mailbox, parameters)) = parser(input)?;
 
-    Ok((remaining, CommandBody::Select { mailbox }))
+    Ok((
+        remaining,
+        CommandBody::Select {
+            mailbox,
+            parameters: parameters.unwrap_or_default(),
+        },
+    ))
 }
 
 /// `status = "STATUS" SP mailbox SP "(" status-att *(SP status-att) ")"`
@@ -422,6 +479,10 @@ pub(crate) fn command_select(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
         thread,
         value(CommandBody::Unselect, tag_no_case(b"UNSELECT")),
         r#move,
+        replace,
+        convert,
+        #[cfg(feature = "ext_gmail")]
+        store_gmail_labels,
     ))(input)
 }
 
@@ -444,7 +505,8 @@ pub(crate) fn copy(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 /// `fetch = "FETCH" SP sequence-set SP ("ALL" /
 ///                                      "FULL" /
 ///                                      "FAST" /
-///                                      fetch-att / "(" fetch-att *(SP fetch-att) ")")`
+///                                      fetch-att / "(" fetch-att *(SP fetch-att) ")")
+///                                      [fetch-modifiers]`
 pub(crate) fn fetch(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     let mut parser = tuple((
         tag_no_case(b"FETCH"),
@@ -472,30 +534,40 @@ pub(crate) fn fetch(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
                 MacroOrMessageDataItemNames::MessageDataItemNames,
             ),
         )),
+        opt(preceded(sp, tagged_ext_params)),
     ));
 
-    let (remaining, (_, _, sequence_set, _, macro_or_item_names)) = parser(input)?;
+    let (remaining, (_, _, sequence_set, _, macro_or_item_names, modifiers)) = parser(input)?;
 
     Ok((
         remaining,
         CommandBody::Fetch {
             sequence_set,
             macro_or_item_names,
+            modifiers: modifiers.unwrap_or_default(),
             uid: false,
         },
     ))
 }
 
-/// `store = "STORE" SP sequence-set SP store-att-flags`
+/// `store = "STORE" SP sequence-set [store-modifiers] SP store-att-flags`
 pub(crate) fn store(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"STORE"), sp, sequence_set, sp, store_att_flags));
+    let mut parser = tuple((
+        tag_no_case(b"STORE"),
+        sp,
+        sequence_set,
+        opt(preceded(sp, tagged_ext_params)),
+        sp,
+        store_att_flags,
+    ));
 
-    let (remaining, (_, _, sequence_set, _, (kind, response, flags))) = parser(input)?;
+    let (remaining, (_, _, sequence_set, modifiers, _, (kind, response, flags))) = parser(input)?;
 
     Ok((
         remaining,
         CommandBody::Store {
             sequence_set,
+            modifiers: modifiers.unwrap_or_default(),
             kind,
             response,
             flags,
@@ -542,7 +614,17 @@ pub(crate) fn uid(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     let mut parser = tuple((
         tag_no_case(b"UID"),
         sp,
-        alt((copy, fetch, search, store, r#move)),
+        alt((
+            copy,
+            fetch,
+            search,
+            store,
+            r#move,
+            replace,
+            convert,
+            #[cfg(feature = "ext_gmail")]
+            store_gmail_labels,
+        )),
     ));
 
     let (remaining, (_, _, mut cmd)) = parser(input)?;
@@ -552,7 +634,11 @@ pub(crate) fn uid(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
         | CommandBody::Fetch { ref mut uid, .. }
         | CommandBody::Search { ref mut uid, .. }
         | CommandBody::Store { ref mut uid, .. }
-        | CommandBody::Move { ref mut uid, .. } => *uid = true,
+        | CommandBody::Move { ref mut uid, .. }
+        | CommandBody::Replace { ref mut uid, .. }
+        | CommandBody::Convert { ref mut uid, .. } => *uid = true,
+        #[cfg(feature = "ext_gmail")]
+        CommandBody::StoreGmailLabels { ref mut uid, .. } => *uid = true,
         _ => unreachable!(),
     }
 
@@ -589,6 +675,11 @@ mod tests {
             (MessageDataItemName::Body, "BODY???"),
             (MessageDataItemName::BodyStructure, "BODYSTRUCTURE???"),
             (MessageDataItemName::Uid, "UID???"),
+            (MessageDataItemName::Preview { lazy: false }, "PREVIEW???"),
+            (
+                MessageDataItemName::Preview { lazy: true },
+                "PREVIEW (LAZY)???",
+            ),
             (
                 MessageDataItemName::BodyExt {
                     partial: None,
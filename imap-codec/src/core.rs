@@ -291,6 +291,16 @@ pub(crate) fn tag_imap(input: &[u8]) -> IMAPResult<&[u8], Tag> {
     })(input)
 }
 
+/// `tag-string = string`
+///
+/// Note: The string must contain a valid `tag`.
+pub(crate) fn tag_string(input: &[u8]) -> IMAPResult<&[u8], Tag> {
+    map_res(string, |string| match string.into_inner() {
+        Cow::Borrowed(bytes) => Tag::try_from(bytes),
+        Cow::Owned(bytes) => Tag::try_from(bytes),
+    })(input)
+}
+
 // TODO: This could be exposed in a more elegant way...
 #[cfg(feature = "fuzz")]
 /// `tag = 1*<any ASTRING-CHAR except "+">`
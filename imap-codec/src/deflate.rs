@@ -0,0 +1,382 @@
+//! Raw DEFLATE transport compression (`COMPRESS=DEFLATE`).
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc4978>.
+//!
+//! After a successful `COMPRESS DEFLATE` (i.e., after the tagged `OK`), both peers compress all
+//! following octets with raw DEFLATE (no zlib header). The compression layer sits *below* IMAP,
+//! i.e., outgoing [`Fragment`]s are compressed after encoding and incoming bytes are decompressed
+//! before they are enqueued into a [`Fragmentizer`].
+//!
+//! IMAP is a line-at-a-time protocol. Thus, the [`Compressor`] flushes its output after every
+//! [`Fragment`] so that the peer can decompress (and act on) a fragment without waiting for more
+//! data.
+//!
+//! # Example
+//!
+//! ```rust
+//! use imap_codec::{
+//!     deflate::{Compressor, Decompressor},
+//!     encode::Encoder,
+//!     fragmentizer::Fragmentizer,
+//!     imap_types::command::{Command, CommandBody},
+//!     CommandCodec,
+//! };
+//!
+//! let mut compressor = Compressor::new();
+//! let mut decompressor = Decompressor::new();
+//! let mut fragmentizer = Fragmentizer::new(1024);
+//!
+//! // Client
+//! let command = Command::new("A1", CommandBody::Noop).unwrap();
+//! let mut compressed = Vec::new();
+//! for fragment in CommandCodec::default().encode(&command) {
+//!     compressed.extend(compressor.compress_fragment(&fragment).unwrap());
+//! }
+//!
+//! // Server
+//! decompressor
+//!     .decompress_into(&compressed, &mut fragmentizer)
+//!     .unwrap();
+//! fragmentizer.progress().unwrap();
+//! assert!(fragmentizer.is_message_complete());
+//! assert_eq!(fragmentizer.message_bytes(), b"A1 NOOP\r\n");
+//! ```
+//!
+//! [`Fragment`]: crate::encode::Fragment
+
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+
+use crate::{encode::Fragment, fragmentizer::Fragmentizer};
+
+/// Size by which the output buffer grows while (de)compressing.
+const CHUNK_SIZE: usize = 4096;
+
+/// Default maximum number of bytes a single [`Decompressor::decompress`] call may produce.
+pub const DEFAULT_MAX_OUTPUT_SIZE: usize = 16 * 1024 * 1024;
+
+/// Compresses outgoing bytes.
+#[derive(Debug)]
+pub struct Compressor {
+    inner: Compress,
+}
+
+impl Compressor {
+    /// Creates a `Compressor` using the default compression level.
+    pub fn new() -> Self {
+        Self::with_level(Compression::default().level())
+    }
+
+    /// Creates a `Compressor` using the given compression level.
+    ///
+    /// The level ranges from `0` (no compression) to `9` (best compression). Higher levels are
+    /// capped to `9`.
+    pub fn with_level(level: u32) -> Self {
+        Self {
+            inner: Compress::new(Compression::new(level.min(9)), false),
+        }
+    }
+
+    /// Compresses `data` and fully flushes the compressed stream.
+    ///
+    /// The returned bytes can be decompressed by the peer without waiting for any further data.
+    pub fn compress(&mut self, data: &[u8]) -> Result<Vec<u8>, DeflateError> {
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut output = Vec::with_capacity(data.len() + CHUNK_SIZE);
+        let mut consumed = 0;
+
+        loop {
+            let total_in = self.inner.total_in();
+
+            self.inner
+                .compress_vec(&data[consumed..], &mut output, FlushCompress::Full)
+                .map_err(|_| DeflateError::Compress)?;
+
+            consumed += (self.inner.total_in() - total_in) as usize;
+
+            // The flush is complete when all input was consumed and the output buffer was not
+            // filled up.
+            if consumed == data.len() && output.len() < output.capacity() {
+                return Ok(output);
+            }
+
+            output.reserve(CHUNK_SIZE);
+        }
+    }
+
+    /// Compresses a single [`Fragment`].
+    ///
+    /// Note: Literals using [`LiteralMode::Sync`](imap_types::core::LiteralMode::Sync) must still
+    /// only be sent after the continuation request was received.
+    pub fn compress_fragment(&mut self, fragment: &Fragment) -> Result<Vec<u8>, DeflateError> {
+        match fragment {
+            Fragment::Line { data } | Fragment::Literal { data, .. } => self.compress(data),
+        }
+    }
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decompresses incoming bytes.
+#[derive(Debug)]
+pub struct Decompressor {
+    inner: Decompress,
+    max_output_size: usize,
+}
+
+impl Decompressor {
+    /// Creates a `Decompressor` using [`DEFAULT_MAX_OUTPUT_SIZE`].
+    pub fn new() -> Self {
+        Self::with_max_output_size(DEFAULT_MAX_OUTPUT_SIZE)
+    }
+
+    /// Creates a `Decompressor` that produces at most `max_output_size` bytes per call.
+    ///
+    /// A few compressed bytes can expand to a huge amount of data. The limit bounds the memory
+    /// allocated by a single [`Decompressor::decompress`] call.
+    pub fn with_max_output_size(max_output_size: usize) -> Self {
+        Self {
+            inner: Decompress::new(false),
+            max_output_size,
+        }
+    }
+
+    /// Decompresses `data`.
+    ///
+    /// `data` doesn't need to end at a flush point. Bytes that can't be decompressed yet are kept
+    /// internally and are emitted with the next call.
+    ///
+    /// Returns [`DeflateError::OutputLimitExceeded`] when `data` decompresses to more than the
+    /// maximum output size. The compressed stream can't be continued after this error.
+    pub fn decompress(&mut self, data: &[u8]) -> Result<Vec<u8>, DeflateError> {
+        // One byte more than allowed, so that exceeding the limit can be detected.
+        let limit = self.max_output_size.saturating_add(1);
+
+        let mut output = Vec::with_capacity((data.len().saturating_mul(2) + CHUNK_SIZE).min(limit));
+        let mut consumed = 0;
+
+        loop {
+            let total_in = self.inner.total_in();
+
+            let status = self
+                .inner
+                .decompress_vec(&data[consumed..], &mut output, FlushDecompress::None)
+                .map_err(|_| DeflateError::Decompress)?;
+
+            consumed += (self.inner.total_in() - total_in) as usize;
+
+            if output.len() > self.max_output_size {
+                return Err(DeflateError::OutputLimitExceeded);
+            }
+
+            if status == Status::StreamEnd {
+                // The compression layer can't be turned off again.
+                return if consumed == data.len() {
+                    Ok(output)
+                } else {
+                    Err(DeflateError::Decompress)
+                };
+            }
+
+            if consumed == data.len() && output.len() < output.capacity() {
+                return Ok(output);
+            }
+
+            output.reserve(CHUNK_SIZE.min(limit - output.len()));
+        }
+    }
+
+    /// Decompresses `data` and enqueues the result into `fragmentizer`.
+    pub fn decompress_into(
+        &mut self,
+        data: &[u8],
+        fragmentizer: &mut Fragmentizer,
+    ) -> Result<(), DeflateError> {
+        let decompressed = self.decompress(data)?;
+        fragmentizer.enqueue_bytes(&decompressed);
+
+        Ok(())
+    }
+}
+
+impl Default for Decompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error during (de)compression.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DeflateError {
+    /// Compression failed.
+    Compress,
+
+    /// Decompression failed, e.g., because the compressed stream is corrupt.
+    Decompress,
+
+    /// Decompression produced more than the maximum output size.
+    OutputLimitExceeded,
+}
+
+impl Display for DeflateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Compress => f.write_str("Compression failed"),
+            Self::Decompress => f.write_str("Decompression failed"),
+            Self::OutputLimitExceeded => f.write_str("Decompressed data is too large"),
+        }
+    }
+}
+
+impl Error for DeflateError {}
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{Literal, LiteralMode},
+    };
+
+    use super::*;
+    use crate::{encode::Encoder, CommandCodec};
+
+    #[test]
+    fn test_decompress_zlib_stream() {
+        // Generated with zlib (`wbits = -15`, `Z_FULL_FLUSH` after each line).
+        let tests = [
+            (
+                b"\x72\x34\x54\xf0\xf3\xf7\x0f\xe0\xe5\x02\x00\x00\x00\xff\xff".as_ref(),
+                b"A1 NOOP\r\n".as_ref(),
+            ),
+            (
+                b"\x72\x34\x52\xf0\xf3\xf7\x0f\xe0\xe5\x02\x00\x00\x00\xff\xff".as_ref(),
+                b"A2 NOOP\r\n".as_ref(),
+            ),
+        ];
+
+        let mut decompressor = Decompressor::new();
+
+        for (test, expected) in tests {
+            assert_eq!(decompressor.decompress(test).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_fragment_is_flushed() {
+        let command = Command::new(
+            "A1",
+            CommandBody::login("alice", Literal::try_from("password").unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        let mut compressor = Compressor::new();
+        let mut decompressor = Decompressor::new();
+        let mut fragmentizer = Fragmentizer::new(1024);
+
+        for fragment in CommandCodec::default().encode(&command) {
+            let compressed = compressor.compress_fragment(&fragment).unwrap();
+
+            // The fragment must be fully available without any further data.
+            let (Fragment::Line { data } | Fragment::Literal { data, .. }) = &fragment;
+            assert_eq!(&decompressor.decompress(&compressed).unwrap(), data);
+
+            fragmentizer.enqueue_bytes(data);
+        }
+
+        fragmentizer.progress().unwrap();
+        fragmentizer.progress().unwrap();
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert_eq!(
+            fragmentizer
+                .decode_message(&CommandCodec::default())
+                .unwrap(),
+            command
+        );
+    }
+
+    #[test]
+    fn test_decompress_split() {
+        let mut compressor = Compressor::with_level(9);
+        let mut decompressor = Decompressor::new();
+        let mut fragmentizer = Fragmentizer::new(1024);
+
+        let mut compressed = compressor
+            .compress_fragment(&Fragment::Line {
+                data: b"A1 APPEND INBOX {3+}\r\n".to_vec(),
+            })
+            .unwrap();
+        compressed.extend(
+            compressor
+                .compress_fragment(&Fragment::Literal {
+                    data: b"abc".to_vec(),
+                    mode: LiteralMode::NonSync,
+                })
+                .unwrap(),
+        );
+        compressed.extend(compressor.compress(b"\r\n").unwrap());
+
+        // Feed the compressed stream byte-by-byte.
+        for byte in compressed {
+            decompressor
+                .decompress_into(&[byte], &mut fragmentizer)
+                .unwrap();
+        }
+
+        while fragmentizer.progress().is_some() {
+            if fragmentizer.is_message_complete() {
+                break;
+            }
+        }
+
+        assert!(fragmentizer.is_message_complete());
+        assert_eq!(
+            fragmentizer.message_bytes(),
+            b"A1 APPEND INBOX {3+}\r\nabc\r\n"
+        );
+    }
+
+    #[test]
+    fn test_decompress_output_limit() {
+        let data = vec![b'a'; 64 * 1024];
+        let compressed = Compressor::with_level(9).compress(&data).unwrap();
+        assert!(compressed.len() < 1024);
+
+        assert_eq!(
+            Decompressor::with_max_output_size(data.len())
+                .decompress(&compressed)
+                .unwrap(),
+            data
+        );
+        assert_eq!(
+            Decompressor::with_max_output_size(data.len() - 1).decompress(&compressed),
+            Err(DeflateError::OutputLimitExceeded)
+        );
+        assert_eq!(
+            Decompressor::with_max_output_size(0).decompress(&compressed),
+            Err(DeflateError::OutputLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn test_decompress_corrupt() {
+        let mut decompressor = Decompressor::new();
+
+        // Reserved block type (`BTYPE = 11`).
+        assert_eq!(
+            decompressor.decompress(b"\xff\xff\xff\xff"),
+            Err(DeflateError::Decompress)
+        );
+    }
+}
@@ -1,15 +1,26 @@
 pub mod binary;
 pub mod compress;
+pub mod convert;
 pub mod enable;
+pub mod esearch;
+pub mod fuzzy;
+#[cfg(feature = "ext_gmail")]
+pub mod gmail;
 #[cfg(feature = "ext_id")]
 pub mod id;
 pub mod idle;
 pub mod literal;
+pub mod messagelimit;
 #[cfg(feature = "ext_metadata")]
 pub mod metadata;
 pub mod r#move;
+pub mod multisearch;
 pub mod quota;
+pub mod replace;
 pub mod sort;
+pub mod tagged_ext;
 pub mod thread;
+pub mod uidonly;
 pub mod uidplus;
+pub mod unauthenticate;
 pub mod unselect;
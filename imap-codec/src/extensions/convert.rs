@@ -0,0 +1,460 @@
+//! IMAP CONVERT Extension
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc5259>.
+
+use std::{io::Write, num::NonZeroU32};
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    command::CommandBody,
+    core::{NString8, Tag, Vec1},
+    extensions::convert::Conversion,
+    fetch::{MessageDataItem, MessageDataItemName},
+    response::Code,
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    combinator::{map, opt, value},
+    multi::separated_list1,
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+};
+
+use crate::{
+    core::{nstring, nz_number, string, tag_string},
+    decode::IMAPResult,
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext},
+    extensions::binary::{literal8, partial},
+    fetch::{fetch_att, msg_att, section_part},
+    sequence::sequence_set,
+};
+
+/// ```abnf
+/// convert = "CONVERT" SP sequence-set SP "(" fetch-att *(SP fetch-att) ")"
+/// ```
+///
+/// Note: The UID variant (`"UID" SP convert`) is handled by `uid`.
+pub(crate) fn convert(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
+    let mut parser = tuple((
+        tag_no_case(b"CONVERT"),
+        sp,
+        sequence_set,
+        sp,
+        delimited(
+            tag(b"("),
+            map(separated_list1(sp, fetch_att), Vec1::unvalidated),
+            tag(b")"),
+        ),
+    ));
+
+    let (remaining, (_, _, sequence_set, _, item_names)) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::Convert {
+            sequence_set,
+            item_names,
+            uid: false,
+        },
+    ))
+}
+
+/// ```abnf
+/// conversion = "(" media-type [SP "(" [conv-param *(SP conv-param)] ")"] ")"
+///
+/// media-type = string
+///
+/// conv-param = string SP string
+/// ```
+pub(crate) fn conversion(input: &[u8]) -> IMAPResult<&[u8], Conversion> {
+    let mut parser = delimited(
+        tag(b"("),
+        tuple((
+            string,
+            opt(preceded(
+                sp,
+                delimited(
+                    tag(b"("),
+                    opt(separated_list1(sp, separated_pair(string, sp, string))),
+                    tag(b")"),
+                ),
+            )),
+        )),
+        tag(b")"),
+    );
+
+    let (remaining, (media_type, params)) = parser(input)?;
+
+    Ok((
+        remaining,
+        Conversion {
+            media_type,
+            params: params.flatten().unwrap_or_default(),
+        },
+    ))
+}
+
+/// ```abnf
+/// section-convert = "[" [section-part SP] conversion "]"
+/// ```
+pub(crate) fn section_convert(input: &[u8]) -> IMAPResult<&[u8], (Vec<NonZeroU32>, Conversion)> {
+    delimited(
+        tag(b"["),
+        tuple((
+            // We use `Vec<T>` instead of `Option<Vec1<T>>`.
+            map(opt(terminated(section_part, sp)), |section_part| {
+                section_part.map(|i| i.into_inner()).unwrap_or_default()
+            }),
+            conversion,
+        )),
+        tag(b"]"),
+    )(input)
+}
+
+/// ```abnf
+/// fetch-att =/ "BINARY" [".PEEK"] section-convert [partial]
+/// ```
+pub(crate) fn fetch_att_convert(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName> {
+    let mut parser = tuple((
+        tag_no_case(b"BINARY"),
+        map(opt(tag_no_case(b".PEEK")), |peek| peek.is_some()),
+        section_convert,
+        opt(partial),
+    ));
+
+    let (remaining, (_, peek, (section, conversion), partial)) = parser(input)?;
+
+    Ok((
+        remaining,
+        MessageDataItemName::BinaryConvert {
+            section,
+            conversion,
+            partial,
+            peek,
+        },
+    ))
+}
+
+/// ```abnf
+/// msg-att-static =/ "BINARY" section-convert SP (nstring / literal8)
+/// ```
+pub(crate) fn msg_att_convert(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem> {
+    let mut parser = tuple((
+        tag_no_case(b"BINARY"),
+        section_convert,
+        sp,
+        alt((
+            map(nstring, NString8::NString),
+            map(literal8, NString8::Literal8),
+        )),
+    ));
+
+    let (remaining, (_, (section, conversion), _, value)) = parser(input)?;
+
+    Ok((
+        remaining,
+        MessageDataItem::BinaryConvert {
+            section,
+            conversion,
+            value,
+        },
+    ))
+}
+
+/// ```abnf
+/// converted-data = "CONVERTED" SP "(" "TAG" SP tag-string ")" SP msg-att
+///
+/// tag-string = string
+/// ```
+///
+/// Note: This parser is called after `nz-number SP`.
+pub(crate) fn converted_data(input: &[u8]) -> IMAPResult<&[u8], (Tag, Vec1<MessageDataItem>)> {
+    let mut parser = tuple((
+        tag_no_case(b"CONVERTED ("),
+        tag_no_case(b"TAG "),
+        tag_string,
+        tag(b") "),
+        msg_att,
+    ));
+
+    let (remaining, (_, _, tag, _, items)) = parser(input)?;
+
+    Ok((remaining, (tag, items)))
+}
+
+/// ```abnf
+/// resp-text-code =/ "BADPARAMS" /
+///                   "NOCONVERT" /
+///                   "MAXCONVERTMESSAGES" SP nz-number /
+///                   "MAXCONVERTPARTS" SP nz-number
+/// ```
+pub(crate) fn resp_code_convert(input: &[u8]) -> IMAPResult<&[u8], Code> {
+    alt((
+        value(Code::BadParams, tag_no_case(b"BADPARAMS")),
+        value(Code::NoConvert, tag_no_case(b"NOCONVERT")),
+        map(
+            preceded(tag_no_case(b"MAXCONVERTMESSAGES "), nz_number),
+            Code::MaxConvertMessages,
+        ),
+        map(
+            preceded(tag_no_case(b"MAXCONVERTPARTS "), nz_number),
+            Code::MaxConvertParts,
+        ),
+    ))(input)
+}
+
+impl<'a> EncodeIntoContext for Conversion<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        ctx.write_all(b"(")?;
+        self.media_type.encode_ctx(ctx)?;
+
+        if !self.params.is_empty() {
+            ctx.write_all(b" (")?;
+            for (i, (name, value)) in self.params.iter().enumerate() {
+                if i > 0 {
+                    ctx.write_all(b" ")?;
+                }
+                name.encode_ctx(ctx)?;
+                ctx.write_all(b" ")?;
+                value.encode_ctx(ctx)?;
+            }
+            ctx.write_all(b")")?;
+        }
+
+        ctx.write_all(b")")
+    }
+}
+
+pub(crate) fn encode_section_convert(
+    section: &[NonZeroU32],
+    conversion: &Conversion,
+    ctx: &mut EncodeContext,
+) -> std::io::Result<()> {
+    ctx.write_all(b"[")?;
+    if !section.is_empty() {
+        join_serializable(section, b".", ctx)?;
+        ctx.write_all(b" ")?;
+    }
+    conversion.encode_ctx(ctx)?;
+    ctx.write_all(b"]")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{IString, Literal, LiteralMode, NString, NString8, Tag, Vec1},
+        extensions::{binary::Literal8, convert::Conversion},
+        fetch::{MessageDataItem, MessageDataItemName},
+        response::{Capability, Code, Data, Response, Status},
+    };
+
+    use crate::testing::{kat_inverse_command, kat_inverse_response};
+
+    fn jpeg_240x180() -> Conversion<'static> {
+        Conversion::new(
+            "image/jpeg",
+            vec![
+                (
+                    IString::try_from("pix-x").unwrap(),
+                    IString::try_from("240").unwrap(),
+                ),
+                (
+                    IString::try_from("pix-y").unwrap(),
+                    IString::try_from("180").unwrap(),
+                ),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_kat_inverse_command_convert() {
+        kat_inverse_command(&[
+            (
+                b"A CONVERT 2 (BINARY[3 (\"image/jpeg\" (\"pix-x\" \"240\" \"pix-y\" \"180\"))])\r\n"
+                    .as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::convert(
+                        2,
+                        vec![MessageDataItemName::BinaryConvert {
+                            section: vec![NonZeroU32::new(3).unwrap()],
+                            conversion: jpeg_240x180(),
+                            partial: None,
+                            peek: false,
+                        }],
+                        false,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A UID CONVERT 1:* (UID BINARY.PEEK[1.2 (\"text/plain;charset=utf-8\")]<0.1024>)\r\n?",
+                b"?",
+                Command::new(
+                    "A",
+                    CommandBody::convert(
+                        "1:*",
+                        vec![
+                            MessageDataItemName::Uid,
+                            MessageDataItemName::BinaryConvert {
+                                section: vec![
+                                    NonZeroU32::new(1).unwrap(),
+                                    NonZeroU32::new(2).unwrap(),
+                                ],
+                                conversion: Conversion::new("text/plain;charset=utf-8", vec![])
+                                    .unwrap(),
+                                partial: Some((0, NonZeroU32::new(1024).unwrap())),
+                                peek: true,
+                            },
+                        ],
+                        true,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A UID FETCH 1 (BINARY[(\"text/plain\")])\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::fetch(
+                        1,
+                        vec![MessageDataItemName::BinaryConvert {
+                            section: vec![],
+                            conversion: Conversion::new("text/plain", vec![]).unwrap(),
+                            partial: None,
+                            peek: false,
+                        }],
+                        true,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_convert() {
+        kat_inverse_response(&[
+            (
+                b"* CAPABILITY IMAP4REV1 BINARY CONVERT\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Capability(
+                    Vec1::try_from(vec![
+                        Capability::Imap4Rev1,
+                        Capability::Binary,
+                        Capability::Convert,
+                    ])
+                    .unwrap(),
+                )),
+            ),
+            (
+                b"* 2 CONVERTED (TAG \"A\") (UID 1234 BINARY[3 (\"image/jpeg\" (\"pix-x\" \"240\" \"pix-y\" \"180\"))] ~{4}\r\n\xff\xd8\x00\x01)\r\n",
+                b"",
+                Response::Data(
+                    Data::converted(
+                        2,
+                        Tag::try_from("A").unwrap(),
+                        vec![
+                            MessageDataItem::Uid(NonZeroU32::new(1234).unwrap()),
+                            MessageDataItem::BinaryConvert {
+                                section: vec![NonZeroU32::new(3).unwrap()],
+                                conversion: jpeg_240x180(),
+                                value: NString8::Literal8(Literal8 {
+                                    data: b"\xff\xd8\x00\x01".as_ref().into(),
+                                    mode: LiteralMode::Sync,
+                                }),
+                            },
+                        ],
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"* 3 CONVERTED (TAG {2}\r\nA1) (UID 1)\r\n",
+                b"",
+                Response::Data(
+                    Data::converted(
+                        3,
+                        Tag::try_from("A1").unwrap(),
+                        vec![MessageDataItem::Uid(NonZeroU32::new(1).unwrap())],
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"* 1 FETCH (BINARY[(\"text/plain\")] {5}\r\nHello)\r\n",
+                b"",
+                Response::Data(
+                    Data::fetch(
+                        1,
+                        vec![MessageDataItem::BinaryConvert {
+                            section: vec![],
+                            conversion: Conversion::new("text/plain", vec![]).unwrap(),
+                            value: NString8::NString(NString::from(
+                                Literal::try_from("Hello").unwrap(),
+                            )),
+                        }],
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"A NO [BADPARAMS] Unsupported conversion parameter\r\n",
+                b"",
+                Response::Status(
+                    Status::no(
+                        Some(Tag::try_from("A").unwrap()),
+                        Some(Code::BadParams),
+                        "Unsupported conversion parameter",
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"A NO [NOCONVERT] Can't convert to image/webp\r\n",
+                b"",
+                Response::Status(
+                    Status::no(
+                        Some(Tag::try_from("A").unwrap()),
+                        Some(Code::NoConvert),
+                        "Can't convert to image/webp",
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"* OK [MAXCONVERTMESSAGES 10] Limit\r\n",
+                b"",
+                Response::Status(
+                    Status::ok(
+                        None,
+                        Some(Code::MaxConvertMessages(NonZeroU32::new(10).unwrap())),
+                        "Limit",
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"* OK [MAXCONVERTPARTS 3] Limit\r\n",
+                b"",
+                Response::Status(
+                    Status::ok(
+                        None,
+                        Some(Code::MaxConvertParts(NonZeroU32::new(3).unwrap())),
+                        "Limit",
+                    )
+                    .unwrap(),
+                ),
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,213 @@
+//! IMAP4 Extension to SEARCH Command for Controlling What Kind of Information Is Returned (ESEARCH)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc4731>.
+
+use std::io::Write;
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    extensions::{
+        esearch::{SearchCorrelator, SearchReturnData, SearchReturnOption},
+        fuzzy::RelevancyScore,
+    },
+    response::Data,
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    combinator::{map, map_opt, opt, value},
+    multi::{many0, separated_list0},
+    sequence::{delimited, preceded, tuple},
+};
+
+use crate::{
+    core::{number, nz_number, tag_string},
+    decode::IMAPResult,
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext},
+    mailbox::mailbox,
+    sequence::sequence_set,
+};
+
+/// ```abnf
+/// search-return-opts = SP "RETURN" SP "(" [search-return-opt *(SP search-return-opt)] ")"
+/// ```
+///
+/// Note: The leading SP is not parsed.
+pub(crate) fn search_return_opts(input: &[u8]) -> IMAPResult<&[u8], Vec<SearchReturnOption>> {
+    delimited(
+        tag_no_case(b"RETURN ("),
+        separated_list0(sp, search_return_opt),
+        tag(b")"),
+    )(input)
+}
+
+/// ```abnf
+/// search-return-opt = "MIN" / "MAX" / "ALL" / "COUNT"
+///
+/// search-return-opt =/ "RELEVANCY" ; RFC 6203
+/// ```
+pub(crate) fn search_return_opt(input: &[u8]) -> IMAPResult<&[u8], SearchReturnOption> {
+    alt((
+        value(SearchReturnOption::Min, tag_no_case(b"MIN")),
+        value(SearchReturnOption::Max, tag_no_case(b"MAX")),
+        value(SearchReturnOption::All, tag_no_case(b"ALL")),
+        value(SearchReturnOption::Count, tag_no_case(b"COUNT")),
+        value(SearchReturnOption::Relevancy, tag_no_case(b"RELEVANCY")),
+    ))(input)
+}
+
+/// ```abnf
+/// esearch-response = "ESEARCH" [search-correlator] [SP "UID"] *(SP search-return-data)
+/// ```
+pub(crate) fn esearch_response(input: &[u8]) -> IMAPResult<&[u8], Data> {
+    let mut parser = tuple((
+        tag_no_case(b"ESEARCH"),
+        opt(preceded(sp, search_correlator)),
+        map(opt(tag_no_case(b" UID")), |uid| uid.is_some()),
+        many0(preceded(sp, search_return_data)),
+    ));
+
+    let (remaining, (_, correlator, uid, data)) = parser(input)?;
+
+    Ok((
+        remaining,
+        Data::ESearch {
+            correlator,
+            uid,
+            data,
+        },
+    ))
+}
+
+/// ```abnf
+/// search-correlator = SP "(" "TAG" SP tag-string
+///                     [SP "MAILBOX" SP astring
+///                      SP "UIDVALIDITY" SP nz-number] ")"
+///                      ; Redefined by RFC 7377
+/// ```
+///
+/// Note: The leading SP is not parsed.
+pub(crate) fn search_correlator(input: &[u8]) -> IMAPResult<&[u8], SearchCorrelator> {
+    let mut parser = tuple((
+        tag_no_case(b"(TAG "),
+        tag_string,
+        opt(tuple((
+            tag_no_case(b" MAILBOX "),
+            mailbox,
+            tag_no_case(b" UIDVALIDITY "),
+            nz_number,
+        ))),
+        tag(b")"),
+    ));
+
+    let (remaining, (_, tag, mailbox, _)) = parser(input)?;
+
+    Ok((
+        remaining,
+        SearchCorrelator {
+            tag,
+            mailbox: mailbox.map(|(_, mailbox, _, uid_validity)| (mailbox, uid_validity)),
+        },
+    ))
+}
+
+/// ```abnf
+/// search-return-data = "MIN" SP nz-number /
+///                      "MAX" SP nz-number /
+///                      "ALL" SP sequence-set /
+///                      "COUNT" SP number
+///
+/// search-return-data =/ "RELEVANCY" SP "(" relevancy-score *(SP relevancy-score) ")" ; RFC 6203
+/// ```
+///
+/// Note: An empty `RELEVANCY ()` is accepted, e.g., when no message matched.
+pub(crate) fn search_return_data(input: &[u8]) -> IMAPResult<&[u8], SearchReturnData> {
+    alt((
+        map(
+            preceded(tag_no_case(b"MIN "), nz_number),
+            SearchReturnData::Min,
+        ),
+        map(
+            preceded(tag_no_case(b"MAX "), nz_number),
+            SearchReturnData::Max,
+        ),
+        map(
+            preceded(tag_no_case(b"ALL "), sequence_set),
+            SearchReturnData::All,
+        ),
+        map(
+            preceded(tag_no_case(b"COUNT "), number),
+            SearchReturnData::Count,
+        ),
+        map(
+            delimited(
+                tag_no_case(b"RELEVANCY ("),
+                separated_list0(sp, relevancy_score),
+                tag(b")"),
+            ),
+            SearchReturnData::Relevancy,
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// relevancy-score = nz-number ; Maximum 100
+/// ```
+pub(crate) fn relevancy_score(input: &[u8]) -> IMAPResult<&[u8], RelevancyScore> {
+    map_opt(number, |score| {
+        RelevancyScore::try_from(u8::try_from(score).ok()?).ok()
+    })(input)
+}
+
+impl EncodeIntoContext for SearchReturnOption {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        match self {
+            Self::Min => ctx.write_all(b"MIN"),
+            Self::Max => ctx.write_all(b"MAX"),
+            Self::All => ctx.write_all(b"ALL"),
+            Self::Count => ctx.write_all(b"COUNT"),
+            Self::Relevancy => ctx.write_all(b"RELEVANCY"),
+        }
+    }
+}
+
+impl<'a> EncodeIntoContext for SearchCorrelator<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        ctx.write_all(b"(TAG \"")?;
+        self.tag.encode_ctx(ctx)?;
+        ctx.write_all(b"\"")?;
+
+        if let Some((mailbox, uid_validity)) = &self.mailbox {
+            ctx.write_all(b" MAILBOX ")?;
+            mailbox.encode_ctx(ctx)?;
+            write!(ctx, " UIDVALIDITY {uid_validity}")?;
+        }
+
+        ctx.write_all(b")")
+    }
+}
+
+impl EncodeIntoContext for RelevancyScore {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        write!(ctx, "{self}")
+    }
+}
+
+impl EncodeIntoContext for SearchReturnData {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        match self {
+            Self::Min(min) => write!(ctx, "MIN {min}"),
+            Self::Max(max) => write!(ctx, "MAX {max}"),
+            Self::All(sequence_set) => {
+                ctx.write_all(b"ALL ")?;
+                sequence_set.encode_ctx(ctx)
+            }
+            Self::Count(count) => write!(ctx, "COUNT {count}"),
+            Self::Relevancy(scores) => {
+                ctx.write_all(b"RELEVANCY (")?;
+                join_serializable(scores.as_ref(), b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{AString, Tag, Vec1},
+        extensions::{
+            esearch::{SearchCorrelator, SearchReturnData, SearchReturnOption},
+            fuzzy::RelevancyScore,
+        },
+        response::{Capability, Data, Response},
+        search::SearchKey,
+        sequence::SequenceSet,
+    };
+
+    use crate::{
+        decode::Decoder,
+        testing::{kat_inverse_command, kat_inverse_response},
+        CommandCodec, ResponseCodec,
+    };
+
+    #[test]
+    fn test_kat_inverse_command_fuzzy() {
+        kat_inverse_command(&[
+            (
+                b"A SEARCH FUZZY SUBJECT imap\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::search(
+                        None,
+                        Vec1::from(SearchKey::Fuzzy(Box::new(SearchKey::Subject(
+                            AString::try_from("imap").unwrap(),
+                        )))),
+                        false,
+                    ),
+                )
+                .unwrap(),
+            ),
+            (
+                b"B ESEARCH RETURN (RELEVANCY ALL) FUZZY TEXT hello\r\n",
+                b"",
+                Command::new(
+                    "B",
+                    CommandBody::esearch(
+                        None,
+                        vec![SearchReturnOption::Relevancy, SearchReturnOption::All],
+                        None,
+                        Vec1::from(SearchKey::Fuzzy(Box::new(SearchKey::Text(
+                            AString::try_from("hello").unwrap(),
+                        )))),
+                    ),
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_fuzzy() {
+        kat_inverse_response(&[
+            (
+                b"* CAPABILITY IMAP4REV1 SEARCH=FUZZY\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Capability(
+                    Vec1::try_from(vec![Capability::Imap4Rev1, Capability::SearchFuzzy]).unwrap(),
+                )),
+            ),
+            (
+                b"* ESEARCH (TAG \"B\") UID ALL 1,5:6 RELEVANCY (4 99 100)\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    correlator: Some(SearchCorrelator {
+                        tag: Tag::try_from("B").unwrap(),
+                        mailbox: None,
+                    }),
+                    uid: true,
+                    data: vec![
+                        SearchReturnData::All(SequenceSet::try_from("1,5:6").unwrap()),
+                        SearchReturnData::Relevancy(vec![
+                            RelevancyScore::try_from(4).unwrap(),
+                            RelevancyScore::try_from(99).unwrap(),
+                            RelevancyScore::try_from(100).unwrap(),
+                        ]),
+                    ],
+                }),
+            ),
+            (
+                b"* ESEARCH (TAG \"C\") UID RELEVANCY ()\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    correlator: Some(SearchCorrelator {
+                        tag: Tag::try_from("C").unwrap(),
+                        mailbox: None,
+                    }),
+                    uid: true,
+                    data: vec![SearchReturnData::Relevancy(vec![])],
+                }),
+            ),
+            (
+                b"* ESEARCH MAX 7\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    correlator: None,
+                    uid: false,
+                    data: vec![SearchReturnData::Max(NonZeroU32::new(7).unwrap())],
+                }),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_decode_fuzzy_limits() {
+        // Relevancy scores must be in `1..=100`.
+        for test in [
+            b"* ESEARCH RELEVANCY (0)\r\n".as_ref(),
+            b"* ESEARCH RELEVANCY (101)\r\n",
+        ] {
+            assert!(ResponseCodec::default().decode(test).is_err());
+        }
+
+        // `FUZZY` counts towards the search recursion limit.
+        let mut command = b"A SEARCH ".to_vec();
+        command.extend_from_slice(&b"FUZZY ".repeat(16));
+        command.extend_from_slice(b"SEEN\r\n");
+        assert!(CommandCodec::default().decode(&command).is_err());
+    }
+}
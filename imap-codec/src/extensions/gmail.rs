@@ -0,0 +1,449 @@
+//! Gmail IMAP Extensions (`X-GM-EXT-1`)
+//!
+//! See <https://developers.google.com/gmail/imap/imap-extensions>.
+
+use std::{borrow::Cow, io::Write};
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    command::CommandBody,
+    core::{AString, Atom, IString, Literal, Quoted},
+    extensions::gmail::GmailLabel,
+    fetch::{MessageDataItem, MessageDataItemName},
+    flag::{StoreResponse, StoreType},
+    search::SearchKey,
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    combinator::{map, map_opt, opt, value},
+    multi::separated_list1,
+    sequence::{delimited, preceded, tuple},
+};
+
+use crate::{
+    core::{astring, atom, number64},
+    decode::IMAPResult,
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext},
+    sequence::sequence_set,
+};
+
+/// ```abnf
+/// gm-label = "\" atom / astring
+/// ```
+///
+/// Note: System labels, e.g., `\Inbox`, are prefixed with a backslash. This includes quoted
+/// labels, e.g., `"\\Inbox"` or `"\\Muy Importante"`.
+pub(crate) fn gm_label(input: &[u8]) -> IMAPResult<&[u8], GmailLabel> {
+    alt((
+        map(preceded(tag(b"\\"), atom), |atom| {
+            GmailLabel::System(atom.into())
+        }),
+        map_opt(astring, |astring| match astring {
+            AString::String(string) if string.as_ref().starts_with(b"\\") => {
+                let system = match string.into_inner() {
+                    Cow::Borrowed(value) => AString::try_from(&value[1..]),
+                    Cow::Owned(value) => AString::try_from(value[1..].to_vec()),
+                };
+
+                match system {
+                    Ok(system) if !system.as_ref().is_empty() => Some(GmailLabel::System(system)),
+                    _ => None,
+                }
+            }
+            astring => Some(GmailLabel::User(astring)),
+        }),
+    ))(input)
+}
+
+/// ```abnf
+/// gm-label-list = "(" [gm-label *(SP gm-label)] ")"
+/// ```
+pub(crate) fn gm_label_list(input: &[u8]) -> IMAPResult<&[u8], Vec<GmailLabel>> {
+    map(
+        delimited(tag(b"("), opt(separated_list1(sp, gm_label)), tag(b")")),
+        Option::unwrap_or_default,
+    )(input)
+}
+
+/// ```abnf
+/// fetch-att =/ "X-GM-MSGID" / "X-GM-THRID" / "X-GM-LABELS"
+/// ```
+pub(crate) fn fetch_att_gmail(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName> {
+    alt((
+        value(
+            MessageDataItemName::GmailMessageId,
+            tag_no_case(b"X-GM-MSGID"),
+        ),
+        value(
+            MessageDataItemName::GmailThreadId,
+            tag_no_case(b"X-GM-THRID"),
+        ),
+        value(
+            MessageDataItemName::GmailLabels,
+            tag_no_case(b"X-GM-LABELS"),
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// msg-att-static =/ "X-GM-MSGID" SP number64 /
+///                   "X-GM-THRID" SP number64 /
+///                   "X-GM-LABELS" SP gm-label-list
+/// ```
+pub(crate) fn msg_att_gmail(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem> {
+    alt((
+        map(
+            preceded(tag_no_case(b"X-GM-MSGID "), number64),
+            MessageDataItem::GmailMessageId,
+        ),
+        map(
+            preceded(tag_no_case(b"X-GM-THRID "), number64),
+            MessageDataItem::GmailThreadId,
+        ),
+        map(
+            preceded(tag_no_case(b"X-GM-LABELS "), gm_label_list),
+            MessageDataItem::GmailLabels,
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// store-gm-labels = "STORE" SP sequence-set SP
+///                   ["+" / "-"] "X-GM-LABELS" [".SILENT"] SP
+///                   (gm-label-list / (gm-label *(SP gm-label)))
+/// ```
+pub(crate) fn store_gmail_labels(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
+    let mut parser = tuple((
+        tag_no_case(b"STORE"),
+        sp,
+        sequence_set,
+        sp,
+        map(
+            opt(alt((
+                value(StoreType::Add, tag(b"+")),
+                value(StoreType::Remove, tag(b"-")),
+            ))),
+            |kind| kind.unwrap_or(StoreType::Replace),
+        ),
+        tag_no_case(b"X-GM-LABELS"),
+        map(opt(tag_no_case(b".SILENT")), |silent| match silent {
+            Some(_) => StoreResponse::Silent,
+            None => StoreResponse::Answer,
+        }),
+        sp,
+        alt((gm_label_list, separated_list1(sp, gm_label))),
+    ));
+
+    let (remaining, (_, _, sequence_set, _, kind, _, response, _, labels)) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::StoreGmailLabels {
+            sequence_set,
+            kind,
+            response,
+            labels,
+            uid: false,
+        },
+    ))
+}
+
+/// ```abnf
+/// search-key =/ "X-GM-RAW" SP astring /
+///               "X-GM-MSGID" SP number64 /
+///               "X-GM-THRID" SP number64 /
+///               "X-GM-LABELS" SP gm-label
+/// ```
+pub(crate) fn search_key_gmail(input: &[u8]) -> IMAPResult<&[u8], SearchKey> {
+    alt((
+        map(
+            preceded(tag_no_case(b"X-GM-RAW "), astring),
+            SearchKey::GmailRaw,
+        ),
+        map(
+            preceded(tag_no_case(b"X-GM-MSGID "), number64),
+            SearchKey::GmailMessageId,
+        ),
+        map(
+            preceded(tag_no_case(b"X-GM-THRID "), number64),
+            SearchKey::GmailThreadId,
+        ),
+        map(
+            preceded(tag_no_case(b"X-GM-LABELS "), gm_label),
+            SearchKey::GmailLabels,
+        ),
+    ))(input)
+}
+
+impl<'a> EncodeIntoContext for GmailLabel<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        match self {
+            GmailLabel::System(AString::Atom(atom)) if Atom::validate(atom.inner()).is_ok() => {
+                ctx.write_all(b"\\")?;
+                atom.encode_ctx(ctx)
+            }
+            // Labels that aren't atoms, e.g., `\Muy Importante`, are sent as strings.
+            GmailLabel::System(AString::Atom(atom)) => {
+                Quoted::unvalidated(format!("\\{}", atom.inner())).encode_ctx(ctx)
+            }
+            GmailLabel::System(AString::String(IString::Quoted(quoted))) => {
+                Quoted::unvalidated(format!("\\{}", quoted.inner())).encode_ctx(ctx)
+            }
+            GmailLabel::System(AString::String(IString::Literal(literal))) => {
+                let mut data = b"\\".to_vec();
+                data.extend_from_slice(literal.data());
+
+                let mut system = Literal::unvalidated(data);
+                system.set_mode(literal.mode());
+                system.encode_ctx(ctx)
+            }
+            GmailLabel::User(astring) => astring.encode_ctx(ctx),
+        }
+    }
+}
+
+pub(crate) fn encode_gm_label_list(
+    labels: &[GmailLabel],
+    ctx: &mut EncodeContext,
+) -> std::io::Result<()> {
+    ctx.write_all(b"(")?;
+    join_serializable(labels, b" ", ctx)?;
+    ctx.write_all(b")")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{AString, Literal, Vec1},
+        extensions::gmail::GmailLabel,
+        fetch::{MessageDataItem, MessageDataItemName},
+        flag::{StoreResponse, StoreType},
+        response::{Capability, Data, Response},
+        search::SearchKey,
+    };
+
+    use crate::testing::{kat_inverse_command, kat_inverse_response, known_answer_test_encode};
+
+    #[test]
+    fn test_kat_inverse_command_fetch_gmail() {
+        kat_inverse_command(&[(
+            b"A UID FETCH 1:* (X-GM-MSGID X-GM-THRID X-GM-LABELS)\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::fetch(
+                    "1:*",
+                    vec![
+                        MessageDataItemName::GmailMessageId,
+                        MessageDataItemName::GmailThreadId,
+                        MessageDataItemName::GmailLabels,
+                    ],
+                    true,
+                )
+                .unwrap(),
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_kat_inverse_command_store_gmail_labels() {
+        kat_inverse_command(&[
+            (
+                b"A STORE 1 +X-GM-LABELS (\\Inbox foo \"Muy Importante\")\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::store_gmail_labels(
+                        1,
+                        StoreType::Add,
+                        StoreResponse::Answer,
+                        vec![
+                            GmailLabel::try_from("\\Inbox").unwrap(),
+                            GmailLabel::try_from("foo").unwrap(),
+                            GmailLabel::try_from("Muy Importante").unwrap(),
+                        ],
+                        false,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A UID STORE 1:5 -X-GM-LABELS.SILENT \\Starred\r\n?",
+                b"?",
+                Command::new(
+                    "A",
+                    CommandBody::store_gmail_labels(
+                        "1:5",
+                        StoreType::Remove,
+                        StoreResponse::Silent,
+                        vec![GmailLabel::System(AString::try_from("Starred").unwrap())],
+                        true,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A STORE 1 -X-GM-LABELS (\"\\\\Inbox\" \"\\\\Muy Importante\")\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::store_gmail_labels(
+                        1,
+                        StoreType::Remove,
+                        StoreResponse::Answer,
+                        vec![
+                            GmailLabel::try_from("\\Inbox").unwrap(),
+                            GmailLabel::try_from("\\Muy Importante").unwrap(),
+                        ],
+                        false,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A STORE 1 X-GM-LABELS (\"foo\\\\bar\" {6}\r\n\\Inbox)\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::store_gmail_labels(
+                        1,
+                        StoreType::Replace,
+                        StoreResponse::Answer,
+                        vec![
+                            GmailLabel::user(AString::try_from("foo\\bar").unwrap()).unwrap(),
+                            GmailLabel::System(AString::from(Literal::try_from("Inbox").unwrap())),
+                        ],
+                        false,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A STORE 1 X-GM-LABELS ()\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::store_gmail_labels(
+                        1,
+                        StoreType::Replace,
+                        StoreResponse::Answer,
+                        vec![],
+                        false,
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_kat_inverse_command_search_gmail() {
+        kat_inverse_command(&[(
+            b"A UID SEARCH X-GM-RAW \"has:attachment in:unread\" X-GM-MSGID 1278455344230334865 X-GM-THRID 1266894439832287888 X-GM-LABELS \\Important\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::search(
+                    None,
+                    Vec1::try_from(vec![
+                        SearchKey::GmailRaw(AString::try_from("has:attachment in:unread").unwrap()),
+                        SearchKey::GmailMessageId(1278455344230334865),
+                        SearchKey::GmailThreadId(1266894439832287888),
+                        SearchKey::GmailLabels(GmailLabel::try_from("\\Important").unwrap()),
+                    ])
+                    .unwrap(),
+                    true,
+                ),
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_encode_gmail_label() {
+        let tests = [
+            (
+                GmailLabel::try_from("\\Inbox").unwrap(),
+                b"\\Inbox".as_ref(),
+            ),
+            (
+                GmailLabel::try_from("\\Muy Importante").unwrap(),
+                b"\"\\\\Muy Importante\"",
+            ),
+            (
+                GmailLabel::System(AString::try_from("Foo]").unwrap()),
+                b"\"\\\\Foo]\"",
+            ),
+            (
+                GmailLabel::System(AString::from(Literal::try_from("Draft").unwrap())),
+                b"{6}\r\n\\Draft",
+            ),
+            (GmailLabel::try_from("Work").unwrap(), b"Work"),
+        ];
+
+        for test in tests {
+            known_answer_test_encode(test);
+        }
+    }
+
+    #[test]
+    fn test_kat_inverse_response_gmail() {
+        kat_inverse_response(&[
+            (
+                b"* CAPABILITY IMAP4REV1 X-GM-EXT-1\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Capability(
+                    Vec1::try_from(vec![Capability::Imap4Rev1, Capability::GmailExt1]).unwrap(),
+                )),
+            ),
+            (
+                b"* 1 FETCH (X-GM-THRID 1266894439832287888 X-GM-MSGID 1278455344230334865 X-GM-LABELS (\\Inbox \\Sent Important \"Muy Importante\"))\r\n",
+                b"",
+                Response::Data(Data::Fetch {
+                    seq: NonZeroU32::new(1).unwrap(),
+                    items: Vec1::try_from(vec![
+                        MessageDataItem::GmailThreadId(1266894439832287888),
+                        MessageDataItem::GmailMessageId(1278455344230334865),
+                        MessageDataItem::GmailLabels(vec![
+                            GmailLabel::try_from("\\Inbox").unwrap(),
+                            GmailLabel::try_from("\\Sent").unwrap(),
+                            GmailLabel::try_from("Important").unwrap(),
+                            GmailLabel::try_from("Muy Importante").unwrap(),
+                        ]),
+                    ])
+                    .unwrap(),
+                }),
+            ),
+            (
+                b"* 3 FETCH (X-GM-LABELS (\"\\\\Inbox\" \"\\\\Muy Importante\" {6}\r\n\\Draft))\r\n",
+                b"",
+                Response::Data(Data::Fetch {
+                    seq: NonZeroU32::new(3).unwrap(),
+                    items: Vec1::from(MessageDataItem::GmailLabels(vec![
+                        GmailLabel::try_from("\\Inbox").unwrap(),
+                        GmailLabel::try_from("\\Muy Importante").unwrap(),
+                        GmailLabel::try_from("\\Draft").unwrap(),
+                    ])),
+                }),
+            ),
+            (
+                b"* 2 FETCH (X-GM-LABELS ())\r\n",
+                b"",
+                Response::Data(Data::Fetch {
+                    seq: NonZeroU32::new(2).unwrap(),
+                    items: Vec1::from(MessageDataItem::GmailLabels(vec![])),
+                }),
+            ),
+        ]);
+    }
+}
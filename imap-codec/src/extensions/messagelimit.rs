@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        core::{Tag, Vec1},
+        response::{Capability, Code, Data, Response, Status},
+    };
+
+    use crate::testing::kat_inverse_response;
+
+    #[test]
+    fn test_kat_inverse_response_messagelimit() {
+        kat_inverse_response(&[
+            (
+                b"* CAPABILITY IMAP4REV1 MESSAGELIMIT=1000 SAVELIMIT=4294967295\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Capability(
+                    Vec1::try_from(vec![
+                        Capability::Imap4Rev1,
+                        Capability::MessageLimit(NonZeroU32::new(1000).unwrap()),
+                        Capability::SaveLimit(NonZeroU32::new(u32::MAX).unwrap()),
+                    ])
+                    .unwrap(),
+                )),
+            ),
+            (
+                b"A OK [LIMIT] FETCH completed with 1000 partial results\r\n",
+                b"",
+                Response::Status(
+                    Status::ok(
+                        Some(Tag::try_from("A").unwrap()),
+                        Some(Code::Limit),
+                        "FETCH completed with 1000 partial results",
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"B NO [LIMIT] Too many messages to process\r\n",
+                b"",
+                Response::Status(
+                    Status::no(
+                        Some(Tag::try_from("B").unwrap()),
+                        Some(Code::Limit),
+                        "Too many messages to process",
+                    )
+                    .unwrap(),
+                ),
+            ),
+        ]);
+    }
+}
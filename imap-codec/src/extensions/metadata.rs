@@ -311,15 +311,16 @@ impl<'a> EncodeIntoContext for EntryValue<'a> {
 mod tests {
     use imap_types::{
         command::{Command, CommandBody},
-        core::{AString, IString, Literal, LiteralMode, NString, NString8, Text, Vec1},
+        core::{AString, Atom, IString, Literal, LiteralMode, NString, NString8, Text, Vec1},
         extensions::{
             binary::Literal8,
+            imapsieve::ScriptScope,
             metadata::{
                 Depth, Entry, EntryValue, GetMetadataOption, MetadataCode, MetadataResponse,
             },
         },
         mailbox::{Mailbox, MailboxOther},
-        response::{Code, Data, Response, Status, StatusBody, StatusKind},
+        response::{Capability, Code, Data, Response, Status, StatusBody, StatusKind},
     };
 
     use crate::testing::{kat_inverse_command, kat_inverse_response};
@@ -589,4 +590,66 @@ mod tests {
             ),
         ]);
     }
+
+    #[test]
+    fn test_kat_inverse_imapsieve() {
+        kat_inverse_command(&[
+            (
+                b"A SETMETADATA INBOX (/private/imapsieve/script \"spam-training\")\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::set_imapsieve_script(
+                        "INBOX",
+                        ScriptScope::Private,
+                        Some("spam-training"),
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A SETMETADATA Junk (/shared/imapsieve/script NIL)\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::set_imapsieve_script::<_, &str>("Junk", ScriptScope::Shared, None)
+                        .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A GETMETADATA Junk (/shared/imapsieve/script /private/imapsieve/script)\r\n"
+                    .as_ref(),
+                b"".as_ref(),
+                Command::new("A", CommandBody::get_imapsieve_scripts("Junk").unwrap()).unwrap(),
+            ),
+        ]);
+
+        kat_inverse_response(&[
+            (
+                b"* CAPABILITY IMAP4REV1 METADATA IMAPSIEVE=sieve://sieve.example.com\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Capability(
+                    Vec1::try_from(vec![
+                        Capability::Imap4Rev1,
+                        Capability::Metadata,
+                        Capability::ImapSieve(Atom::try_from("sieve://sieve.example.com").unwrap()),
+                    ])
+                    .unwrap(),
+                )),
+            ),
+            (
+                b"* METADATA Junk (/shared/imapsieve/script \"spam-training\")\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Metadata {
+                    mailbox: Mailbox::try_from("Junk").unwrap(),
+                    items: MetadataResponse::WithValues(Vec1::from(
+                        EntryValue::imapsieve_script(ScriptScope::Shared, Some("spam-training"))
+                            .unwrap(),
+                    )),
+                }),
+            ),
+        ]);
+    }
 }
@@ -0,0 +1,332 @@
+//! IMAP4 Multimailbox SEARCH Extension (MULTISEARCH)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc7377>.
+
+use std::io::Write;
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    command::CommandBody, core::Vec1, extensions::multisearch::FilterMailboxes, mailbox::Mailbox,
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    combinator::{map, opt, value},
+    multi::separated_list1,
+    sequence::{delimited, preceded, tuple},
+};
+
+use crate::{
+    core::charset,
+    decode::IMAPResult,
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext},
+    extensions::esearch::search_return_opts,
+    mailbox::mailbox,
+    search::search_key,
+};
+
+/// ```abnf
+/// esearch = "ESEARCH" [SP esearch-source-opts] [SP search-return-opts] SP search-program
+///
+/// search-program = ["CHARSET" SP charset SP] search-key *(SP search-key)
+/// ```
+pub(crate) fn esearch(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
+    let mut parser = tuple((
+        tag_no_case(b"ESEARCH"),
+        opt(preceded(sp, esearch_source_opts)),
+        opt(preceded(sp, search_return_opts)),
+        opt(preceded(tag_no_case(b" CHARSET "), charset)),
+        sp,
+        map(separated_list1(sp, search_key(9)), Vec1::unvalidated),
+    ));
+
+    let (remaining, (_, source, return_options, charset, _, criteria)) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::ESearch {
+            source,
+            return_options: return_options.unwrap_or_default(),
+            charset,
+            criteria,
+        },
+    ))
+}
+
+/// ```abnf
+/// esearch-source-opts = "IN" SP "(" source-mbox ")"
+///
+/// source-mbox = filter-mailboxes *(SP filter-mailboxes)
+/// ```
+pub(crate) fn esearch_source_opts(input: &[u8]) -> IMAPResult<&[u8], Vec1<FilterMailboxes>> {
+    delimited(
+        tag_no_case(b"IN ("),
+        map(separated_list1(sp, filter_mailboxes), Vec1::unvalidated),
+        tag(b")"),
+    )(input)
+}
+
+/// ```abnf
+/// filter-mailboxes = filter-mailboxes-selected /
+///                    filter-mailboxes-other
+///
+/// filter-mailboxes-selected = "selected"
+///
+/// filter-mailboxes-other = "inboxes" /
+///                          "personal" /
+///                          "subscribed" /
+///                          ("subtree" SP one-or-more-mailbox) /
+///                          ("subtree-one" SP one-or-more-mailbox) /
+///                          ("mailboxes" SP one-or-more-mailbox)
+/// ```
+pub(crate) fn filter_mailboxes(input: &[u8]) -> IMAPResult<&[u8], FilterMailboxes> {
+    alt((
+        value(FilterMailboxes::Selected, tag_no_case(b"selected")),
+        value(FilterMailboxes::Inboxes, tag_no_case(b"inboxes")),
+        value(FilterMailboxes::Personal, tag_no_case(b"personal")),
+        value(FilterMailboxes::Subscribed, tag_no_case(b"subscribed")),
+        map(
+            preceded(tag_no_case(b"subtree-one "), one_or_more_mailbox),
+            FilterMailboxes::SubtreeOne,
+        ),
+        map(
+            preceded(tag_no_case(b"subtree "), one_or_more_mailbox),
+            FilterMailboxes::Subtree,
+        ),
+        map(
+            preceded(tag_no_case(b"mailboxes "), one_or_more_mailbox),
+            FilterMailboxes::Mailboxes,
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// one-or-more-mailbox = mailbox / many-mailboxes
+///
+/// many-mailboxes = "(" mailbox *(SP mailbox) ")"
+/// ```
+pub(crate) fn one_or_more_mailbox(input: &[u8]) -> IMAPResult<&[u8], Vec1<Mailbox>> {
+    alt((
+        map(
+            delimited(tag(b"("), separated_list1(sp, mailbox), tag(b")")),
+            Vec1::unvalidated,
+        ),
+        map(mailbox, Vec1::from),
+    ))(input)
+}
+
+impl<'a> EncodeIntoContext for FilterMailboxes<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        let (name, mailboxes) = match self {
+            Self::Selected => return ctx.write_all(b"selected"),
+            Self::Inboxes => return ctx.write_all(b"inboxes"),
+            Self::Personal => return ctx.write_all(b"personal"),
+            Self::Subscribed => return ctx.write_all(b"subscribed"),
+            Self::Subtree(mailboxes) => ("subtree", mailboxes),
+            Self::SubtreeOne(mailboxes) => ("subtree-one", mailboxes),
+            Self::Mailboxes(mailboxes) => ("mailboxes", mailboxes),
+        };
+
+        write!(ctx, "{name} ")?;
+
+        match mailboxes.as_ref() {
+            [mailbox] => mailbox.encode_ctx(ctx),
+            mailboxes => {
+                ctx.write_all(b"(")?;
+                join_serializable(mailboxes, b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{AString, Charset, Tag, Vec1},
+        extensions::{
+            esearch::{SearchCorrelator, SearchReturnData, SearchReturnOption},
+            multisearch::FilterMailboxes,
+        },
+        mailbox::Mailbox,
+        response::{Capability, Data, Response},
+        search::SearchKey,
+        sequence::SequenceSet,
+    };
+
+    use crate::{
+        extensions::esearch::search_correlator,
+        testing::{kat_inverse_command, kat_inverse_response, known_answer_test_parse},
+    };
+
+    #[test]
+    fn test_kat_inverse_command_esearch() {
+        kat_inverse_command(&[
+            (
+                b"A ESEARCH IN (selected personal inboxes subscribed) RETURN (MIN MAX ALL COUNT) UNSEEN\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::esearch(
+                        Some(
+                            Vec1::try_from(vec![
+                                FilterMailboxes::Selected,
+                                FilterMailboxes::Personal,
+                                FilterMailboxes::Inboxes,
+                                FilterMailboxes::Subscribed,
+                            ])
+                            .unwrap(),
+                        ),
+                        vec![
+                            SearchReturnOption::Min,
+                            SearchReturnOption::Max,
+                            SearchReturnOption::All,
+                            SearchReturnOption::Count,
+                        ],
+                        None,
+                        Vec1::from(SearchKey::Unseen),
+                    ),
+                )
+                .unwrap(),
+            ),
+            (
+                b"B ESEARCH IN (subtree (Work Archive) subtree-one INBOX mailboxes Sent) CHARSET UTF-8 SUBJECT foo\r\n",
+                b"",
+                Command::new(
+                    "B",
+                    CommandBody::esearch(
+                        Some(
+                            Vec1::try_from(vec![
+                                FilterMailboxes::Subtree(
+                                    Vec1::try_from(vec![
+                                        Mailbox::try_from("Work").unwrap(),
+                                        Mailbox::try_from("Archive").unwrap(),
+                                    ])
+                                    .unwrap(),
+                                ),
+                                FilterMailboxes::SubtreeOne(Vec1::from(Mailbox::Inbox)),
+                                FilterMailboxes::Mailboxes(Vec1::from(
+                                    Mailbox::try_from("Sent").unwrap(),
+                                )),
+                            ])
+                            .unwrap(),
+                        ),
+                        vec![],
+                        Some(Charset::try_from("UTF-8").unwrap()),
+                        Vec1::from(SearchKey::Subject(AString::try_from("foo").unwrap())),
+                    ),
+                )
+                .unwrap(),
+            ),
+            (
+                b"C ESEARCH DELETED SEEN\r\n",
+                b"",
+                Command::new(
+                    "C",
+                    CommandBody::esearch(
+                        None,
+                        vec![],
+                        None,
+                        Vec1::try_from(vec![SearchKey::Deleted, SearchKey::Seen]).unwrap(),
+                    ),
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_esearch() {
+        kat_inverse_response(&[
+            (
+                b"* CAPABILITY IMAP4REV1 MULTISEARCH\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Capability(
+                    Vec1::try_from(vec![Capability::Imap4Rev1, Capability::MultiSearch]).unwrap(),
+                )),
+            ),
+            (
+                b"* ESEARCH (TAG \"A\" MAILBOX Sent UIDVALIDITY 1) UID MIN 2 MAX 47 COUNT 3 ALL 2,10:47\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    correlator: Some(SearchCorrelator {
+                        tag: Tag::try_from("A").unwrap(),
+                        mailbox: Some((
+                            Mailbox::try_from("Sent").unwrap(),
+                            NonZeroU32::new(1).unwrap(),
+                        )),
+                    }),
+                    uid: true,
+                    data: vec![
+                        SearchReturnData::Min(NonZeroU32::new(2).unwrap()),
+                        SearchReturnData::Max(NonZeroU32::new(47).unwrap()),
+                        SearchReturnData::Count(3),
+                        SearchReturnData::All(SequenceSet::try_from("2,10:47").unwrap()),
+                    ],
+                }),
+            ),
+            (
+                b"* ESEARCH (TAG \"A\") UID COUNT 0\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    correlator: Some(SearchCorrelator {
+                        tag: Tag::try_from("A").unwrap(),
+                        mailbox: None,
+                    }),
+                    uid: true,
+                    data: vec![SearchReturnData::Count(0)],
+                }),
+            ),
+            (
+                b"* ESEARCH COUNT 5\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    correlator: None,
+                    uid: false,
+                    data: vec![SearchReturnData::Count(5)],
+                }),
+            ),
+            (
+                b"* ESEARCH\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    correlator: None,
+                    uid: false,
+                    data: vec![],
+                }),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_search_correlator() {
+        let tests = [
+            (
+                b"(TAG \"A1\")".as_ref(),
+                b"".as_ref(),
+                SearchCorrelator {
+                    tag: Tag::try_from("A1").unwrap(),
+                    mailbox: None,
+                },
+            ),
+            // `tag-string` is a `string`, i.e., it can also be a literal.
+            (
+                b"(TAG {2}\r\nA1 MAILBOX INBOX UIDVALIDITY 7)",
+                b"",
+                SearchCorrelator {
+                    tag: Tag::try_from("A1").unwrap(),
+                    mailbox: Some((Mailbox::Inbox, NonZeroU32::new(7).unwrap())),
+                },
+            ),
+        ];
+
+        for test in tests {
+            known_answer_test_parse(test, search_correlator);
+        }
+
+        assert!(search_correlator(b"(TAG \"A+\")").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! IMAP - REPLACE Extension
+
+use abnf_core::streaming::sp;
+use imap_types::{command::CommandBody, extensions::binary::LiteralOrLiteral8};
+use nom::{
+    branch::alt,
+    bytes::streaming::tag_no_case,
+    combinator::{map, opt},
+    sequence::{preceded, tuple},
+};
+
+use crate::{
+    core::literal, datetime::date_time, decode::IMAPResult, extensions::binary::literal8,
+    flag::flag_list, mailbox::mailbox, sequence::seq_number,
+};
+
+/// ```abnf
+/// replace = "REPLACE" SP seq-number SP mailbox [SP flag-list] [SP date-time] SP literal
+/// ```
+///
+/// Note: The UID variant (`uid-replace = "UID" SP replace`) is handled by `uid`.
+pub(crate) fn replace(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
+    let mut parser = tuple((
+        tag_no_case(b"REPLACE"),
+        sp,
+        seq_number,
+        sp,
+        mailbox,
+        opt(preceded(sp, flag_list)),
+        opt(preceded(sp, date_time)),
+        sp,
+        alt((
+            map(literal, LiteralOrLiteral8::Literal),
+            map(literal8, LiteralOrLiteral8::Literal8),
+        )),
+    ));
+
+    let (remaining, (_, _, sequence, _, mailbox, flags, date, _, message)) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::Replace {
+            sequence,
+            mailbox,
+            flags: flags.unwrap_or_default(),
+            date,
+            message,
+            uid: false,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{Literal, LiteralMode, Tag, Text},
+        extensions::binary::{Literal8, LiteralOrLiteral8},
+        flag::Flag,
+        mailbox::Mailbox,
+        response::{Code, Response, Status, StatusBody, StatusKind, Tagged},
+        sequence::SeqOrUid,
+    };
+
+    use crate::{
+        decode::{CommandDecodeError, Decoder},
+        encode::{Encoder, Fragment},
+        testing::{kat_inverse_command, kat_inverse_response},
+        CommandCodec,
+    };
+
+    #[test]
+    fn test_kat_inverse_command_replace() {
+        kat_inverse_command(&[
+            (
+                b"A REPLACE 1 Drafts {5}\r\nHello\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::replace(1, "Drafts", vec![], None, "Hello", false).unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                b"A UID REPLACE 42 Drafts (\\Seen \\Draft) {5+}\r\nHello\r\n?",
+                b"?",
+                Command::new(
+                    "A",
+                    CommandBody::Replace {
+                        sequence: SeqOrUid::Value(NonZeroU32::new(42).unwrap()),
+                        mailbox: Mailbox::try_from("Drafts").unwrap(),
+                        flags: vec![Flag::Seen, Flag::Draft],
+                        date: None,
+                        message: LiteralOrLiteral8::Literal(
+                            Literal::try_from("Hello").unwrap().into_non_sync(),
+                        ),
+                        uid: true,
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A REPLACE * INBOX ~{3}\r\n\x00\x01\x02\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::Replace {
+                        sequence: SeqOrUid::Asterisk,
+                        mailbox: Mailbox::Inbox,
+                        flags: vec![],
+                        date: None,
+                        message: LiteralOrLiteral8::Literal8(Literal8 {
+                            data: b"\x00\x01\x02".as_ref().into(),
+                            mode: LiteralMode::Sync,
+                        }),
+                        uid: false,
+                    },
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_decode_command_replace_literal() {
+        let got = CommandCodec::default().decode(b"A REPLACE 1 Drafts {5}\r\n");
+
+        assert_eq!(
+            got,
+            Err(CommandDecodeError::LiteralFound {
+                tag: Tag::try_from("A").unwrap(),
+                length: 5,
+                mode: LiteralMode::Sync,
+            })
+        );
+    }
+
+    #[test]
+    fn test_encode_command_replace_fragments() {
+        let tests = [
+            (
+                LiteralMode::Sync,
+                [
+                    Fragment::Line {
+                        data: b"A UID REPLACE 42 Drafts {5}\r\n".to_vec(),
+                    },
+                    Fragment::Literal {
+                        data: b"Hello".to_vec(),
+                        mode: LiteralMode::Sync,
+                    },
+                    Fragment::Line {
+                        data: b"\r\n".to_vec(),
+                    },
+                ],
+            ),
+            (
+                LiteralMode::NonSync,
+                [
+                    Fragment::Line {
+                        data: b"A UID REPLACE 42 Drafts {5+}\r\n".to_vec(),
+                    },
+                    Fragment::Literal {
+                        data: b"Hello".to_vec(),
+                        mode: LiteralMode::NonSync,
+                    },
+                    Fragment::Line {
+                        data: b"\r\n".to_vec(),
+                    },
+                ],
+            ),
+        ];
+
+        for (mode, expected) in tests {
+            let mut literal = Literal::try_from("Hello").unwrap();
+            literal.set_mode(mode);
+
+            let command = Command::new(
+                "A",
+                CommandBody::Replace {
+                    sequence: SeqOrUid::Value(NonZeroU32::new(42).unwrap()),
+                    mailbox: Mailbox::try_from("Drafts").unwrap(),
+                    flags: vec![],
+                    date: None,
+                    message: LiteralOrLiteral8::Literal(literal),
+                    uid: true,
+                },
+            )
+            .unwrap();
+
+            let got = CommandCodec::default().encode(&command).collect::<Vec<_>>();
+
+            assert_eq!(got, expected);
+        }
+    }
+
+    #[test]
+    fn test_kat_inverse_response_replace_appenduid() {
+        kat_inverse_response(&[(
+            b"A OK [APPENDUID 38505 3955] REPLACE completed\r\n".as_ref(),
+            b"".as_ref(),
+            Response::Status(Status::Tagged(Tagged {
+                tag: Tag::try_from("A").unwrap(),
+                body: StatusBody {
+                    kind: StatusKind::Ok,
+                    code: Some(Code::AppendUid {
+                        uid_validity: NonZeroU32::new(38505).unwrap(),
+                        uid: NonZeroU32::new(3955).unwrap(),
+                    }),
+                    text: Text::try_from("REPLACE completed").unwrap(),
+                },
+            })),
+        )]);
+    }
+}
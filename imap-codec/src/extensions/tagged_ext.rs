@@ -0,0 +1,759 @@
+//! Collected Extensions to IMAP4 ABNF (tagged-ext)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc4466> and <https://datatracker.ietf.org/doc/html/rfc5258>.
+
+use std::io::Write;
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    core::Vec1,
+    extensions::tagged_ext::{
+        ListExtendedItem, ListOption, TaggedExt, TaggedExtComp, TaggedExtItem, TaggedExtItemVal,
+        TaggedExtLabel, TaggedExtParam, TaggedExtSimple, TaggedExtVal,
+    },
+    fetch::{MessageDataItem, MessageDataItemOther},
+    sequence::{SeqOrUid, Sequence},
+    status::{StatusDataItem, StatusDataItemOther},
+    utils::indicators::{is_tagged_label_char, is_tagged_label_fchar},
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, take_while, take_while_m_n},
+    combinator::{map, map_opt, opt, recognize},
+    multi::{separated_list0, separated_list1},
+    sequence::{delimited, preceded, tuple},
+};
+
+use crate::{
+    core::{astring, atom, nstring, number64},
+    decode::{IMAPErrorKind, IMAPParseError, IMAPResult},
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext},
+    sequence::sequence_set,
+};
+
+/// ```abnf
+/// tagged-ext-label   = tagged-label-fchar *tagged-label-char
+/// tagged-label-fchar = ALPHA / "-" / "_" / "."
+/// tagged-label-char  = tagged-label-fchar / DIGIT / ":"
+/// ```
+pub(crate) fn tagged_ext_label(input: &[u8]) -> IMAPResult<&[u8], TaggedExtLabel> {
+    map(
+        recognize(tuple((
+            take_while_m_n(1, 1, is_tagged_label_fchar),
+            take_while(is_tagged_label_char),
+        ))),
+        |bytes: &[u8]| {
+            // # Safety
+            //
+            // `unwrap` is safe here, because `is_tagged_label_(f)char` enforces that the bytes ...
+            //   * contain ASCII-only characters, i.e., `from_utf8` will return `Ok`.
+            //   * are valid according to `TaggedExtLabel::validate()`, i.e., `unvalidated` is safe.
+            TaggedExtLabel::unvalidated(std::str::from_utf8(bytes).unwrap())
+        },
+    )(input)
+}
+
+/// `tagged-ext = tagged-ext-label SP tagged-ext-val`
+pub(crate) fn tagged_ext(input: &[u8]) -> IMAPResult<&[u8], TaggedExt> {
+    let mut parser = tuple((tagged_ext_label, sp, tagged_ext_val));
+
+    let (remaining, (label, _, value)) = parser(input)?;
+
+    Ok((remaining, TaggedExt { label, value }))
+}
+
+/// ```abnf
+/// select-param   = select-param-name [SP select-param-value]
+/// rename-param   = rename-param-name [SP rename-param-value]
+/// fetch-modifier = fetch-modifier-name [SP fetch-modif-params]
+/// store-modifier = store-modifier-name [SP store-modif-params]
+/// ```
+pub(crate) fn tagged_ext_param(input: &[u8]) -> IMAPResult<&[u8], TaggedExtParam> {
+    let mut parser = tuple((tagged_ext_label, opt(preceded(sp, tagged_ext_val))));
+
+    let (remaining, (name, value)) = parser(input)?;
+
+    Ok((remaining, TaggedExtParam { name, value }))
+}
+
+/// ```abnf
+/// select-params   = SP "(" select-param *(SP select-param) ")"
+/// rename-params   = SP "(" rename-param *(SP rename-param) ")"
+/// fetch-modifiers = SP "(" fetch-modifier *(SP fetch-modifier) ")"
+/// store-modifiers = SP "(" store-modifier *(SP store-modifier) ")"
+/// ```
+///
+/// Note: The leading SP is not parsed.
+pub(crate) fn tagged_ext_params(input: &[u8]) -> IMAPResult<&[u8], Vec<TaggedExtParam>> {
+    delimited(tag(b"("), separated_list1(sp, tagged_ext_param), tag(b")"))(input)
+}
+
+/// ```abnf
+/// tagged-ext-val = tagged-ext-simple /
+///                  "(" [tagged-ext-comp] ")"
+/// ```
+pub(crate) fn tagged_ext_val(input: &[u8]) -> IMAPResult<&[u8], TaggedExtVal> {
+    alt((
+        map(tagged_ext_simple, TaggedExtVal::Simple),
+        map(
+            delimited(
+                tag(b"("),
+                separated_list0(sp, tagged_ext_comp(8)),
+                tag(b")"),
+            ),
+            TaggedExtVal::Comp,
+        ),
+    ))(input)
+}
+
+/// `tagged-ext-simple = sequence-set / number / number64`
+///
+/// Note: A sequence set consisting of a single number is parsed as a number.
+pub(crate) fn tagged_ext_simple(input: &[u8]) -> IMAPResult<&[u8], TaggedExtSimple> {
+    alt((
+        map(sequence_set, |sequence_set| match sequence_set.0.as_ref() {
+            [Sequence::Single(SeqOrUid::Value(value))] => {
+                TaggedExtSimple::Number(value.get().into())
+            }
+            _ => TaggedExtSimple::SequenceSet(sequence_set),
+        }),
+        map(number64, TaggedExtSimple::Number),
+    ))(input)
+}
+
+/// ```abnf
+/// tagged-ext-comp = astring /
+///                   tagged-ext-comp *(SP tagged-ext-comp) /
+///                   "(" tagged-ext-comp ")"
+/// ```
+///
+/// This parser is recursively defined. Thus, in order to not overflow the stack,
+/// it is needed to limit how may recursions are allowed.
+pub(crate) fn tagged_ext_comp(
+    remaining_recursions: usize,
+) -> impl Fn(&[u8]) -> IMAPResult<&[u8], TaggedExtComp> {
+    move |input: &[u8]| tagged_ext_comp_limited(input, remaining_recursions)
+}
+
+fn tagged_ext_comp_limited<'a>(
+    input: &'a [u8],
+    remaining_recursion: usize,
+) -> IMAPResult<&'a [u8], TaggedExtComp> {
+    if remaining_recursion == 0 {
+        return Err(nom::Err::Failure(IMAPParseError {
+            input,
+            kind: IMAPErrorKind::RecursionLimitExceeded,
+        }));
+    }
+
+    let tagged_ext_comp = move |input: &'a [u8]| {
+        tagged_ext_comp_limited(input, remaining_recursion.saturating_sub(1))
+    };
+
+    alt((
+        map(astring, TaggedExtComp::AString),
+        map(
+            delimited(tag(b"("), separated_list1(sp, tagged_ext_comp), tag(b")")),
+            |comps| TaggedExtComp::List(Vec1::unvalidated(comps)),
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// list-select-opts = "(" [list-select-option *(SP list-select-option)] ")"
+/// list-return-opts = "RETURN" SP "(" [return-option *(SP return-option)] ")"
+/// ```
+///
+/// Note: The `RETURN` keyword is not parsed.
+pub(crate) fn list_options(input: &[u8]) -> IMAPResult<&[u8], Vec<ListOption>> {
+    delimited(tag(b"("), separated_list0(sp, list_option), tag(b")"))(input)
+}
+
+/// ```abnf
+/// option-extension = (option-standard-tag / option-vendor-tag) [SP option-value]
+/// option-value     = "(" option-val-comp ")"
+/// option-val-comp  = astring /
+///                    option-val-comp *(SP option-val-comp) /
+///                    "(" option-val-comp ")"
+/// ```
+pub(crate) fn list_option(input: &[u8]) -> IMAPResult<&[u8], ListOption> {
+    let mut parser = tuple((
+        atom,
+        opt(preceded(
+            sp,
+            delimited(
+                tag(b"("),
+                separated_list1(sp, tagged_ext_comp(8)),
+                tag(b")"),
+            ),
+        )),
+    ));
+
+    let (remaining, (name, value)) = parser(input)?;
+
+    Ok((
+        remaining,
+        ListOption {
+            name,
+            value: value.map(Vec1::unvalidated),
+        },
+    ))
+}
+
+/// ```abnf
+/// mbox-list-extended = "(" [mbox-list-extended-item *(SP mbox-list-extended-item)] ")"
+/// ```
+pub(crate) fn mbox_list_extended(input: &[u8]) -> IMAPResult<&[u8], Vec<ListExtendedItem>> {
+    delimited(
+        tag(b"("),
+        separated_list0(sp, mbox_list_extended_item),
+        tag(b")"),
+    )(input)
+}
+
+/// `mbox-list-extended-item = mbox-list-extended-item-tag SP tagged-ext-val`
+pub(crate) fn mbox_list_extended_item(input: &[u8]) -> IMAPResult<&[u8], ListExtendedItem> {
+    let mut parser = tuple((astring, sp, tagged_ext_val));
+
+    let (remaining, (tag, _, value)) = parser(input)?;
+
+    Ok((remaining, ListExtendedItem { tag, value }))
+}
+
+/// ```abnf
+/// tagged-ext-item = tagged-ext-label SP (tagged-ext-val / nstring) ; simplified
+/// ```
+pub(crate) fn tagged_ext_item(input: &[u8]) -> IMAPResult<&[u8], TaggedExtItem> {
+    let mut parser = tuple((
+        tagged_ext_label,
+        sp,
+        alt((
+            map(tagged_ext_val, TaggedExtItemVal::TaggedExtVal),
+            map(nstring, TaggedExtItemVal::NString),
+        )),
+    ));
+
+    let (remaining, (label, _, value)) = parser(input)?;
+
+    Ok((remaining, TaggedExtItem { label, value }))
+}
+
+/// Parses a message data item of an unknown extension.
+///
+/// Note: Known message data items are rejected.
+pub(crate) fn msg_att_other(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem> {
+    map_opt(tagged_ext_item, |item| {
+        MessageDataItemOther::try_from(item)
+            .ok()
+            .map(MessageDataItem::Other)
+    })(input)
+}
+
+/// Parses a status data item of an unknown extension.
+///
+/// Note: Known status data items are rejected.
+pub(crate) fn status_att_val_other(input: &[u8]) -> IMAPResult<&[u8], StatusDataItem> {
+    map_opt(tagged_ext_item, |item| {
+        StatusDataItemOther::try_from(item)
+            .ok()
+            .map(StatusDataItem::Other)
+    })(input)
+}
+
+/// Encodes `params` as ` (<param> <param> ...)`. Nothing is encoded when `params` is empty.
+pub(crate) fn encode_tagged_ext_params(
+    params: &[TaggedExtParam],
+    ctx: &mut EncodeContext,
+) -> std::io::Result<()> {
+    if !params.is_empty() {
+        ctx.write_all(b" (")?;
+        join_serializable(params, b" ", ctx)?;
+        ctx.write_all(b")")?;
+    }
+
+    Ok(())
+}
+
+impl<'a> EncodeIntoContext for TaggedExtLabel<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        ctx.write_all(self.inner().as_bytes())
+    }
+}
+
+impl<'a> EncodeIntoContext for TaggedExt<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        self.label.encode_ctx(ctx)?;
+        ctx.write_all(b" ")?;
+        self.value.encode_ctx(ctx)
+    }
+}
+
+impl<'a> EncodeIntoContext for TaggedExtItem<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        self.label.encode_ctx(ctx)?;
+        ctx.write_all(b" ")?;
+        self.value.encode_ctx(ctx)
+    }
+}
+
+impl<'a> EncodeIntoContext for TaggedExtItemVal<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        match self {
+            Self::TaggedExtVal(value) => value.encode_ctx(ctx),
+            Self::NString(nstring) => nstring.encode_ctx(ctx),
+        }
+    }
+}
+
+impl<'a> EncodeIntoContext for TaggedExtParam<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        self.name.encode_ctx(ctx)?;
+
+        if let Some(value) = &self.value {
+            ctx.write_all(b" ")?;
+            value.encode_ctx(ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> EncodeIntoContext for TaggedExtVal<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        match self {
+            Self::Simple(simple) => simple.encode_ctx(ctx),
+            Self::Comp(comps) => {
+                ctx.write_all(b"(")?;
+                join_serializable(comps, b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+        }
+    }
+}
+
+impl EncodeIntoContext for TaggedExtSimple {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        match self {
+            Self::SequenceSet(sequence_set) => sequence_set.encode_ctx(ctx),
+            Self::Number(number) => write!(ctx, "{number}"),
+        }
+    }
+}
+
+impl<'a> EncodeIntoContext for TaggedExtComp<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        match self {
+            Self::AString(astring) => astring.encode_ctx(ctx),
+            Self::List(comps) => {
+                ctx.write_all(b"(")?;
+                join_serializable(comps.as_ref(), b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+        }
+    }
+}
+
+impl<'a> EncodeIntoContext for ListOption<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        self.name.encode_ctx(ctx)?;
+
+        if let Some(value) = &self.value {
+            ctx.write_all(b" (")?;
+            join_serializable(value.as_ref(), b" ", ctx)?;
+            ctx.write_all(b")")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> EncodeIntoContext for ListExtendedItem<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        self.tag.encode_ctx(ctx)?;
+        ctx.write_all(b" ")?;
+        self.value.encode_ctx(ctx)
+    }
+}
+
+impl<'a> EncodeIntoContext for MessageDataItemOther<'a> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        self.inner().encode_ctx(ctx)
+    }
+}
+
+impl EncodeIntoContext for StatusDataItemOther {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        self.inner().encode_ctx(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{AString, Atom, IString, Literal, NString, Quoted},
+        extensions::binary::LiteralOrLiteral8,
+        fetch::{Macro, MacroOrMessageDataItemNames, MessageDataItemName},
+        flag::{Flag, StoreResponse, StoreType},
+        mailbox::Mailbox,
+        response::{Data, Response},
+        sequence::SequenceSet,
+    };
+
+    use super::*;
+    use crate::testing::{kat_inverse_command, kat_inverse_response};
+
+    fn label(label: &str) -> TaggedExtLabel {
+        TaggedExtLabel::try_from(label).unwrap()
+    }
+
+    fn number(number: u64) -> TaggedExtVal<'static> {
+        TaggedExtVal::Simple(TaggedExtSimple::Number(number))
+    }
+
+    fn comp(astring: &str) -> TaggedExtComp {
+        TaggedExtComp::AString(AString::try_from(astring).unwrap())
+    }
+
+    #[test]
+    fn test_parse_tagged_ext_val() {
+        let tests = [
+            (b"0)".as_ref(), number(0)),
+            (b"4294967296)", number(4294967296)),
+            (b"12)", number(12)),
+            (
+                b"1:5,7)",
+                TaggedExtVal::Simple(TaggedExtSimple::SequenceSet(
+                    SequenceSet::try_from("1:5,7").unwrap(),
+                )),
+            ),
+            (b"())", TaggedExtVal::Comp(vec![])),
+            (
+                b"(a \"b c\" (d (e)))",
+                TaggedExtVal::Comp(vec![
+                    comp("a"),
+                    comp("b c"),
+                    TaggedExtComp::List(Vec1::unvalidated(vec![
+                        comp("d"),
+                        TaggedExtComp::List(Vec1::from(comp("e"))),
+                    ])),
+                ]),
+            ),
+        ];
+
+        for (test, expected) in tests {
+            let (remaining, got) = tagged_ext_val(test).unwrap();
+            assert_eq!(remaining, b")");
+            assert_eq!(got, expected);
+        }
+
+        assert!(tagged_ext_val(b"((((((((((a))))))))))").is_err());
+    }
+
+    #[test]
+    fn test_kat_inverse_command_tagged_ext() {
+        kat_inverse_command(&[
+            (
+                b"A SELECT INBOX (CONDSTORE)\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::Select {
+                        mailbox: Mailbox::Inbox,
+                        parameters: vec![TaggedExtParam::new("CONDSTORE").unwrap()],
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A EXAMINE INBOX (QRESYNC (67890007 20050715194045000 41,43:211,214:541))\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::Examine {
+                        mailbox: Mailbox::Inbox,
+                        parameters: vec![TaggedExtParam::with_value(
+                            "QRESYNC",
+                            TaggedExtVal::Comp(vec![
+                                comp("67890007"),
+                                comp("20050715194045000"),
+                                comp("41,43:211,214:541"),
+                            ]),
+                        )
+                        .unwrap()],
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A RENAME a b (X-FOO 1:3 X-BAR)\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::Rename {
+                        from: Mailbox::try_from("a").unwrap(),
+                        to: Mailbox::try_from("b").unwrap(),
+                        parameters: vec![
+                            TaggedExtParam::with_value(
+                                "X-FOO",
+                                TaggedExtVal::Simple(TaggedExtSimple::SequenceSet(
+                                    SequenceSet::try_from("1:3").unwrap(),
+                                )),
+                            )
+                            .unwrap(),
+                            TaggedExtParam::new("X-BAR").unwrap(),
+                        ],
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A UID FETCH 1:* (FLAGS) (CHANGEDSINCE 12345 VANISHED)\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::Fetch {
+                        sequence_set: SequenceSet::try_from("1:*").unwrap(),
+                        macro_or_item_names: MacroOrMessageDataItemNames::MessageDataItemNames(
+                            vec![MessageDataItemName::Flags],
+                        ),
+                        modifiers: vec![
+                            TaggedExtParam::with_value("CHANGEDSINCE", number(12345)).unwrap(),
+                            TaggedExtParam::new("VANISHED").unwrap(),
+                        ],
+                        uid: true,
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A FETCH 1 FAST (X-FOO)\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::Fetch {
+                        sequence_set: SequenceSet::try_from(1).unwrap(),
+                        macro_or_item_names: MacroOrMessageDataItemNames::Macro(Macro::Fast),
+                        modifiers: vec![TaggedExtParam::new("X-FOO").unwrap()],
+                        uid: false,
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A STORE 1 (UNCHANGEDSINCE 5) +FLAGS (\\Seen)\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::Store {
+                        sequence_set: SequenceSet::try_from(1).unwrap(),
+                        modifiers: vec![
+                            TaggedExtParam::with_value("UNCHANGEDSINCE", number(5)).unwrap()
+                        ],
+                        kind: StoreType::Add,
+                        response: StoreResponse::Answer,
+                        flags: vec![Flag::Seen],
+                        uid: false,
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A LIST (SUBSCRIBED REMOTE) \"\" * RETURN (CHILDREN STATUS (MESSAGES UNSEEN))\r\n",
+                b"",
+                Command::new(
+                    "A",
+                    CommandBody::List {
+                        select_options: vec![
+                            ListOption {
+                                name: Atom::try_from("SUBSCRIBED").unwrap(),
+                                value: None,
+                            },
+                            ListOption {
+                                name: Atom::try_from("REMOTE").unwrap(),
+                                value: None,
+                            },
+                        ],
+                        reference: Mailbox::try_from("").unwrap(),
+                        mailbox_wildcard: "*".try_into().unwrap(),
+                        return_options: vec![
+                            ListOption {
+                                name: Atom::try_from("CHILDREN").unwrap(),
+                                value: None,
+                            },
+                            ListOption {
+                                name: Atom::try_from("STATUS").unwrap(),
+                                value: Some(Vec1::unvalidated(vec![
+                                    comp("MESSAGES"),
+                                    comp("UNSEEN"),
+                                ])),
+                            },
+                        ],
+                    },
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_kat_inverse_command_append_tagged_ext() {
+        kat_inverse_command(&[(
+            b"A APPEND INBOX (\\Seen) X-FOO (bar) X-BAR 7 {5+}\r\nhello\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::Append {
+                    mailbox: Mailbox::Inbox,
+                    flags: vec![Flag::Seen],
+                    date: None,
+                    extensions: vec![
+                        TaggedExt {
+                            label: label("X-FOO"),
+                            value: TaggedExtVal::Comp(vec![comp("bar")]),
+                        },
+                        TaggedExt {
+                            label: label("X-BAR"),
+                            value: number(7),
+                        },
+                    ],
+                    message: LiteralOrLiteral8::Literal(
+                        Literal::try_from("hello").unwrap().into_non_sync(),
+                    ),
+                },
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_tagged_ext() {
+        kat_inverse_response(&[
+            (
+                b"* 1 FETCH (UID 7 MODSEQ (12345))\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(
+                    Data::fetch(
+                        1,
+                        vec![
+                            MessageDataItem::Uid(NonZeroU32::new(7).unwrap()),
+                            MessageDataItem::Other(
+                                MessageDataItemOther::try_from(TaggedExt {
+                                    label: label("MODSEQ"),
+                                    value: TaggedExtVal::Comp(vec![comp("12345")]),
+                                })
+                                .unwrap(),
+                            ),
+                        ],
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"* STATUS INBOX (MESSAGES 2 HIGHESTMODSEQ 4294967296)\r\n",
+                b"",
+                Response::Data(Data::Status {
+                    mailbox: Mailbox::Inbox,
+                    items: vec![
+                        StatusDataItem::Messages(2),
+                        StatusDataItem::Other(
+                            StatusDataItemOther::try_from(TaggedExt {
+                                label: label("HIGHESTMODSEQ"),
+                                value: number(4294967296),
+                            })
+                            .unwrap(),
+                        ),
+                    ]
+                    .into(),
+                }),
+            ),
+            (
+                b"* LIST () \"/\" foo (\"CHILDINFO\" (\"SUBSCRIBED\"))\r\n",
+                b"",
+                Response::Data(Data::List {
+                    items: vec![],
+                    delimiter: Some('/'.try_into().unwrap()),
+                    mailbox: Mailbox::try_from("foo").unwrap(),
+                    extended_items: vec![ListExtendedItem {
+                        tag: AString::String(IString::Quoted("CHILDINFO".try_into().unwrap())),
+                        value: TaggedExtVal::Comp(vec![TaggedExtComp::AString(AString::String(
+                            IString::Quoted("SUBSCRIBED".try_into().unwrap()),
+                        ))]),
+                    }],
+                }),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_tagged_ext_nstring() {
+        let item = |label_: &'static str, nstring| TaggedExtItem {
+            label: label(label_),
+            value: TaggedExtItemVal::NString(nstring),
+        };
+
+        kat_inverse_response(&[
+            (
+                b"* 1 FETCH (SAVEDATE NIL X-FOO \"bar\")\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(
+                    Data::fetch(
+                        1,
+                        vec![
+                            MessageDataItem::Other(
+                                MessageDataItemOther::try_from(item("SAVEDATE", NString(None)))
+                                    .unwrap(),
+                            ),
+                            MessageDataItem::Other(
+                                MessageDataItemOther::try_from(item(
+                                    "X-FOO",
+                                    NString(Some(IString::Quoted(
+                                        Quoted::try_from("bar").unwrap(),
+                                    ))),
+                                ))
+                                .unwrap(),
+                            ),
+                        ],
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"* STATUS INBOX (X-FOO {3}\r\nbar X-BAR NIL)\r\n",
+                b"",
+                Response::Data(Data::Status {
+                    mailbox: Mailbox::Inbox,
+                    items: vec![
+                        StatusDataItem::Other(
+                            StatusDataItemOther::try_from(item(
+                                "X-FOO",
+                                NString(Some(IString::Literal(Literal::try_from("bar").unwrap()))),
+                            ))
+                            .unwrap(),
+                        ),
+                        StatusDataItem::Other(
+                            StatusDataItemOther::try_from(item("X-BAR", NString(None))).unwrap(),
+                        ),
+                    ]
+                    .into(),
+                }),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_other_rejects_known_items() {
+        assert!(MessageDataItemOther::try_from(TaggedExt {
+            label: label("uid"),
+            value: number(1),
+        })
+        .is_err());
+        assert!(StatusDataItemOther::try_from(TaggedExt {
+            label: label("Messages"),
+            value: number(1),
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{Tag, Vec1},
+        extensions::enable::CapabilityEnable,
+        fetch::MessageDataItem,
+        flag::{Flag, FlagFetch},
+        response::{Capability, Code, Data, Response, Status},
+    };
+
+    use crate::testing::{kat_inverse_command, kat_inverse_response};
+
+    #[test]
+    fn test_kat_inverse_command_enable_uidonly() {
+        kat_inverse_command(&[(
+            b"A ENABLE UIDONLY\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::enable(vec![CapabilityEnable::UidOnly]).unwrap(),
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_uidonly() {
+        kat_inverse_response(&[
+            (
+                b"* CAPABILITY IMAP4REV1 ENABLE UIDONLY\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Capability(
+                    Vec1::try_from(vec![
+                        Capability::Imap4Rev1,
+                        Capability::Enable,
+                        Capability::UidOnly,
+                    ])
+                    .unwrap(),
+                )),
+            ),
+            (
+                b"* ENABLED UIDONLY\r\n",
+                b"",
+                Response::Data(Data::Enabled {
+                    capabilities: vec![CapabilityEnable::UidOnly],
+                }),
+            ),
+            (
+                b"* 4294967295 UIDFETCH (FLAGS (\\Seen) RFC822.SIZE 44827)\r\n",
+                b"",
+                Response::Data(
+                    Data::uid_fetch(
+                        u32::MAX,
+                        vec![
+                            MessageDataItem::Flags(vec![FlagFetch::Flag(Flag::Seen)]),
+                            MessageDataItem::Rfc822Size(44827),
+                        ],
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"A BAD [UIDREQUIRED] Message sequence numbers are not allowed\r\n",
+                b"",
+                Response::Status(
+                    Status::bad(
+                        Some(Tag::try_from("A").unwrap()),
+                        Some(Code::UidRequired),
+                        "Message sequence numbers are not allowed",
+                    )
+                    .unwrap(),
+                ),
+            ),
+        ]);
+
+        // Sequence numbers in FETCH are still decoded as such.
+        kat_inverse_response(&[(
+            b"* 1 FETCH (UID 4294967295)\r\n".as_ref(),
+            b"".as_ref(),
+            Response::Data(
+                Data::fetch(
+                    1,
+                    vec![MessageDataItem::Uid(NonZeroU32::new(u32::MAX).unwrap())],
+                )
+                .unwrap(),
+            ),
+        )]);
+    }
+}
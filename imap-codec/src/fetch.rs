@@ -13,13 +13,19 @@ use nom::{
     sequence::{delimited, preceded, tuple},
 };
 
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::{fetch_att_gmail, msg_att_gmail};
 use crate::{
     body::body,
     core::{astring, nstring, number, nz_number},
     datetime::date_time,
     decode::IMAPResult,
     envelope::envelope,
-    extensions::binary::{literal8, partial, section_binary},
+    extensions::{
+        binary::{literal8, partial, section_binary},
+        convert::{fetch_att_convert, msg_att_convert},
+        tagged_ext::msg_att_other,
+    },
     flag::flag_fetch,
 };
 
@@ -34,8 +40,16 @@ use crate::{
 ///             "BODY.PEEK" section ["<" number "." nz-number ">"] /
 ///             "BINARY"      section-binary [partial] / ; RFC 3516
 ///             "BINARY.PEEK" section-binary [partial] / ; RFC 3516
-///             "BINARY.SIZE" section-binary             ; RFC 3516
+///             "BINARY.SIZE" section-binary /           ; RFC 3516
+///             "BINARY" [".PEEK"] section-convert [partial] / ; RFC 5259
+///             "PREVIEW" [SP "(" preview-mod *(SP preview-mod) ")"] / ; RFC 8970
+///             "X-GM-MSGID" / "X-GM-THRID" / "X-GM-LABELS"           ; X-GM-EXT-1
+///
+/// preview-mod = "LAZY" ; RFC 8970
 /// ```
+///
+/// Note: `LAZY` is the only preview modifier. Repeating it, e.g., `PREVIEW (LAZY LAZY)`, is
+/// rejected as it can't be represented by [`MessageDataItemName::Preview`].
 pub(crate) fn fetch_att(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName> {
     alt((
         value(MessageDataItemName::Envelope, tag_no_case(b"ENVELOPE")),
@@ -100,6 +114,7 @@ pub(crate) fn fetch_att(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName>
             preceded(tag_no_case("BINARY.SIZE"), section_binary),
             |section| MessageDataItemName::BinarySize { section },
         ),
+        fetch_att_convert,
         value(MessageDataItemName::Body, tag_no_case(b"BODY")),
         value(MessageDataItemName::Uid, tag_no_case(b"UID")),
         value(
@@ -109,17 +124,37 @@ pub(crate) fn fetch_att(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName>
         value(MessageDataItemName::Rfc822Size, tag_no_case(b"RFC822.SIZE")),
         value(MessageDataItemName::Rfc822Text, tag_no_case(b"RFC822.TEXT")),
         value(MessageDataItemName::Rfc822, tag_no_case(b"RFC822")),
+        map(
+            preceded(
+                tag_no_case(b"PREVIEW"),
+                opt(delimited(tag(b" ("), preview_mod, tag(b")"))),
+            ),
+            |modifiers| MessageDataItemName::Preview {
+                lazy: modifiers.is_some(),
+            },
+        ),
+        #[cfg(feature = "ext_gmail")]
+        fetch_att_gmail,
     ))(input)
 }
 
+/// ```abnf
+/// preview-mod = "LAZY"
+/// ```
+pub(crate) fn preview_mod(input: &[u8]) -> IMAPResult<&[u8], ()> {
+    value((), tag_no_case(b"LAZY"))(input)
+}
+
 /// `msg-att = "("
 ///            (msg-att-dynamic / msg-att-static) *(SP (msg-att-dynamic / msg-att-static))
 ///            ")"`
+///
+/// Note: Message data items of unknown extensions are parsed as `tagged-ext` (RFC 4466).
 pub(crate) fn msg_att(input: &[u8]) -> IMAPResult<&[u8], Vec1<MessageDataItem>> {
     delimited(
         tag(b"("),
         map(
-            separated_list1(sp, alt((msg_att_dynamic, msg_att_static))),
+            separated_list1(sp, alt((msg_att_dynamic, msg_att_static, msg_att_other))),
             Vec1::unvalidated,
         ),
         tag(b")"),
@@ -150,7 +185,12 @@ pub(crate) fn msg_att_dynamic(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem
 ///                  "BODY" section ["<" number ">"] SP nstring /
 ///                  "UID" SP uniqueid /
 ///                  "BINARY" section-binary SP (nstring / literal8) / ; RFC 3516
-///                  "BINARY.SIZE" section-binary SP number            ; RFC 3516
+///                  "BINARY.SIZE" section-binary SP number /          ; RFC 3516
+///                  "BINARY" section-convert SP (nstring / literal8) / ; RFC 5259
+///                  "PREVIEW" SP nstring /                             ; RFC 8970
+///                  "X-GM-MSGID" SP number64 /                         ; X-GM-EXT-1
+///                  "X-GM-THRID" SP number64 /                         ; X-GM-EXT-1
+///                  "X-GM-LABELS" SP gm-label-list                     ; X-GM-EXT-1
 /// ```
 ///
 /// Note: MUST NOT change for a message
@@ -221,6 +261,13 @@ pub(crate) fn msg_att_static(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem>
             tuple((tag_no_case(b"BINARY.SIZE"), section_binary, sp, number)),
             |(_, section, _, size)| MessageDataItem::BinarySize { section, size },
         ),
+        msg_att_convert,
+        map(
+            tuple((tag_no_case(b"PREVIEW"), sp, nstring)),
+            |(_, _, nstring)| MessageDataItem::Preview(nstring),
+        ),
+        #[cfg(feature = "ext_gmail")]
+        msg_att_gmail,
     ))(input)
 }
 
@@ -329,10 +376,15 @@ mod tests {
         core::{IString, NString},
         datetime::DateTime,
         envelope::Envelope,
+        response::{Data, Response},
     };
 
     use super::*;
-    use crate::testing::known_answer_test_encode;
+    use crate::{
+        decode::Decoder,
+        testing::{kat_inverse_response, known_answer_test_encode},
+        CommandCodec,
+    };
 
     #[test]
     fn test_encode_message_data_item_name() {
@@ -355,6 +407,11 @@ mod tests {
             (MessageDataItemName::Rfc822Size, b"RFC822.SIZE"),
             (MessageDataItemName::Rfc822Text, b"RFC822.TEXT"),
             (MessageDataItemName::Uid, b"UID"),
+            (MessageDataItemName::Preview { lazy: false }, b"PREVIEW"),
+            (
+                MessageDataItemName::Preview { lazy: true },
+                b"PREVIEW (LAZY)",
+            ),
         ];
 
         for test in tests {
@@ -459,6 +516,11 @@ mod tests {
                 MessageDataItem::Uid(NonZeroU32::try_from(u32::MAX).unwrap()),
                 b"UID 4294967295",
             ),
+            (MessageDataItem::Preview(NString(None)), b"PREVIEW NIL"),
+            (
+                MessageDataItem::Preview(NString::try_from("Hello, World!").unwrap()),
+                b"PREVIEW \"Hello, World!\"",
+            ),
         ];
 
         for test in tests {
@@ -466,6 +528,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_fetch_att_preview() {
+        let tests = [
+            (b"PREVIEW???".as_ref(), false),
+            (b"PREVIEW (LAZY)???", true),
+            (b"preview (lazy)???", true),
+        ];
+
+        for (test, lazy) in tests {
+            let (remaining, got) = fetch_att(test).unwrap();
+            assert_eq!(remaining, b"???");
+            assert_eq!(got, MessageDataItemName::Preview { lazy });
+        }
+
+        for test in [
+            b"A FETCH 1 (PREVIEW (LAZY LAZY))\r\n".as_ref(),
+            b"A FETCH 1 (UID PREVIEW (LAZY LAZY))\r\n",
+            b"A FETCH 1 (PREVIEW ())\r\n",
+        ] {
+            assert!(CommandCodec::default().decode(test).is_err());
+        }
+    }
+
+    #[test]
+    fn test_kat_inverse_response_preview() {
+        kat_inverse_response(&[
+            (
+                b"* 1 FETCH (UID 42 PREVIEW \"Hello, World!\")\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Fetch {
+                    seq: NonZeroU32::new(1).unwrap(),
+                    items: Vec1::try_from(vec![
+                        MessageDataItem::Uid(NonZeroU32::new(42).unwrap()),
+                        MessageDataItem::Preview(NString::try_from("Hello, World!").unwrap()),
+                    ])
+                    .unwrap(),
+                }),
+            ),
+            (
+                b"* 2 FETCH (PREVIEW NIL)\r\n",
+                b"",
+                Response::Data(Data::Fetch {
+                    seq: NonZeroU32::new(2).unwrap(),
+                    items: Vec1::from(MessageDataItem::Preview(NString(None))),
+                }),
+            ),
+        ]);
+    }
+
     #[test]
     fn test_encode_section() {
         let tests = [
@@ -84,6 +84,7 @@
 //!
 //! | Feature               | Description                    | Enabled by default |
 //! |-----------------------|--------------------------------|--------------------|
+//! | deflate               | `COMPRESS=DEFLATE` compression.| No                 |
 //! | quirk_crlf_relaxed    | Make `\r` in `\r\n` optional.  | No                 |
 //! | quirk_rectify_numbers | Rectify (invalid) numbers.     | No                 |
 //! | quirk_missing_text    | Rectify missing `text` element.| No                 |
@@ -134,6 +135,9 @@ mod status;
 #[cfg(test)]
 mod testing;
 
+#[cfg(feature = "deflate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+pub mod deflate;
 pub mod fragmentizer;
 #[cfg(feature = "fuzz")]
 pub mod fuzz {
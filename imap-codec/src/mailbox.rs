@@ -20,7 +20,9 @@ use crate::{
     core::{astring, nil, number, nz_number, quoted_char, string},
     decode::IMAPResult,
     extensions::{
+        esearch::esearch_response,
         quota::{quota_response, quotaroot_response},
+        tagged_ext::mbox_list_extended,
         thread::thread_data,
     },
     flag::{flag_list, mbx_list_flags},
@@ -59,9 +61,10 @@ pub(crate) fn mailbox(input: &[u8]) -> IMAPResult<&[u8], Mailbox> {
 
 /// ```abnf
 /// mailbox-data = "FLAGS" SP flag-list /
-///                "LIST" SP mailbox-list /
+///                "LIST" SP mailbox-list [SP mbox-list-extended] / ; RFC 5258
 ///                "LSUB" SP mailbox-list /
 ///                "SEARCH" *(SP nz-number) /
+///                esearch-response / ; RFC 4731
 ///                "STATUS" SP mailbox SP "(" [status-att-list] ")" /
 ///                "METADATA" SP mailbox SP (entry-values / entry-list) / ; RFC 5464
 ///                number SP "EXISTS" /
@@ -71,11 +74,16 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
     alt((
         map(preceded(tag_no_case(b"FLAGS "), flag_list), Data::Flags),
         map(
-            preceded(tag_no_case(b"LIST "), mailbox_list),
-            |(items, delimiter, mailbox)| Data::List {
+            tuple((
+                tag_no_case(b"LIST "),
+                mailbox_list,
+                opt(preceded(sp, mbox_list_extended)),
+            )),
+            |(_, (items, delimiter, mailbox), extended_items)| Data::List {
                 items: items.unwrap_or_default(),
                 mailbox,
                 delimiter,
+                extended_items: extended_items.unwrap_or_default(),
             },
         ),
         map(
@@ -95,6 +103,7 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
             Data::Sort,
         ),
         thread_data,
+        esearch_response,
         map(
             tuple((
                 tag_no_case(b"STATUS "),
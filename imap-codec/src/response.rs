@@ -8,7 +8,7 @@ use imap_types::{
     core::{Text, Vec1},
     response::{
         Bye, Capability, Code, CodeOther, CommandContinuationRequest, Data, Greeting, GreetingKind,
-        Response, Status, StatusBody, StatusKind, Tagged,
+        Progress, Response, Status, StatusBody, StatusKind, Tagged,
     },
 };
 #[cfg(feature = "quirk_missing_text")]
@@ -26,9 +26,10 @@ use crate::extensions::id::id_response;
 #[cfg(feature = "ext_metadata")]
 use crate::extensions::metadata::metadata_code;
 use crate::{
-    core::{atom, charset, nz_number, tag_imap, text},
+    core::{atom, charset, nil, number, nz_number, tag_imap, tag_string, text},
     decode::IMAPResult,
     extensions::{
+        convert::{converted_data, resp_code_convert},
         enable::enable_data,
         uidplus::{resp_code_apnd, resp_code_copy},
     },
@@ -138,6 +139,12 @@ pub(crate) fn resp_text(input: &[u8]) -> IMAPResult<&[u8], (Option<Code>, Text)>
 ///                    "NOPRIVATE"
 ///                  ) /
 ///                  "UNKNOWN-CTE" /       ; RFC 3516
+///                  "BADPARAMS" /         ; RFC 5259
+///                  "NOCONVERT" /         ; RFC 5259
+///                  "MAXCONVERTMESSAGES" SP nz-number / ; RFC 5259
+///                  "MAXCONVERTPARTS" SP nz-number /    ; RFC 5259
+///                  resp-code-rfc5530 /                 ; RFC 5530, RFC 9585
+///                  "UIDREQUIRED" /                     ; RFC 9586
 ///                  atom [SP 1*<any TEXT-CHAR except "]">]
 /// ```
 ///
@@ -194,9 +201,80 @@ pub(crate) fn resp_text_code(input: &[u8]) -> IMAPResult<&[u8], Code> {
         resp_code_apnd,
         resp_code_copy,
         value(Code::UidNotSticky, tag_no_case(b"UIDNOTSTICKY")),
+        // Note: `alt` is limited to 21 parsers.
+        alt((
+            resp_code_convert,
+            resp_code_rfc5530,
+            value(Code::UidRequired, tag_no_case(b"UIDREQUIRED")),
+        )),
+    ))(input)
+}
+
+/// ```abnf
+/// resp-text-code =/ "UNAVAILABLE" / "AUTHENTICATIONFAILED" / "AUTHORIZATIONFAILED" /
+///                   "EXPIRED" / "PRIVACYREQUIRED" / "CONTACTADMIN" / "NOPERM" /
+///                   "INUSE" / "EXPUNGEISSUED" / "CORRUPTION" / "SERVERBUG" /
+///                   "CLIENTBUG" / "CANNOT" / "LIMIT" / "ALREADYEXISTS" /
+///                   "NONEXISTENT" /                                   ; RFC 5530
+///                   "INPROGRESS" [SP "(" progress-tag SP
+///                                 progress-number SP progress-number ")"] ; RFC 9585
+/// ```
+pub(crate) fn resp_code_rfc5530(input: &[u8]) -> IMAPResult<&[u8], Code> {
+    alt((
+        value(Code::Unavailable, tag_no_case(b"UNAVAILABLE")),
+        value(
+            Code::AuthenticationFailed,
+            tag_no_case(b"AUTHENTICATIONFAILED"),
+        ),
+        value(
+            Code::AuthorizationFailed,
+            tag_no_case(b"AUTHORIZATIONFAILED"),
+        ),
+        value(Code::Expired, tag_no_case(b"EXPIRED")),
+        value(Code::PrivacyRequired, tag_no_case(b"PRIVACYREQUIRED")),
+        value(Code::ContactAdmin, tag_no_case(b"CONTACTADMIN")),
+        value(Code::NoPerm, tag_no_case(b"NOPERM")),
+        value(Code::InUse, tag_no_case(b"INUSE")),
+        value(Code::ExpungeIssued, tag_no_case(b"EXPUNGEISSUED")),
+        value(Code::Corruption, tag_no_case(b"CORRUPTION")),
+        value(Code::ServerBug, tag_no_case(b"SERVERBUG")),
+        value(Code::ClientBug, tag_no_case(b"CLIENTBUG")),
+        value(Code::Cannot, tag_no_case(b"CANNOT")),
+        value(Code::Limit, tag_no_case(b"LIMIT")),
+        value(Code::AlreadyExists, tag_no_case(b"ALREADYEXISTS")),
+        value(Code::NonExistent, tag_no_case(b"NONEXISTENT")),
+        map(
+            preceded(tag_no_case(b"INPROGRESS"), opt(preceded(sp, progress))),
+            Code::InProgress,
+        ),
     ))(input)
 }
 
+/// ```abnf
+/// progress = "(" progress-tag SP progress-number SP progress-number ")"
+///
+/// progress-tag = tag-string / nil
+///
+/// progress-number = number / nil
+/// ```
+pub(crate) fn progress(input: &[u8]) -> IMAPResult<&[u8], Progress> {
+    let mut parser = delimited(
+        tag(b"("),
+        tuple((
+            alt((map(tag_string, Some), value(None, nil))),
+            sp,
+            alt((map(number, Some), value(None, nil))),
+            sp,
+            alt((map(number, Some), value(None, nil))),
+        )),
+        tag(b")"),
+    );
+
+    let (remaining, (tag, _, done, _, goal)) = parser(input)?;
+
+    Ok((remaining, Progress { tag, done, goal }))
+}
+
 /// `capability-data = "CAPABILITY" *(SP capability) SP "IMAP4rev1" *(SP capability)`
 ///
 /// Servers MUST implement the STARTTLS, AUTH=PLAIN, and LOGINDISABLED capabilities
@@ -366,7 +444,11 @@ pub(crate) fn response_fatal(input: &[u8]) -> IMAPResult<&[u8], Status> {
     Ok((remaining, Status::Bye(Bye { code, text })))
 }
 
-/// `message-data = nz-number SP ("EXPUNGE" / ("FETCH" SP msg-att))`
+/// ```abnf
+/// message-data = nz-number SP ("EXPUNGE" / ("FETCH" SP msg-att) / converted-data)
+///
+/// message-data =/ uniqueid SP "UIDFETCH" SP msg-att ; RFC 9586
+/// ```
 pub(crate) fn message_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
     let (remaining, seq) = terminated(nz_number, sp)(input)?;
 
@@ -375,6 +457,14 @@ pub(crate) fn message_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
         map(preceded(tag_no_case(b"FETCH "), msg_att), move |items| {
             Data::Fetch { seq, items }
         }),
+        map(preceded(tag_no_case(b"UIDFETCH "), msg_att), move |items| {
+            Data::UidFetch { uid: seq, items }
+        }),
+        map(converted_data, move |(tag, items)| Data::Converted {
+            seq,
+            tag,
+            items,
+        }),
     ))(remaining)
 }
 
@@ -389,10 +479,15 @@ mod tests {
         },
         core::{IString, NString, QuotedChar, Tag},
         flag::FlagNameAttribute,
+        response::FailureKind,
     };
 
     use super::*;
-    use crate::testing::{kat_inverse_greeting, kat_inverse_response, known_answer_test_encode};
+    use crate::{
+        decode::Decoder,
+        testing::{kat_inverse_greeting, kat_inverse_response, known_answer_test_encode},
+        ResponseCodec,
+    };
 
     #[test]
     fn test_kat_inverse_greeting() {
@@ -443,6 +538,7 @@ mod tests {
                     items: vec![FlagNameAttribute::Noselect],
                     delimiter: Some(QuotedChar::try_from('/').unwrap()),
                     mailbox: "bbb".try_into().unwrap(),
+                    extended_items: vec![],
                 }),
             ),
             (
@@ -570,6 +666,97 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_kat_inverse_response_code_rfc5530_rfc9585() {
+        let tests = [
+            ("UNAVAILABLE", Code::Unavailable),
+            ("AUTHENTICATIONFAILED", Code::AuthenticationFailed),
+            ("AUTHORIZATIONFAILED", Code::AuthorizationFailed),
+            ("EXPIRED", Code::Expired),
+            ("PRIVACYREQUIRED", Code::PrivacyRequired),
+            ("CONTACTADMIN", Code::ContactAdmin),
+            ("NOPERM", Code::NoPerm),
+            ("INUSE", Code::InUse),
+            ("EXPUNGEISSUED", Code::ExpungeIssued),
+            ("CORRUPTION", Code::Corruption),
+            ("SERVERBUG", Code::ServerBug),
+            ("CLIENTBUG", Code::ClientBug),
+            ("CANNOT", Code::Cannot),
+            ("LIMIT", Code::Limit),
+            ("ALREADYEXISTS", Code::AlreadyExists),
+            ("NONEXISTENT", Code::NonExistent),
+            ("INPROGRESS", Code::InProgress(None)),
+            (
+                "INPROGRESS (\"A1\" 17 42)",
+                Code::InProgress(Some(Progress {
+                    tag: Some(Tag::try_from("A1").unwrap()),
+                    done: Some(17),
+                    goal: Some(42),
+                })),
+            ),
+            (
+                "INPROGRESS ({2}\r\nA1 NIL 42)",
+                Code::InProgress(Some(Progress {
+                    tag: Some(Tag::try_from("A1").unwrap()),
+                    done: None,
+                    goal: Some(42),
+                })),
+            ),
+            (
+                "INPROGRESS (NIL NIL NIL)",
+                Code::InProgress(Some(Progress {
+                    tag: None,
+                    done: None,
+                    goal: None,
+                })),
+            ),
+        ];
+
+        for (test, code) in tests {
+            let input = format!("A1 NO [{test}] ...\r\n");
+
+            kat_inverse_response(&[(
+                input.as_bytes(),
+                b"".as_ref(),
+                Response::Status(
+                    Status::no(Some(Tag::try_from("A1").unwrap()), Some(code), "...").unwrap(),
+                ),
+            )]);
+        }
+
+        // Known codes with unexpected parameters are still recognized as `Code::Other`.
+        let (_, got) = ResponseCodec::default()
+            .decode(b"* OK [INPROGRESS (A1 1 2)] ...\r\n")
+            .unwrap();
+        assert!(matches!(
+            got,
+            Response::Status(Status::Untagged(StatusBody {
+                code: Some(Code::Other(_)),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_code_failure_kind() {
+        let tests = [
+            (Code::Unavailable, Some(FailureKind::Transient)),
+            (Code::InUse, Some(FailureKind::Transient)),
+            (Code::ExpungeIssued, Some(FailureKind::Transient)),
+            (Code::AuthenticationFailed, Some(FailureKind::Permanent)),
+            (Code::NoPerm, Some(FailureKind::Permanent)),
+            (Code::OverQuota, Some(FailureKind::Permanent)),
+            (Code::NonExistent, Some(FailureKind::Permanent)),
+            (Code::Alert, None),
+            (Code::TryCreate, None),
+            (Code::InProgress(None), None),
+        ];
+
+        for (code, expected) in tests {
+            assert_eq!(code.failure_kind(), expected);
+        }
+    }
+
     /*
     // TODO(#184)
     #[test]
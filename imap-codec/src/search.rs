@@ -12,6 +12,8 @@ use nom::{
     sequence::{delimited, separated_pair, tuple},
 };
 
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::search_key_gmail;
 use crate::{
     core::{astring, atom, charset, number},
     datetime::date,
@@ -87,6 +89,10 @@ pub(crate) fn search(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 ///               sequence-set /
 ///               "(" search-key *(SP search-key) ")"`
 ///
+/// `search-key =/ "FUZZY" SP search-key` (RFC 6203)
+///
+/// With `ext_gmail`, the `X-GM-RAW`, `X-GM-MSGID`, `X-GM-THRID`, and `X-GM-LABELS` search keys are supported, too.
+///
 /// This parser is recursively defined. Thus, in order to not overflow the stack,
 /// it is needed to limit how may recursions are allowed. (8 should suffice).
 pub(crate) fn search_key(
@@ -190,6 +196,10 @@ fn search_key_limited<'a>(
                 tuple((tag_no_case(b"OR"), sp, search_key, sp, search_key)),
                 |(_, _, alt1, _, alt2)| SearchKey::Or(Box::new(alt1), Box::new(alt2)),
             ),
+            map(
+                tuple((tag_no_case(b"FUZZY"), sp, search_key)),
+                |(_, _, val)| SearchKey::Fuzzy(Box::new(val)),
+            ),
             map(
                 tuple((tag_no_case(b"SENTBEFORE"), sp, map_opt(date, |date| date))),
                 |(_, _, date)| SearchKey::SentBefore(date),
@@ -217,6 +227,8 @@ fn search_key_limited<'a>(
                 |val| SearchKey::And(Vec1::unvalidated(val)),
             ),
         )),
+        #[cfg(feature = "ext_gmail")]
+        search_key_gmail,
     ))(input)
 }
 
@@ -316,6 +328,10 @@ mod tests {
         assert!(search_key(1)(b"(1:5)|").is_err());
         assert!(search_key(2)(b"(1:5)|").is_ok());
         assert!(search_key(2)(b"((1:5))|").is_err());
+        assert!(search_key(2)(b"FUZZY SUBJECT a|").is_ok());
+        assert!(search_key(2)(b"FUZZY FUZZY SUBJECT a|").is_err());
+        assert!(search_key(3)(b"FUZZY NOT (SEEN)|").is_err());
+        assert!(search_key(4)(b"FUZZY NOT (SEEN)|").is_ok());
     }
 
     #[test]
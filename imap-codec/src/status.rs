@@ -11,6 +11,7 @@ use nom::{
 use crate::{
     core::{number, number64, nz_number},
     decode::IMAPResult,
+    extensions::tagged_ext::status_att_val_other,
 };
 
 /// `status-att = "MESSAGES" /
@@ -52,6 +53,8 @@ pub(crate) fn status_att_list(input: &[u8]) -> IMAPResult<&[u8], Vec<StatusDataI
 ///                    ("UNSEEN" SP number)`
 ///
 /// Note: See errata id: 261
+///
+/// Note: Status data items of unknown extensions are parsed as `tagged-ext` (RFC 4466).
 fn status_att_val(input: &[u8]) -> IMAPResult<&[u8], StatusDataItem> {
     alt((
         map(
@@ -82,6 +85,7 @@ fn status_att_val(input: &[u8]) -> IMAPResult<&[u8], StatusDataItem> {
             tuple((tag_no_case(b"DELETED"), sp, number)),
             |(_, _, num)| StatusDataItem::Deleted(num),
         ),
+        status_att_val_other,
     ))(input)
 }
 
@@ -1,6 +1,8 @@
 use arbitrary::{Arbitrary, Unstructured};
 use chrono::{FixedOffset, TimeZone};
 
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::GmailLabel;
 use crate::{
     auth::AuthMechanism,
     body::{
@@ -11,7 +13,12 @@ use crate::{
         Text, Vec1, Vec2,
     },
     datetime::{DateTime, NaiveDate},
-    extensions::{enable::CapabilityEnable, quota::Resource},
+    extensions::{
+        enable::CapabilityEnable,
+        quota::Resource,
+        tagged_ext::{TaggedExtItem, TaggedExtLabel},
+    },
+    fetch::MessageDataItemOther,
     flag::{Flag, FlagNameAttribute},
     mailbox::{ListCharString, Mailbox, MailboxOther},
     response::{
@@ -20,6 +27,7 @@ use crate::{
     },
     search::SearchKey,
     sequence::SequenceSet,
+    status::StatusDataItemOther,
 };
 #[cfg(not(feature = "arbitrary_simplified"))]
 use crate::{body::MultiPartExtensionData, envelope::Envelope};
@@ -70,9 +78,22 @@ impl_arbitrary_try_from! { MailboxOther<'a>, AString<'a> }
 impl_arbitrary_try_from! { CapabilityEnable<'a>, &str }
 impl_arbitrary_try_from! { Resource<'a>, &str }
 impl_arbitrary_try_from! { AuthMechanism<'a>, &str }
+impl_arbitrary_try_from! { TaggedExtLabel<'a>, &str }
+impl_arbitrary_try_from! { MessageDataItemOther<'a>, TaggedExtItem<'a> }
 impl_arbitrary_try_from_t! { Vec1<T>, Vec<T> }
 impl_arbitrary_try_from_t! { Vec2<T>, Vec<T> }
 
+#[cfg(feature = "ext_gmail")]
+impl<'a> Arbitrary<'a> for GmailLabel<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        if bool::arbitrary(u)? {
+            Ok(GmailLabel::System(AString::arbitrary(u)?))
+        } else {
+            GmailLabel::user(AString::arbitrary(u)?).map_err(|_| arbitrary::Error::IncorrectFormat)
+        }
+    }
+}
+
 impl<'a> Arbitrary<'a> for CommandContinuationRequestBasic<'a> {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         Self::new(Option::<Code>::arbitrary(u)?, Text::arbitrary(u)?)
@@ -178,6 +199,15 @@ impl<'a> Arbitrary<'a> for CodeOther<'a> {
     }
 }
 
+impl<'a> Arbitrary<'a> for StatusDataItemOther {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        match StatusDataItemOther::try_from(TaggedExtItem::arbitrary(u)?) {
+            Ok(passed) => Ok(passed),
+            Err(_) => Err(arbitrary::Error::IncorrectFormat),
+        }
+    }
+}
+
 impl<'a> Arbitrary<'a> for SearchKey<'a> {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         #[cfg(not(feature = "arbitrary_simplified"))]
@@ -196,7 +226,12 @@ fn arbitrary_search_key_limited<'a>(
         return arbitrary_search_key_leaf(u);
     }
 
-    Ok(match u.int_in_range(0u8..=36)? {
+    #[cfg(not(feature = "ext_gmail"))]
+    let max = 37;
+    #[cfg(feature = "ext_gmail")]
+    let max = 41;
+
+    Ok(match u.int_in_range(0u8..=max)? {
         0 => SearchKey::And({
             let keys = {
                 let len = u.arbitrary_len::<SearchKey>()?;
@@ -254,12 +289,26 @@ fn arbitrary_search_key_limited<'a>(
         34 => SearchKey::Unflagged,
         35 => SearchKey::Unkeyword(Atom::arbitrary(u)?),
         36 => SearchKey::Unseen,
+        37 => SearchKey::Fuzzy(Box::new(arbitrary_search_key_limited(u, depth - 1)?)),
+        #[cfg(feature = "ext_gmail")]
+        38 => SearchKey::GmailRaw(AString::arbitrary(u)?),
+        #[cfg(feature = "ext_gmail")]
+        39 => SearchKey::GmailMessageId(u64::arbitrary(u)?),
+        #[cfg(feature = "ext_gmail")]
+        40 => SearchKey::GmailThreadId(u64::arbitrary(u)?),
+        #[cfg(feature = "ext_gmail")]
+        41 => SearchKey::GmailLabels(GmailLabel::arbitrary(u)?),
         _ => unreachable!(),
     })
 }
 
 fn arbitrary_search_key_leaf<'a>(u: &mut Unstructured<'a>) -> arbitrary::Result<SearchKey<'a>> {
-    Ok(match u.int_in_range(0u8..=33)? {
+    #[cfg(not(feature = "ext_gmail"))]
+    let max = 33;
+    #[cfg(feature = "ext_gmail")]
+    let max = 37;
+
+    Ok(match u.int_in_range(0u8..=max)? {
         0 => SearchKey::SequenceSet(SequenceSet::arbitrary(u)?),
         1 => SearchKey::All,
         2 => SearchKey::Answered,
@@ -294,6 +343,14 @@ fn arbitrary_search_key_leaf<'a>(u: &mut Unstructured<'a>) -> arbitrary::Result<
         31 => SearchKey::Unflagged,
         32 => SearchKey::Unkeyword(Atom::arbitrary(u)?),
         33 => SearchKey::Unseen,
+        #[cfg(feature = "ext_gmail")]
+        34 => SearchKey::GmailRaw(AString::arbitrary(u)?),
+        #[cfg(feature = "ext_gmail")]
+        35 => SearchKey::GmailMessageId(u64::arbitrary(u)?),
+        #[cfg(feature = "ext_gmail")]
+        36 => SearchKey::GmailThreadId(u64::arbitrary(u)?),
+        #[cfg(feature = "ext_gmail")]
+        37 => SearchKey::GmailLabels(GmailLabel::arbitrary(u)?),
         _ => unreachable!(),
     })
 }
@@ -1,5 +1,7 @@
 //! Body(structure)-related types.
 
+use std::{borrow::Cow, num::NonZeroU32};
+
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
@@ -9,6 +11,8 @@ use serde::{Deserialize, Serialize};
 use crate::{
     core::{IString, NString, Vec1},
     envelope::Envelope,
+    fetch::{Part, Section},
+    utils::mime::parameter,
 };
 
 /// Inner part of [`BodyStructure`].
@@ -246,6 +250,266 @@ pub enum BodyStructure<'a> {
     },
 }
 
+impl<'a> BodyStructure<'a> {
+    /// Media type and subtype, e.g., `("TEXT", "PLAIN")`.
+    pub fn media_type(&self) -> (Cow<str>, Cow<str>) {
+        match self {
+            Self::Single { body, .. } => match &body.specific {
+                SpecificFields::Basic { r#type, subtype } => (lossy(r#type), lossy(subtype)),
+                SpecificFields::Message { .. } => {
+                    (Cow::Borrowed("MESSAGE"), Cow::Borrowed("RFC822"))
+                }
+                SpecificFields::Text { subtype, .. } => (Cow::Borrowed("TEXT"), lossy(subtype)),
+            },
+            Self::Multi { subtype, .. } => (Cow::Borrowed("MULTIPART"), lossy(subtype)),
+        }
+    }
+
+    /// Whether the media type is `r#type/subtype` (compared case-insensitively).
+    pub fn is_media_type(&self, r#type: &str, subtype: &str) -> bool {
+        let (actual_type, actual_subtype) = self.media_type();
+
+        actual_type.eq_ignore_ascii_case(r#type) && actual_subtype.eq_ignore_ascii_case(subtype)
+    }
+
+    /// Content transfer encoding (`None` for multiparts).
+    pub fn encoding(&self) -> Option<&IString<'a>> {
+        match self {
+            Self::Single { body, .. } => Some(&body.basic.content_transfer_encoding),
+            Self::Multi { .. } => None,
+        }
+    }
+
+    /// Size in octets in its transfer encoding (`None` for multiparts).
+    pub fn size(&self) -> Option<u32> {
+        match self {
+            Self::Single { body, .. } => Some(body.basic.size),
+            Self::Multi { .. } => None,
+        }
+    }
+
+    /// Content-Type parameters (only available with extension data for multiparts).
+    pub fn parameters(&self) -> &[(IString<'a>, IString<'a>)] {
+        match self {
+            Self::Single { body, .. } => &body.basic.parameter_list,
+            Self::Multi { extension_data, .. } => extension_data
+                .as_ref()
+                .map(|extension_data| extension_data.parameter_list.as_slice())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Content-Disposition type and parameters (only available with extension data).
+    #[allow(clippy::type_complexity)]
+    pub fn disposition(&self) -> Option<&(IString<'a>, Vec<(IString<'a>, IString<'a>)>)> {
+        let tail = match self {
+            Self::Single { extension_data, .. } => extension_data.as_ref()?.tail.as_ref(),
+            Self::Multi { extension_data, .. } => extension_data.as_ref()?.tail.as_ref(),
+        };
+
+        tail?.disposition.as_ref()
+    }
+
+    /// File name from the Content-Disposition `filename` or the Content-Type `name` parameter.
+    ///
+    /// [RFC 2231] parameter value continuations and encodings as well as [RFC 2047]
+    /// encoded-words are decoded.
+    ///
+    /// [RFC 2231]: https://datatracker.ietf.org/doc/html/rfc2231
+    /// [RFC 2047]: https://datatracker.ietf.org/doc/html/rfc2047
+    pub fn filename(&self) -> Option<String> {
+        self.disposition()
+            .and_then(|(_, parameters)| parameter(parameters, "filename"))
+            .or_else(|| parameter(self.parameters(), "name"))
+    }
+
+    /// Whether this (non-multipart) part is an attachment.
+    ///
+    /// A part is an attachment if its disposition is `attachment`, or if it has no disposition
+    /// but a file name.
+    pub fn is_attachment(&self) -> bool {
+        if let Self::Multi { .. } = self {
+            return false;
+        }
+
+        match self.disposition() {
+            Some((disposition, _)) => lossy(disposition).eq_ignore_ascii_case("attachment"),
+            None => self.filename().is_some(),
+        }
+    }
+
+    /// All parts with their part numbers (in depth-first order).
+    ///
+    /// Multiparts that have no part number on their own, i.e., a top-level multipart or the body
+    /// of a MESSAGE/RFC822 part, are not included (but their children are).
+    pub fn parts(&self) -> Vec<BodyPart<'_, 'a>> {
+        let mut parts = Vec::new();
+        self.collect_parts(&[], &mut parts);
+        parts
+    }
+
+    /// All attachments (see [`Self::is_attachment`]) with their part numbers.
+    pub fn attachments(&self) -> Vec<BodyPart<'_, 'a>> {
+        self.parts()
+            .into_iter()
+            .filter(|part| part.body.is_attachment())
+            .collect()
+    }
+
+    /// Find the part with the given part number.
+    pub fn find(&self, part: &Part) -> Option<&BodyStructure<'a>> {
+        let mut container = Some(self);
+        let mut current = self;
+
+        for number in part.0.as_ref() {
+            current = container?.child(*number)?;
+            container = current.container();
+        }
+
+        Some(current)
+    }
+
+    /// Find the body structure a section refers to.
+    ///
+    /// * Part numbers and `MIME` refer to the numbered part.
+    /// * `HEADER` (and `HEADER.FIELDS[.NOT]`) refer to the MESSAGE/RFC822 part.
+    /// * `TEXT` refers to the body of the MESSAGE/RFC822 part.
+    /// * Sections without part number refer to the message, i.e., `self`.
+    pub fn resolve(&self, section: &Section) -> Option<&BodyStructure<'a>> {
+        match section {
+            Section::Part(part) | Section::Mime(part) => self.find(part),
+            Section::Header(part)
+            | Section::HeaderFields(part, _)
+            | Section::HeaderFieldsNot(part, _) => match part {
+                Some(part) => self.find(part).filter(|body| body.message().is_some()),
+                None => Some(self),
+            },
+            Section::Text(part) => match part {
+                Some(part) => self.find(part)?.message(),
+                None => Some(self),
+            },
+        }
+    }
+
+    /// Find the best TEXT/`subtype` part, e.g., `"plain"` or `"html"`.
+    ///
+    /// Parts of MULTIPART/ALTERNATIVE are considered from last (preferred) to first, all other
+    /// multiparts from first to last. Attachments and MESSAGE/RFC822 parts are skipped.
+    pub fn text(&self, subtype: &str) -> Option<BodyPart<'_, 'a>> {
+        self.find_text(&[], subtype)
+    }
+
+    /// Inner body of a MESSAGE/RFC822 part.
+    fn message(&self) -> Option<&BodyStructure<'a>> {
+        match self {
+            Self::Single {
+                body:
+                    Body {
+                        specific: SpecificFields::Message { body_structure, .. },
+                        ..
+                    },
+                ..
+            } => Some(body_structure),
+            _ => None,
+        }
+    }
+
+    /// The body structure whose children are numbered `<part number>.<n>` (if any).
+    fn container(&self) -> Option<&BodyStructure<'a>> {
+        match self {
+            Self::Single { .. } => self.message(),
+            Self::Multi { .. } => Some(self),
+        }
+    }
+
+    /// The `number`th child of a container (see [`Self::container`]).
+    ///
+    /// Non-multipart bodies have a single child `1`, i.e., themselves.
+    fn child(&self, number: NonZeroU32) -> Option<&BodyStructure<'a>> {
+        match self {
+            Self::Single { .. } => (number.get() == 1).then_some(self),
+            Self::Multi { bodies, .. } => bodies.as_ref().get(number.get() as usize - 1),
+        }
+    }
+
+    /// Children of a container (see [`Self::container`]) with their numbers.
+    fn children(&self) -> impl Iterator<Item = (NonZeroU32, &BodyStructure<'a>)> {
+        let children = match self {
+            Self::Single { .. } => std::slice::from_ref(self),
+            Self::Multi { bodies, .. } => bodies.as_ref(),
+        };
+
+        (1..).filter_map(NonZeroU32::new).zip(children)
+    }
+
+    fn collect_parts<'s>(&'s self, prefix: &[NonZeroU32], parts: &mut Vec<BodyPart<'s, 'a>>) {
+        for (number, child) in self.children() {
+            let mut part = prefix.to_vec();
+            part.push(number);
+
+            parts.push(BodyPart {
+                part: Part(Vec1::unvalidated(part.clone())),
+                body: child,
+            });
+
+            if let Some(container) = child.container() {
+                container.collect_parts(&part, parts);
+            }
+        }
+    }
+
+    fn find_text<'s>(&'s self, prefix: &[NonZeroU32], subtype: &str) -> Option<BodyPart<'s, 'a>> {
+        let mut children: Vec<_> = self.children().collect();
+
+        if self.is_media_type("MULTIPART", "ALTERNATIVE") {
+            children.reverse();
+        }
+
+        children.into_iter().find_map(|(number, child)| {
+            let mut part = prefix.to_vec();
+            part.push(number);
+
+            match child {
+                Self::Multi { .. } => child.find_text(&part, subtype),
+                Self::Single { .. }
+                    if child.is_media_type("TEXT", subtype) && !child.is_attachment() =>
+                {
+                    Some(BodyPart {
+                        part: Part(Vec1::unvalidated(part)),
+                        body: child,
+                    })
+                }
+                Self::Single { .. } => None,
+            }
+        })
+    }
+}
+
+/// A (numbered) part of a [`BodyStructure`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BodyPart<'s, 'a> {
+    /// Part number, e.g., `2.1`.
+    pub part: Part,
+    /// Body structure of the part.
+    pub body: &'s BodyStructure<'a>,
+}
+
+impl BodyPart<'_, '_> {
+    /// Section to fetch the part, e.g., `BODY[2.1]`.
+    pub fn section(&self) -> Section<'static> {
+        Section::Part(self.part.clone())
+    }
+
+    /// Section to fetch the MIME header of the part, e.g., `BODY[2.1.MIME]`.
+    pub fn mime(&self) -> Section<'static> {
+        Section::Mime(self.part.clone())
+    }
+}
+
+fn lossy<'a>(value: &'a IString) -> Cow<'a, str> {
+    String::from_utf8_lossy(value.as_ref())
+}
+
 /// The extension data of a non-multipart body part.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -335,3 +599,300 @@ pub enum BodyExtension<'a> {
     /// List.
     List(Vec1<BodyExtension<'a>>),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn istr(value: &'static str) -> IString<'static> {
+        IString::try_from(value).unwrap()
+    }
+
+    fn part(value: &str) -> Part {
+        Part(Vec1::unvalidated(
+            value
+                .split('.')
+                .map(|number| number.parse().unwrap())
+                .collect(),
+        ))
+    }
+
+    fn basic(
+        parameters: &[(&'static str, &'static str)],
+        specific: SpecificFields<'static>,
+    ) -> Body<'static> {
+        Body {
+            basic: BasicFields {
+                parameter_list: parameters
+                    .iter()
+                    .map(|(key, value)| (istr(key), istr(value)))
+                    .collect(),
+                id: NString(None),
+                description: NString(None),
+                content_transfer_encoding: istr("BASE64"),
+                size: 123,
+            },
+            specific,
+        }
+    }
+
+    fn text(subtype: &'static str) -> BodyStructure<'static> {
+        BodyStructure::Single {
+            body: basic(
+                &[("CHARSET", "UTF-8")],
+                SpecificFields::Text {
+                    subtype: istr(subtype),
+                    number_of_lines: 1,
+                },
+            ),
+            extension_data: None,
+        }
+    }
+
+    fn file(
+        r#type: &'static str,
+        subtype: &'static str,
+        parameters: &[(&'static str, &'static str)],
+        disposition: Option<(&'static str, &[(&'static str, &'static str)])>,
+    ) -> BodyStructure<'static> {
+        BodyStructure::Single {
+            body: basic(
+                parameters,
+                SpecificFields::Basic {
+                    r#type: istr(r#type),
+                    subtype: istr(subtype),
+                },
+            ),
+            extension_data: disposition.map(|(disposition, parameters)| SinglePartExtensionData {
+                md5: NString(None),
+                tail: Some(Disposition {
+                    disposition: Some((
+                        istr(disposition),
+                        parameters
+                            .iter()
+                            .map(|(key, value)| (istr(key), istr(value)))
+                            .collect(),
+                    )),
+                    tail: None,
+                }),
+            }),
+        }
+    }
+
+    fn multi(subtype: &'static str, bodies: Vec<BodyStructure<'static>>) -> BodyStructure<'static> {
+        BodyStructure::Multi {
+            bodies: Vec1::try_from(bodies).unwrap(),
+            subtype: istr(subtype),
+            extension_data: None,
+        }
+    }
+
+    fn message(body_structure: BodyStructure<'static>) -> BodyStructure<'static> {
+        BodyStructure::Single {
+            body: basic(
+                &[],
+                SpecificFields::Message {
+                    envelope: Box::new(Envelope {
+                        date: NString(None),
+                        subject: NString(None),
+                        from: vec![],
+                        sender: vec![],
+                        reply_to: vec![],
+                        to: vec![],
+                        cc: vec![],
+                        bcc: vec![],
+                        in_reply_to: NString(None),
+                        message_id: NString(None),
+                    }),
+                    body_structure: Box::new(body_structure),
+                    number_of_lines: 1,
+                },
+            ),
+            extension_data: None,
+        }
+    }
+
+    /// ```text
+    /// MULTIPART/MIXED
+    /// 1       TEXT/PLAIN
+    /// 2       APPLICATION/OCTET-STREAM (attachment)
+    /// 3       MULTIPART/ALTERNATIVE
+    /// 3.1     TEXT/PLAIN
+    /// 3.2     TEXT/HTML
+    /// 4       MESSAGE/RFC822
+    /// 4.TEXT  MULTIPART/MIXED
+    /// 4.1     TEXT/PLAIN
+    /// 4.2     IMAGE/PNG
+    /// ```
+    fn body_structure() -> BodyStructure<'static> {
+        multi(
+            "MIXED",
+            vec![
+                text("PLAIN"),
+                file(
+                    "APPLICATION",
+                    "OCTET-STREAM",
+                    &[],
+                    Some(("ATTACHMENT", &[("FILENAME", "a.bin")])),
+                ),
+                multi("ALTERNATIVE", vec![text("PLAIN"), text("HTML")]),
+                message(multi(
+                    "MIXED",
+                    vec![
+                        text("PLAIN"),
+                        file("IMAGE", "PNG", &[("NAME*", "utf-8''%E2%82%AC.png")], None),
+                    ],
+                )),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_parts() {
+        let body_structure = body_structure();
+
+        let parts: Vec<_> = body_structure
+            .parts()
+            .into_iter()
+            .map(|part| {
+                let (media_type, subtype) = part.body.media_type();
+                (part.part, format!("{media_type}/{subtype}"))
+            })
+            .collect();
+
+        assert_eq!(
+            parts,
+            vec![
+                (part("1"), "TEXT/PLAIN".to_owned()),
+                (part("2"), "APPLICATION/OCTET-STREAM".to_owned()),
+                (part("3"), "MULTIPART/ALTERNATIVE".to_owned()),
+                (part("3.1"), "TEXT/PLAIN".to_owned()),
+                (part("3.2"), "TEXT/HTML".to_owned()),
+                (part("4"), "MESSAGE/RFC822".to_owned()),
+                (part("4.1"), "TEXT/PLAIN".to_owned()),
+                (part("4.2"), "IMAGE/PNG".to_owned()),
+            ]
+        );
+
+        for part in body_structure.parts() {
+            assert_eq!(body_structure.find(&part.part), Some(part.body));
+            assert_eq!(body_structure.resolve(&part.section()), Some(part.body));
+            assert_eq!(body_structure.resolve(&part.mime()), Some(part.body));
+        }
+    }
+
+    #[test]
+    fn test_single_part() {
+        let body_structure = text("PLAIN");
+
+        let parts = body_structure.parts();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].part, part("1"));
+        assert_eq!(parts[0].body, &body_structure);
+
+        assert_eq!(body_structure.find(&part("1")), Some(&body_structure));
+        assert_eq!(body_structure.find(&part("2")), None);
+        assert_eq!(body_structure.find(&part("1.1")), None);
+        assert_eq!(body_structure.text("plain").unwrap().part, part("1"));
+        assert_eq!(body_structure.text("html"), None);
+    }
+
+    #[test]
+    fn test_find_and_resolve() {
+        let body_structure = body_structure();
+
+        for invalid in ["5", "1.1", "3.3", "4.3", "4.1.1"] {
+            assert_eq!(body_structure.find(&part(invalid)), None, "{invalid}");
+        }
+
+        let message = body_structure.find(&part("4")).unwrap();
+        let inner = message.message().unwrap();
+
+        assert_eq!(
+            body_structure.resolve(&Section::Text(Some(part("4")))),
+            Some(inner)
+        );
+        assert_eq!(
+            body_structure.resolve(&Section::Header(Some(part("4")))),
+            Some(message)
+        );
+        assert_eq!(
+            body_structure.resolve(&Section::Header(Some(part("1")))),
+            None
+        );
+        assert_eq!(
+            body_structure.resolve(&Section::Text(Some(part("1")))),
+            None
+        );
+        assert_eq!(
+            body_structure.resolve(&Section::Text(None)),
+            Some(&body_structure)
+        );
+        assert_eq!(
+            body_structure.resolve(&Section::Header(None)),
+            Some(&body_structure)
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let body_structure = body_structure();
+
+        assert_eq!(body_structure.text("PLAIN").unwrap().part, part("1"));
+        assert_eq!(body_structure.text("html").unwrap().part, part("3.2"));
+        assert_eq!(body_structure.text("richtext"), None);
+
+        // The last alternative is preferred.
+        let body_structure = multi(
+            "ALTERNATIVE",
+            vec![
+                text("PLAIN"),
+                multi("RELATED", vec![text("HTML")]),
+                text("HTML"),
+            ],
+        );
+
+        assert_eq!(body_structure.text("html").unwrap().part, part("3"));
+        assert_eq!(body_structure.text("plain").unwrap().part, part("1"));
+    }
+
+    #[test]
+    fn test_attachments() {
+        let body_structure = body_structure();
+
+        let attachments: Vec<_> = body_structure
+            .attachments()
+            .into_iter()
+            .map(|attachment| {
+                (
+                    attachment.part,
+                    attachment.body.filename(),
+                    attachment.body.encoding().cloned(),
+                    attachment.body.size(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            attachments,
+            vec![
+                (
+                    part("2"),
+                    Some("a.bin".to_owned()),
+                    Some(istr("BASE64")),
+                    Some(123)
+                ),
+                (
+                    part("4.2"),
+                    Some("€.png".to_owned()),
+                    Some(istr("BASE64")),
+                    Some(123)
+                ),
+            ]
+        );
+
+        assert_eq!(body_structure.encoding(), None);
+        assert_eq!(body_structure.size(), None);
+        assert!(!body_structure.is_attachment());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! See <https://tools.ietf.org/html/rfc3501#section-6>.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt::Display};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
@@ -12,23 +12,34 @@ use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ext_id")]
 use crate::core::{IString, NString};
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::GmailLabel;
 #[cfg(feature = "ext_metadata")]
 use crate::extensions::metadata::{Entry, EntryValue, GetMetadataOption};
 use crate::{
     auth::AuthMechanism,
     command::error::{AppendError, CopyError, ListError, LoginError, RenameError},
-    core::{AString, Charset, Literal, Tag, Vec1},
+    core::{AString, Atom, Charset, Literal, Tag, Vec1},
     datetime::DateTime,
     extensions::{
-        binary::LiteralOrLiteral8, compress::CompressionAlgorithm, enable::CapabilityEnable,
-        quota::QuotaSet, sort::SortCriterion, thread::ThreadingAlgorithm,
+        binary::LiteralOrLiteral8,
+        compress::CompressionAlgorithm,
+        enable::CapabilityEnable,
+        esearch::SearchReturnOption,
+        multisearch::FilterMailboxes,
+        quota::QuotaSet,
+        sort::{SortAlgorithm, SortCriterion, SortKey},
+        tagged_ext::{ListOption, TaggedExt, TaggedExtParam},
+        thread::ThreadingAlgorithm,
     },
-    fetch::MacroOrMessageDataItemNames,
+    fetch::{MacroOrMessageDataItemNames, MessageDataItemName},
     flag::{Flag, StoreResponse, StoreType},
     mailbox::{ListMailbox, Mailbox},
+    response::Capability,
     search::SearchKey,
     secret::Secret,
-    sequence::SequenceSet,
+    sequence::{SeqOrUid, SequenceSet},
+    state::State,
     status::StatusDataItemName,
 };
 
@@ -396,6 +407,8 @@ pub enum CommandBody<'a> {
     Select {
         /// Mailbox.
         mailbox: Mailbox<'a>,
+        /// Select parameters, e.g., `CONDSTORE` (RFC 4466).
+        parameters: Vec<TaggedExtParam<'a>>,
     },
 
     /// Unselect a mailbox.
@@ -407,6 +420,20 @@ pub enum CommandBody<'a> {
     /// </div>
     Unselect,
 
+    /// Reset the connection to the NOT AUTHENTICATED state.
+    ///
+    /// After a successful UNAUTHENTICATE, the server has released all state associated with the
+    /// authenticated user (including a selected mailbox and ENABLEd extensions) and the client may
+    /// issue LOGIN or AUTHENTICATE again on the same connection.
+    ///
+    /// Note: A security layer negotiated by a SASL mechanism can't be reset. Thus, the server will
+    /// fail UNAUTHENTICATE in that case.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the UNAUTHENTICATE capability.
+    /// </div>
+    Unauthenticate,
+
     /// 6.3.2.  EXAMINE Command
     ///
     /// Arguments:  mailbox name
@@ -429,6 +456,8 @@ pub enum CommandBody<'a> {
     Examine {
         /// Mailbox.
         mailbox: Mailbox<'a>,
+        /// Examine parameters, e.g., `CONDSTORE` (RFC 4466).
+        parameters: Vec<TaggedExtParam<'a>>,
     },
 
     /// ### 6.3.3.  CREATE Command
@@ -561,6 +590,8 @@ pub enum CommandBody<'a> {
         from: Mailbox<'a>,
         /// New name.
         to: Mailbox<'a>,
+        /// Rename parameters (RFC 4466).
+        parameters: Vec<TaggedExtParam<'a>>,
     },
 
     /// ### 6.3.6.  SUBSCRIBE Command
@@ -741,10 +772,14 @@ pub enum CommandBody<'a> {
     /// failure; it is not relevant whether the user's real INBOX resides
     /// on this or some other server.
     List {
+        /// Selection options, e.g., `SUBSCRIBED` (RFC 5258).
+        select_options: Vec<ListOption<'a>>,
         /// Reference.
         reference: Mailbox<'a>,
         /// Mailbox (wildcard).
         mailbox_wildcard: ListMailbox<'a>,
+        /// Return options, e.g., `CHILDREN` (RFC 5258).
+        return_options: Vec<ListOption<'a>>,
     },
 
     /// ### 6.3.9.  LSUB Command
@@ -899,6 +934,8 @@ pub enum CommandBody<'a> {
         flags: Vec<Flag<'a>>,
         /// Datetime.
         date: Option<DateTime>,
+        /// Append extensions (RFC 4466).
+        extensions: Vec<TaggedExt<'a>>,
         /// Message to append.
         ///
         /// <div class="warning">
@@ -1145,6 +1182,8 @@ pub enum CommandBody<'a> {
         sequence_set: SequenceSet,
         /// Message data items (or a macro).
         macro_or_item_names: MacroOrMessageDataItemNames<'a>,
+        /// Fetch modifiers, e.g., `CHANGEDSINCE 12345` (RFC 4466).
+        modifiers: Vec<TaggedExtParam<'a>>,
         /// Use UID variant.
         uid: bool,
     },
@@ -1202,6 +1241,8 @@ pub enum CommandBody<'a> {
     Store {
         /// Set of messages.
         sequence_set: SequenceSet,
+        /// Store modifiers, e.g., `UNCHANGEDSINCE 12345` (RFC 4466).
+        modifiers: Vec<TaggedExtParam<'a>>,
         /// Kind of storage, i.e., replace, add, or remove.
         kind: StoreType,
         /// Kind of response, i.e., answer or silent.
@@ -1491,6 +1532,105 @@ pub enum CommandBody<'a> {
         uid: bool,
     },
 
+    /// REPLACE command.
+    ///
+    /// Atomically appends a message to a mailbox and removes the message identified by `sequence`
+    /// from the selected mailbox (as if by `STORE +FLAGS.SILENT \Deleted` followed by an expunge of
+    /// that single message).
+    ///
+    /// When the server supports UIDPLUS, the tagged OK response carries an
+    /// [`APPENDUID`](crate::response::Code::AppendUid) code for the newly created message.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the REPLACE capability.
+    /// </div>
+    Replace {
+        /// Message (sequence number or UID) to be replaced.
+        sequence: SeqOrUid,
+        /// Mailbox the new message is appended to.
+        mailbox: Mailbox<'a>,
+        /// Flags.
+        flags: Vec<Flag<'a>>,
+        /// Datetime.
+        date: Option<DateTime>,
+        /// Replacement message.
+        ///
+        /// <div class="warning">
+        /// Use [`LiteralOrLiteral8::Literal8`] only when the server advertised [`Capability::Binary`](crate::response::Capability::Binary).
+        /// </div>
+        message: LiteralOrLiteral8<'a>,
+        /// Use UID variant.
+        uid: bool,
+    },
+
+    /// CONVERT command.
+    ///
+    /// Requests the server to convert (parts of) messages, e.g., to transcode images or character
+    /// sets, without fetching them first. Conversions are requested via
+    /// [`MessageDataItemName::BinaryConvert`] and returned in a
+    /// [`Data::Converted`](crate::response::Data::Converted) response.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the CONVERT capability.
+    /// </div>
+    Convert {
+        /// Set of messages.
+        sequence_set: SequenceSet,
+        /// Message data items.
+        item_names: Vec1<MessageDataItemName<'a>>,
+        /// Use UID variant.
+        uid: bool,
+    },
+
+    /// ESEARCH command.
+    ///
+    /// Searches one or more mailboxes. The results are returned as
+    /// [`Data::ESearch`](crate::response::Data::ESearch) responses (one per mailbox) and always
+    /// contain UIDs.
+    ///
+    /// Data:
+    /// * untagged responses: ESEARCH
+    ///
+    /// Result:
+    /// * OK - search completed
+    /// * NO - search error: can't search that charset or criteria
+    /// * BAD - command unknown or arguments invalid
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the MULTISEARCH capability.
+    /// </div>
+    ESearch {
+        /// Mailboxes to search (`None` means the selected mailbox).
+        source: Option<Vec1<FilterMailboxes<'a>>>,
+        /// Search return options.
+        return_options: Vec<SearchReturnOption>,
+        /// Charset.
+        charset: Option<Charset<'a>>,
+        /// Criteria.
+        criteria: Vec1<SearchKey<'a>>,
+    },
+
+    #[cfg(feature = "ext_gmail")]
+    /// Alter the Gmail labels of messages (`[+|-]X-GM-LABELS[.SILENT]`).
+    ///
+    /// This is a STORE command and is encoded as such.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the X-GM-EXT-1 capability.
+    /// </div>
+    StoreGmailLabels {
+        /// Set of messages.
+        sequence_set: SequenceSet,
+        /// Kind of storage, i.e., replace, add, or remove.
+        kind: StoreType,
+        /// Kind of response, i.e., answer or silent.
+        response: StoreResponse,
+        /// Labels.
+        labels: Vec<GmailLabel<'a>>,
+        /// Use UID variant.
+        uid: bool,
+    },
+
     #[cfg(feature = "ext_id")]
     /// ID command.
     ///
@@ -1584,6 +1724,7 @@ impl<'a> CommandBody<'a> {
     {
         Ok(CommandBody::Select {
             mailbox: mailbox.try_into()?,
+            parameters: vec![],
         })
     }
 
@@ -1594,6 +1735,7 @@ impl<'a> CommandBody<'a> {
     {
         Ok(CommandBody::Examine {
             mailbox: mailbox.try_into()?,
+            parameters: vec![],
         })
     }
 
@@ -1626,6 +1768,7 @@ impl<'a> CommandBody<'a> {
         Ok(CommandBody::Rename {
             from: mailbox.try_into().map_err(RenameError::From)?,
             to: new_mailbox.try_into().map_err(RenameError::To)?,
+            parameters: vec![],
         })
     }
 
@@ -1659,8 +1802,10 @@ impl<'a> CommandBody<'a> {
         B: TryInto<ListMailbox<'a>>,
     {
         Ok(CommandBody::List {
+            select_options: vec![],
             reference: reference.try_into().map_err(ListError::Reference)?,
             mailbox_wildcard: mailbox_wildcard.try_into().map_err(ListError::Mailbox)?,
+            return_options: vec![],
         })
     }
 
@@ -1708,6 +1853,7 @@ impl<'a> CommandBody<'a> {
             mailbox: mailbox.try_into().map_err(AppendError::Mailbox)?,
             flags,
             date,
+            extensions: vec![],
             message: LiteralOrLiteral8::Literal(message.try_into().map_err(AppendError::Data)?),
         })
     }
@@ -1732,6 +1878,7 @@ impl<'a> CommandBody<'a> {
         Ok(CommandBody::Fetch {
             sequence_set,
             macro_or_item_names: macro_or_item_names.into(),
+            modifiers: vec![],
             uid,
         })
     }
@@ -1751,6 +1898,7 @@ impl<'a> CommandBody<'a> {
 
         Ok(CommandBody::Store {
             sequence_set,
+            modifiers: vec![],
             kind,
             response,
             flags,
@@ -1789,6 +1937,7 @@ impl<'a> CommandBody<'a> {
             Self::Sort { .. } => "SORT",
             Self::Thread { .. } => "THREAD",
             Self::Unselect => "UNSELECT",
+            Self::Unauthenticate => "UNAUTHENTICATE",
             Self::Examine { .. } => "EXAMINE",
             Self::Create { .. } => "CREATE",
             Self::Delete { .. } => "DELETE",
@@ -1814,6 +1963,11 @@ impl<'a> CommandBody<'a> {
             Self::GetQuotaRoot { .. } => "GETQUOTAROOT",
             Self::SetQuota { .. } => "SETQUOTA",
             Self::Move { .. } => "MOVE",
+            Self::Replace { .. } => "REPLACE",
+            Self::Convert { .. } => "CONVERT",
+            Self::ESearch { .. } => "ESEARCH",
+            #[cfg(feature = "ext_gmail")]
+            Self::StoreGmailLabels { .. } => "STORE",
             #[cfg(feature = "ext_id")]
             Self::Id { .. } => "ID",
             #[cfg(feature = "ext_metadata")]
@@ -1822,6 +1976,383 @@ impl<'a> CommandBody<'a> {
             Self::GetMetadata { .. } => "GETMETADATA",
         }
     }
+
+    // ----- Validity -----
+
+    /// Returns whether the command is valid in `state` (RFC 3501, section 6, and extensions).
+    ///
+    /// No command is valid before the greeting, during IDLE, or after logout.
+    pub fn is_valid_in(&self, state: &State) -> bool {
+        let (not_authenticated, authenticated, selected) = match self {
+            // ----- Any State -----
+            Self::Capability | Self::Noop | Self::Logout => (true, true, true),
+            #[cfg(feature = "ext_id")]
+            Self::Id { .. } => (true, true, true),
+
+            // ----- Not Authenticated State -----
+            #[cfg(feature = "starttls")]
+            Self::StartTLS => (true, false, false),
+            Self::Authenticate { .. } | Self::Login { .. } => (true, false, false),
+
+            // ----- Authenticated State -----
+            // "The ENABLE command is only valid in the authenticated state [...]" (RFC 5161)
+            Self::Enable { .. } => (false, true, false),
+            Self::Select { .. }
+            | Self::Examine { .. }
+            | Self::Create { .. }
+            | Self::Delete { .. }
+            | Self::Rename { .. }
+            | Self::Subscribe { .. }
+            | Self::Unsubscribe { .. }
+            | Self::Lsub { .. }
+            | Self::Status { .. }
+            | Self::Append { .. }
+            | Self::Unauthenticate
+            | Self::Idle
+            | Self::Compress { .. }
+            | Self::GetQuota { .. }
+            | Self::GetQuotaRoot { .. }
+            | Self::SetQuota { .. } => (false, true, true),
+            #[cfg(feature = "ext_metadata")]
+            Self::SetMetadata { .. } | Self::GetMetadata { .. } => (false, true, true),
+            // Without a source, the selected mailbox is searched (RFC 7377).
+            Self::ESearch {
+                source: Some(_), ..
+            } => (false, true, true),
+
+            // ----- Selected State -----
+            Self::ESearch { source: None, .. }
+            | Self::Check
+            | Self::Close
+            | Self::Unselect
+            | Self::Expunge
+            | Self::ExpungeUid { .. }
+            | Self::Search { .. }
+            | Self::Sort { .. }
+            | Self::Thread { .. }
+            | Self::Fetch { .. }
+            | Self::Store { .. }
+            | Self::Copy { .. }
+            | Self::Move { .. }
+            | Self::Replace { .. }
+            | Self::Convert { .. } => (false, false, true),
+            #[cfg(feature = "ext_gmail")]
+            Self::StoreGmailLabels { .. } => (false, false, true),
+        };
+
+        match state {
+            State::NotAuthenticated => not_authenticated,
+            State::Authenticated => authenticated,
+            State::Selected(_) => selected,
+            State::Greeting
+            | State::Logout
+            | State::IdleAuthenticated(_)
+            | State::IdleSelected(_, _) => false,
+        }
+    }
+
+    /// Returns what the server must support before the command can be used.
+    ///
+    /// All returned requirements must be met. An empty list means that the command is part of
+    /// IMAP4rev1. Use [`Requirement::is_met`] to check the requirements against the advertised
+    /// (and enabled) capabilities.
+    ///
+    /// Note: LOGIN must not be used when the server advertised `LOGINDISABLED`.
+    pub fn requirements(&self) -> Vec<Requirement<'a>> {
+        let requires = |capability| vec![Requirement::Capability(capability)];
+
+        match self {
+            #[cfg(feature = "starttls")]
+            Self::StartTLS => requires(Capability::StartTls),
+            Self::Authenticate {
+                mechanism,
+                initial_response,
+            } => {
+                let mut requirements = requires(Capability::Auth(mechanism.clone()));
+
+                if initial_response.is_some() {
+                    requirements.push(Requirement::Capability(Capability::SaslIr));
+                }
+
+                requirements
+            }
+            Self::Unauthenticate => requires(Capability::Unauthenticate),
+            Self::Select { parameters, .. } | Self::Examine { parameters, .. } => {
+                ext_requirements(parameters)
+            }
+            Self::Fetch {
+                macro_or_item_names,
+                modifiers,
+                ..
+            } => {
+                let mut requirements = ext_requirements(modifiers);
+
+                if let MacroOrMessageDataItemNames::MessageDataItemNames(item_names) =
+                    macro_or_item_names
+                {
+                    for requirement in fetch_requirements(item_names) {
+                        if !requirements.contains(&requirement) {
+                            requirements.push(requirement);
+                        }
+                    }
+                }
+
+                requirements
+            }
+            Self::Store { modifiers, .. } => ext_requirements(modifiers),
+            Self::Unselect => requires(Capability::Unselect),
+            Self::ExpungeUid { .. } => requires(Capability::UidPlus),
+            Self::Search { criteria, .. } => search_requirements(criteria.as_ref()),
+            Self::Sort {
+                sort_criteria,
+                search_criteria,
+                ..
+            } => {
+                let mut requirements = requires(Capability::Sort(None));
+
+                // DISPLAYFROM and DISPLAYTO require `SORT=DISPLAY` (RFC 5957).
+                if sort_criteria.as_ref().iter().any(|criterion| {
+                    matches!(criterion.key, SortKey::DisplayFrom | SortKey::DisplayTo)
+                }) {
+                    requirements.push(Requirement::Capability(Capability::Sort(Some(
+                        SortAlgorithm::Display,
+                    ))));
+                }
+
+                requirements.extend(search_requirements(search_criteria.as_ref()));
+                requirements
+            }
+            Self::Thread {
+                algorithm,
+                search_criteria,
+                ..
+            } => {
+                let mut requirements = requires(Capability::Thread(algorithm.clone()));
+                requirements.extend(search_requirements(search_criteria.as_ref()));
+                requirements
+            }
+            Self::Idle => requires(Capability::Idle),
+            Self::Enable { .. } => requires(Capability::Enable),
+            Self::Compress { algorithm } => requires(Capability::Compress {
+                algorithm: algorithm.clone(),
+            }),
+            Self::GetQuota { .. } | Self::GetQuotaRoot { .. } => requires(Capability::Quota),
+            Self::SetQuota { .. } => vec![
+                Requirement::Capability(Capability::Quota),
+                Requirement::Capability(Capability::QuotaSet),
+            ],
+            Self::Move { .. } => requires(Capability::Move),
+            Self::Replace { message, .. } => {
+                let mut requirements = requires(Capability::Replace);
+
+                if let LiteralOrLiteral8::Literal8(_) = message {
+                    requirements.push(Requirement::Capability(Capability::Binary));
+                }
+
+                requirements
+            }
+            Self::Convert { .. } => requires(Capability::Convert),
+            Self::ESearch {
+                return_options,
+                criteria,
+                ..
+            } => {
+                let mut requirements = requires(Capability::MultiSearch);
+                requirements.extend(search_requirements(criteria.as_ref()));
+
+                // RELEVANCY requires `SEARCH=FUZZY` (RFC 6203).
+                let fuzzy = Requirement::Capability(Capability::SearchFuzzy);
+                if return_options.contains(&SearchReturnOption::Relevancy)
+                    && !requirements.contains(&fuzzy)
+                {
+                    requirements.push(fuzzy);
+                }
+
+                requirements
+            }
+            // "Servers that support the extended LIST command [...] MUST include "LIST-EXTENDED"
+            // in the CAPABILITY response" (RFC 5258)
+            Self::List {
+                select_options,
+                return_options,
+                ..
+            } => {
+                if select_options.is_empty() && return_options.is_empty() {
+                    vec![]
+                } else {
+                    requires(Capability::ListExtended)
+                }
+            }
+            // LITERAL8 requires `BINARY` (RFC 3516).
+            Self::Append {
+                message: LiteralOrLiteral8::Literal8(_),
+                ..
+            } => requires(Capability::Binary),
+            #[cfg(feature = "ext_gmail")]
+            Self::StoreGmailLabels { .. } => requires(Capability::GmailExt1),
+            #[cfg(feature = "ext_id")]
+            Self::Id { .. } => requires(Capability::Id),
+            // "A server that supports only server annotations indicates the presence of this
+            // extension by returning "METADATA-SERVER" [...]" (RFC 5464)
+            #[cfg(feature = "ext_metadata")]
+            Self::SetMetadata { mailbox, .. } | Self::GetMetadata { mailbox, .. } => {
+                if matches!(mailbox, Mailbox::Other(other) if other.as_ref().is_empty()) {
+                    requires(Capability::MetadataServer)
+                } else {
+                    requires(Capability::Metadata)
+                }
+            }
+            Self::Capability
+            | Self::Noop
+            | Self::Logout
+            | Self::Login { .. }
+            | Self::Create { .. }
+            | Self::Delete { .. }
+            | Self::Rename { .. }
+            | Self::Subscribe { .. }
+            | Self::Unsubscribe { .. }
+            | Self::List { .. }
+            | Self::Lsub { .. }
+            | Self::Status { .. }
+            | Self::Append { .. }
+            | Self::Check
+            | Self::Close
+            | Self::Expunge
+            | Self::Copy { .. } => vec![],
+        }
+    }
+}
+
+/// Requirements of SELECT/EXAMINE parameters and FETCH/STORE modifiers (RFC 7162).
+fn ext_requirements<'a>(params: &[TaggedExtParam]) -> Vec<Requirement<'a>> {
+    let mut requirements = Vec::new();
+
+    for param in params {
+        let name = param.name.inner();
+
+        let requirement =
+            if name.eq_ignore_ascii_case("QRESYNC") || name.eq_ignore_ascii_case("VANISHED") {
+                Requirement::Enabled(CapabilityEnable::from(Atom::unvalidated("QRESYNC")))
+            } else if name.eq_ignore_ascii_case("CONDSTORE")
+                || name.eq_ignore_ascii_case("CHANGEDSINCE")
+                || name.eq_ignore_ascii_case("UNCHANGEDSINCE")
+            {
+                Requirement::Capability(Capability::CondStore)
+            } else {
+                continue;
+            };
+
+        if !requirements.contains(&requirement) {
+            requirements.push(requirement);
+        }
+    }
+
+    requirements
+}
+
+/// Requirements of FETCH data items.
+fn fetch_requirements<'a>(item_names: &[MessageDataItemName]) -> Vec<Requirement<'a>> {
+    let mut requirements = Vec::new();
+
+    for item_name in item_names {
+        let capability = match item_name {
+            MessageDataItemName::Binary { .. } | MessageDataItemName::BinarySize { .. } => {
+                Capability::Binary
+            }
+            MessageDataItemName::BinaryConvert { .. } => Capability::Convert,
+            MessageDataItemName::Preview { .. } => Capability::Preview,
+            #[cfg(feature = "ext_gmail")]
+            MessageDataItemName::GmailMessageId
+            | MessageDataItemName::GmailThreadId
+            | MessageDataItemName::GmailLabels => Capability::GmailExt1,
+            _ => continue,
+        };
+
+        let requirement = Requirement::Capability(capability);
+
+        if !requirements.contains(&requirement) {
+            requirements.push(requirement);
+        }
+    }
+
+    requirements
+}
+
+/// Requirements of search keys (including nested search keys).
+fn search_requirements<'a>(criteria: &[SearchKey]) -> Vec<Requirement<'a>> {
+    let mut requirements = Vec::new();
+    let mut stack: Vec<&SearchKey> = criteria.iter().collect();
+
+    while let Some(key) = stack.pop() {
+        let capability = match key {
+            SearchKey::And(keys) => {
+                stack.extend(keys.as_ref());
+                continue;
+            }
+            SearchKey::Not(key) => {
+                stack.push(key);
+                continue;
+            }
+            SearchKey::Or(left, right) => {
+                stack.push(left);
+                stack.push(right);
+                continue;
+            }
+            SearchKey::Fuzzy(key) => {
+                stack.push(key);
+                Capability::SearchFuzzy
+            }
+            #[cfg(feature = "ext_gmail")]
+            SearchKey::GmailRaw(_)
+            | SearchKey::GmailMessageId(_)
+            | SearchKey::GmailThreadId(_)
+            | SearchKey::GmailLabels(_) => Capability::GmailExt1,
+            _ => continue,
+        };
+
+        let requirement = Requirement::Capability(capability);
+
+        if !requirements.contains(&requirement) {
+            requirements.push(requirement);
+        }
+    }
+
+    requirements
+}
+
+/// Server support required for a command, see [`CommandBody::requirements`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum Requirement<'a> {
+    /// The server must have advertised the capability.
+    Capability(Capability<'a>),
+    /// The capability must have been enabled using ENABLE (RFC 5161).
+    Enabled(CapabilityEnable<'a>),
+}
+
+impl<'a> Requirement<'a> {
+    /// Returns whether the requirement is met given the `advertised` and `enabled` capabilities.
+    ///
+    /// Capabilities are compared case-insensitively.
+    pub fn is_met(&self, advertised: &[Capability], enabled: &[CapabilityEnable]) -> bool {
+        fn eq(a: impl Display, b: impl Display) -> bool {
+            a.to_string().eq_ignore_ascii_case(&b.to_string())
+        }
+
+        match self {
+            Self::Capability(required) => advertised.iter().any(|capability| {
+                match (required, capability) {
+                    // `SORT=<algorithm>` implies `SORT` (RFC 5957).
+                    (Capability::Sort(None), Capability::Sort(_)) => true,
+                    // `METADATA` implies `METADATA-SERVER` (RFC 5464).
+                    #[cfg(feature = "ext_metadata")]
+                    (Capability::MetadataServer, Capability::Metadata) => true,
+                    _ => eq(capability, required),
+                }
+            }),
+            Self::Enabled(required) => enabled.iter().any(|capability| eq(capability, required)),
+        }
+    }
 }
 
 /// Error-related types.
@@ -1881,7 +2412,9 @@ mod tests {
         extensions::{
             binary::Literal8,
             compress::CompressionAlgorithm,
+            convert::Conversion,
             enable::{CapabilityEnable, Utf8Kind},
+            sort::SortAlgorithm,
         },
         fetch::{Macro, MacroOrMessageDataItemNames, MessageDataItemName, Part, Section},
         flag::{Flag, StoreType},
@@ -2096,13 +2629,16 @@ mod tests {
             (
                 CommandBody::Select {
                     mailbox: Mailbox::Inbox,
+                    parameters: vec![],
                 },
                 "SELECT",
             ),
             (CommandBody::Unselect, "UNSELECT"),
+            (CommandBody::Unauthenticate, "UNAUTHENTICATE"),
             (
                 CommandBody::Examine {
                     mailbox: Mailbox::Inbox,
+                    parameters: vec![],
                 },
                 "EXAMINE",
             ),
@@ -2122,6 +2658,7 @@ mod tests {
                 CommandBody::Rename {
                     from: Mailbox::Inbox,
                     to: Mailbox::Inbox,
+                    parameters: vec![],
                 },
                 "RENAME",
             ),
@@ -2139,8 +2676,10 @@ mod tests {
             ),
             (
                 CommandBody::List {
+                    select_options: vec![],
                     reference: Mailbox::Inbox,
                     mailbox_wildcard: ListMailbox::try_from("").unwrap(),
+                    return_options: vec![],
                 },
                 "LIST",
             ),
@@ -2163,6 +2702,7 @@ mod tests {
                     mailbox: Mailbox::Inbox,
                     flags: vec![],
                     date: None,
+                    extensions: vec![],
                     message: LiteralOrLiteral8::Literal(Literal::try_from("").unwrap()),
                 },
                 "APPEND",
@@ -2172,6 +2712,7 @@ mod tests {
                     mailbox: Mailbox::Inbox,
                     flags: vec![],
                     date: None,
+                    extensions: vec![],
                     message: LiteralOrLiteral8::Literal8(Literal8 {
                         data: b"Hello\x00World\x00".as_ref().into(),
                         mode: LiteralMode::NonSync,
@@ -2194,6 +2735,7 @@ mod tests {
                 CommandBody::Fetch {
                     sequence_set: SequenceSet::try_from(1u32).unwrap(),
                     macro_or_item_names: MacroOrMessageDataItemNames::Macro(Macro::Full),
+                    modifiers: vec![],
                     uid: true,
                 },
                 "FETCH",
@@ -2201,6 +2743,7 @@ mod tests {
             (
                 CommandBody::Store {
                     sequence_set: SequenceSet::try_from(1).unwrap(),
+                    modifiers: vec![],
                     flags: vec![],
                     response: StoreResponse::Silent,
                     kind: StoreType::Add,
@@ -2256,10 +2799,357 @@ mod tests {
                 },
                 "MOVE",
             ),
+            (
+                CommandBody::Replace {
+                    sequence: SeqOrUid::try_from(1).unwrap(),
+                    mailbox: Mailbox::Inbox,
+                    flags: vec![],
+                    date: None,
+                    message: LiteralOrLiteral8::Literal(Literal::try_from("").unwrap()),
+                    uid: true,
+                },
+                "REPLACE",
+            ),
+            (
+                CommandBody::Convert {
+                    sequence_set: SequenceSet::try_from(1).unwrap(),
+                    item_names: Vec1::from(MessageDataItemName::BinarySize { section: vec![] }),
+                    uid: false,
+                },
+                "CONVERT",
+            ),
+            (
+                CommandBody::ESearch {
+                    source: None,
+                    return_options: vec![],
+                    charset: None,
+                    criteria: Vec1::from(SearchKey::All),
+                },
+                "ESEARCH",
+            ),
         ];
 
         for (test, expected) in tests {
             assert_eq!(test.name(), expected);
         }
     }
+
+    #[test]
+    fn test_command_body_is_valid_in() {
+        let tests = [
+            (CommandBody::Noop, [true, true, true]),
+            (
+                CommandBody::login("alice", "password").unwrap(),
+                [true, false, false],
+            ),
+            (
+                CommandBody::enable(vec![CapabilityEnable::Utf8(Utf8Kind::Accept)]).unwrap(),
+                [false, true, false],
+            ),
+            (CommandBody::select("INBOX").unwrap(), [false, true, true]),
+            (CommandBody::Unauthenticate, [false, true, true]),
+            (CommandBody::Idle, [false, true, true]),
+            (CommandBody::Close, [false, false, true]),
+            (CommandBody::Unselect, [false, false, true]),
+            (
+                CommandBody::fetch("1:*", Macro::Fast, false).unwrap(),
+                [false, false, true],
+            ),
+        ];
+
+        for (test, [not_authenticated, authenticated, selected]) in tests {
+            assert_eq!(
+                test.is_valid_in(&State::NotAuthenticated),
+                not_authenticated
+            );
+            assert_eq!(test.is_valid_in(&State::Authenticated), authenticated);
+            assert_eq!(test.is_valid_in(&State::Selected(Mailbox::Inbox)), selected);
+            assert!(!test.is_valid_in(&State::Greeting));
+            assert!(!test.is_valid_in(&State::Logout));
+            assert!(!test.is_valid_in(&State::IdleAuthenticated(Tag::try_from("A").unwrap())));
+        }
+    }
+
+    #[test]
+    fn test_command_body_requirements() {
+        let tests = [
+            (CommandBody::Noop, vec![]),
+            (CommandBody::Unselect, vec![Capability::Unselect]),
+            (
+                CommandBody::r#move("1", "Archive", true).unwrap(),
+                vec![Capability::Move],
+            ),
+            (
+                CommandBody::compress(CompressionAlgorithm::Deflate),
+                vec![Capability::Compress {
+                    algorithm: CompressionAlgorithm::Deflate,
+                }],
+            ),
+            (
+                CommandBody::authenticate_with_ir(
+                    AuthMechanism::Plain,
+                    b"\x00alice\x00password".as_ref(),
+                ),
+                vec![Capability::Auth(AuthMechanism::Plain), Capability::SaslIr],
+            ),
+            (
+                CommandBody::Thread {
+                    algorithm: ThreadingAlgorithm::References,
+                    charset: Charset::try_from("UTF-8").unwrap(),
+                    search_criteria: Vec1::from(SearchKey::All),
+                    uid: true,
+                },
+                vec![Capability::Thread(ThreadingAlgorithm::References)],
+            ),
+            (
+                CommandBody::Thread {
+                    algorithm: ThreadingAlgorithm::References,
+                    charset: Charset::try_from("UTF-8").unwrap(),
+                    search_criteria: Vec1::from(SearchKey::Not(Box::new(SearchKey::Fuzzy(
+                        Box::new(SearchKey::Subject(AString::try_from("foo").unwrap())),
+                    )))),
+                    uid: true,
+                },
+                vec![
+                    Capability::Thread(ThreadingAlgorithm::References),
+                    Capability::SearchFuzzy,
+                ],
+            ),
+            (
+                CommandBody::search(None, Vec1::from(SearchKey::All), false),
+                vec![],
+            ),
+            (
+                CommandBody::search(
+                    None,
+                    Vec1::try_from(vec![
+                        SearchKey::Fuzzy(Box::new(SearchKey::Text(
+                            AString::try_from("foo").unwrap(),
+                        ))),
+                        SearchKey::Or(
+                            Box::new(SearchKey::All),
+                            Box::new(SearchKey::Fuzzy(Box::new(SearchKey::Text(
+                                AString::try_from("bar").unwrap(),
+                            )))),
+                        ),
+                    ])
+                    .unwrap(),
+                    true,
+                ),
+                vec![Capability::SearchFuzzy],
+            ),
+            (
+                CommandBody::Sort {
+                    sort_criteria: Vec1::from(SortCriterion {
+                        reverse: false,
+                        key: SortKey::Date,
+                    }),
+                    charset: Charset::try_from("UTF-8").unwrap(),
+                    search_criteria: Vec1::from(SearchKey::All),
+                    uid: false,
+                },
+                vec![Capability::Sort(None)],
+            ),
+            (
+                CommandBody::Sort {
+                    sort_criteria: Vec1::try_from(vec![
+                        SortCriterion {
+                            reverse: true,
+                            key: SortKey::DisplayFrom,
+                        },
+                        SortCriterion {
+                            reverse: false,
+                            key: SortKey::DisplayTo,
+                        },
+                    ])
+                    .unwrap(),
+                    charset: Charset::try_from("UTF-8").unwrap(),
+                    search_criteria: Vec1::from(SearchKey::Fuzzy(Box::new(SearchKey::All))),
+                    uid: false,
+                },
+                vec![
+                    Capability::Sort(None),
+                    Capability::Sort(Some(SortAlgorithm::Display)),
+                    Capability::SearchFuzzy,
+                ],
+            ),
+            (
+                CommandBody::ESearch {
+                    source: None,
+                    return_options: vec![SearchReturnOption::Relevancy],
+                    charset: None,
+                    criteria: Vec1::from(SearchKey::Fuzzy(Box::new(SearchKey::All))),
+                },
+                vec![Capability::MultiSearch, Capability::SearchFuzzy],
+            ),
+            (
+                CommandBody::ESearch {
+                    source: None,
+                    return_options: vec![SearchReturnOption::Relevancy],
+                    charset: None,
+                    criteria: Vec1::from(SearchKey::All),
+                },
+                vec![Capability::MultiSearch, Capability::SearchFuzzy],
+            ),
+            (
+                CommandBody::SetQuota {
+                    root: AString::try_from("").unwrap(),
+                    quotas: vec![],
+                },
+                vec![Capability::Quota, Capability::QuotaSet],
+            ),
+            (CommandBody::fetch("1", Macro::Full, false).unwrap(), vec![]),
+            (
+                CommandBody::fetch(
+                    "1",
+                    vec![
+                        MessageDataItemName::Preview { lazy: true },
+                        MessageDataItemName::Binary {
+                            section: vec![],
+                            partial: None,
+                            peek: true,
+                        },
+                        MessageDataItemName::BinarySize { section: vec![] },
+                        MessageDataItemName::BinaryConvert {
+                            section: vec![],
+                            conversion: Conversion::new("text/plain", vec![]).unwrap(),
+                            partial: None,
+                            peek: false,
+                        },
+                    ],
+                    true,
+                )
+                .unwrap(),
+                vec![Capability::Preview, Capability::Binary, Capability::Convert],
+            ),
+            (
+                CommandBody::Fetch {
+                    sequence_set: SequenceSet::try_from("1").unwrap(),
+                    macro_or_item_names: vec![MessageDataItemName::Binary {
+                        section: vec![],
+                        partial: None,
+                        peek: false,
+                    }]
+                    .into(),
+                    modifiers: vec![TaggedExtParam::new("CHANGEDSINCE").unwrap()],
+                    uid: false,
+                },
+                vec![Capability::CondStore, Capability::Binary],
+            ),
+            (
+                CommandBody::append("INBOX", vec![], None, "").unwrap(),
+                vec![],
+            ),
+            (
+                CommandBody::Append {
+                    mailbox: Mailbox::Inbox,
+                    flags: vec![],
+                    date: None,
+                    extensions: vec![],
+                    message: LiteralOrLiteral8::Literal8(Literal8 {
+                        data: b"\x00".as_ref().into(),
+                        mode: LiteralMode::Sync,
+                    }),
+                },
+                vec![Capability::Binary],
+            ),
+            (
+                CommandBody::Replace {
+                    sequence: SeqOrUid::Value(1.try_into().unwrap()),
+                    mailbox: Mailbox::Inbox,
+                    flags: vec![],
+                    date: None,
+                    message: LiteralOrLiteral8::Literal8(Literal8 {
+                        data: b"\x00".as_ref().into(),
+                        mode: LiteralMode::Sync,
+                    }),
+                    uid: false,
+                },
+                vec![Capability::Replace, Capability::Binary],
+            ),
+            (CommandBody::list("", "*").unwrap(), vec![]),
+            (
+                CommandBody::List {
+                    select_options: vec![],
+                    reference: Mailbox::try_from("").unwrap(),
+                    mailbox_wildcard: ListMailbox::try_from("*").unwrap(),
+                    return_options: vec![ListOption {
+                        name: Atom::try_from("CHILDREN").unwrap(),
+                        value: None,
+                    }],
+                },
+                vec![Capability::ListExtended],
+            ),
+            (
+                CommandBody::List {
+                    select_options: vec![ListOption {
+                        name: Atom::try_from("SUBSCRIBED").unwrap(),
+                        value: None,
+                    }],
+                    reference: Mailbox::try_from("").unwrap(),
+                    mailbox_wildcard: ListMailbox::try_from("*").unwrap(),
+                    return_options: vec![],
+                },
+                vec![Capability::ListExtended],
+            ),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(
+                test.requirements(),
+                expected
+                    .into_iter()
+                    .map(Requirement::Capability)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[cfg(feature = "ext_gmail")]
+    #[test]
+    fn test_command_body_requirements_gmail() {
+        let fetch = CommandBody::fetch(
+            "1:*",
+            vec![
+                MessageDataItemName::GmailMessageId,
+                MessageDataItemName::GmailThreadId,
+                MessageDataItemName::GmailLabels,
+            ],
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fetch.requirements(),
+            vec![Requirement::Capability(Capability::GmailExt1)]
+        );
+    }
+
+    #[test]
+    fn test_requirement_is_met() {
+        let advertised = [
+            Capability::Imap4Rev1,
+            Capability::Sort(Some(SortAlgorithm::Display)),
+            Capability::try_from("condstore").unwrap(),
+        ];
+
+        assert!(Requirement::Capability(Capability::Sort(None)).is_met(&advertised, &[]));
+        assert!(!Requirement::Capability(Capability::Move).is_met(&advertised, &[]));
+
+        let requirements = CommandBody::Select {
+            mailbox: Mailbox::Inbox,
+            parameters: vec![
+                TaggedExtParam::new("CONDSTORE").unwrap(),
+                TaggedExtParam::new("QRESYNC").unwrap(),
+            ],
+        }
+        .requirements();
+        assert_eq!(requirements.len(), 2);
+        assert!(requirements[0].is_met(&advertised, &[]));
+        assert!(!requirements[1].is_met(&advertised, &[]));
+        assert!(requirements[1].is_met(
+            &advertised,
+            &[CapabilityEnable::try_from("qresync").unwrap()]
+        ));
+    }
 }
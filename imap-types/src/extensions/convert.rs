@@ -0,0 +1,127 @@
+//! IMAP CONVERT Extension
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc5259>.
+
+use std::num::NonZeroU32;
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::CommandBody,
+    core::{IString, Tag, Vec1},
+    extensions::convert::error::ConvertError,
+    fetch::{MessageDataItem, MessageDataItemName},
+    response::Data,
+    sequence::SequenceSet,
+};
+
+/// Conversion requested for a body part, e.g., `("image/jpeg" ("pix-x" "240" "pix-y" "180"))`.
+///
+/// The server converts the (decoded) body part to `media_type` using the conversion parameters
+/// in `params`. Parameter names are compared case-insensitively by the server.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct Conversion<'a> {
+    /// Target media type, e.g., `image/jpeg` or `text/plain;charset=utf-8`.
+    pub media_type: IString<'a>,
+    /// Conversion parameters (name/value pairs).
+    pub params: Vec<(IString<'a>, IString<'a>)>,
+}
+
+impl<'a> Conversion<'a> {
+    pub fn new<M>(media_type: M, params: Vec<(IString<'a>, IString<'a>)>) -> Result<Self, M::Error>
+    where
+        M: TryInto<IString<'a>>,
+    {
+        Ok(Self {
+            media_type: media_type.try_into()?,
+            params,
+        })
+    }
+}
+
+impl<'a> CommandBody<'a> {
+    /// Construct a CONVERT command.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the CONVERT capability.
+    /// </div>
+    pub fn convert<S, I>(
+        sequence_set: S,
+        item_names: I,
+        uid: bool,
+    ) -> Result<Self, ConvertError<S::Error, I::Error>>
+    where
+        S: TryInto<SequenceSet>,
+        I: TryInto<Vec1<MessageDataItemName<'a>>>,
+    {
+        Ok(CommandBody::Convert {
+            sequence_set: sequence_set.try_into().map_err(ConvertError::Sequence)?,
+            item_names: item_names.try_into().map_err(ConvertError::Items)?,
+            uid,
+        })
+    }
+}
+
+impl<'a> Data<'a> {
+    pub fn converted<S, I>(
+        seq: S,
+        tag: Tag<'a>,
+        items: I,
+    ) -> Result<Self, ConvertError<S::Error, I::Error>>
+    where
+        S: TryInto<NonZeroU32>,
+        I: TryInto<Vec1<MessageDataItem<'a>>>,
+    {
+        Ok(Self::Converted {
+            seq: seq.try_into().map_err(ConvertError::Sequence)?,
+            tag,
+            items: items.try_into().map_err(ConvertError::Items)?,
+        })
+    }
+}
+
+/// Error-related types.
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum ConvertError<S, I> {
+        #[error("Invalid sequence: {0:?}")]
+        Sequence(S),
+        #[error("Invalid items: {0:?}")]
+        Items(I),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_convert() {
+        assert!(CommandBody::convert(
+            "1:*",
+            vec![MessageDataItemName::BinaryConvert {
+                section: vec![NonZeroU32::new(2).unwrap()],
+                conversion: Conversion::new("image/png", vec![]).unwrap(),
+                partial: None,
+                peek: false,
+            }],
+            true,
+        )
+        .is_ok());
+        assert!(CommandBody::convert("1:*", vec![], false).is_err());
+        assert!(Data::converted(
+            0,
+            Tag::try_from("A").unwrap(),
+            vec![MessageDataItem::Uid(NonZeroU32::new(1).unwrap())]
+        )
+        .is_err());
+    }
+}
@@ -48,6 +48,8 @@ pub enum CapabilityEnable<'a> {
     #[cfg(feature = "ext_metadata")]
     /// Client can handle server annotations.
     MetadataServer,
+    /// Client wants to use UIDs only, see RFC 9586.
+    UidOnly,
     Other(CapabilityEnableOther<'a>),
 }
 
@@ -70,6 +72,7 @@ impl<'a> From<Atom<'a>> for CapabilityEnable<'a> {
             "metadata" => Self::Metadata,
             #[cfg(feature = "ext_metadata")]
             "metadata-server" => Self::MetadataServer,
+            "uidonly" => Self::UidOnly,
             _ => Self::Other(CapabilityEnableOther(atom)),
         }
     }
@@ -85,6 +88,7 @@ impl<'a> Display for CapabilityEnable<'a> {
             Self::Metadata => write!(f, "METADATA"),
             #[cfg(feature = "ext_metadata")]
             Self::MetadataServer => write!(f, "METADATA-SERVER"),
+            Self::UidOnly => write!(f, "UIDONLY"),
             Self::Other(other) => write!(f, "{}", other.0),
         }
     }
@@ -129,6 +133,10 @@ mod tests {
             CapabilityEnable::from(Atom::try_from("utf8=accept").unwrap()),
             CapabilityEnable::Utf8(Utf8Kind::Accept)
         );
+        assert_eq!(
+            CapabilityEnable::try_from("UIDonly").unwrap(),
+            CapabilityEnable::UidOnly
+        );
         assert_eq!(
             CapabilityEnable::try_from("utf").unwrap(),
             CapabilityEnable::Other(CapabilityEnableOther(Atom::try_from("utf").unwrap()))
@@ -0,0 +1,71 @@
+//! IMAP4 Extension to SEARCH Command for Controlling What Kind of Information Is Returned (ESEARCH)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc4731>.
+//!
+//! This module provides the search return options and the ESEARCH response data used by
+//! [MULTISEARCH](crate::extensions::multisearch).
+
+use std::num::NonZeroU32;
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::Tag, extensions::fuzzy::RelevancyScore, mailbox::Mailbox, sequence::SequenceSet,
+};
+
+/// Search return option, i.e., what kind of information the server should return.
+///
+/// No return option (or `RETURN ()`) is equivalent to [`SearchReturnOption::All`].
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
+pub enum SearchReturnOption {
+    /// Return the lowest message number/UID satisfying the search criteria.
+    Min,
+    /// Return the highest message number/UID satisfying the search criteria.
+    Max,
+    /// Return all message numbers/UIDs satisfying the search criteria.
+    All,
+    /// Return the number of messages satisfying the search criteria.
+    Count,
+    /// Return the relevancy scores of the messages satisfying the search criteria (RFC 6203).
+    Relevancy,
+}
+
+/// Search return data of an ESEARCH response.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum SearchReturnData {
+    /// `MIN`
+    Min(NonZeroU32),
+    /// `MAX`
+    Max(NonZeroU32),
+    /// `ALL`
+    All(SequenceSet),
+    /// `COUNT`
+    Count(u32),
+    /// `RELEVANCY` (RFC 6203)
+    ///
+    /// One score per returned message, in the same order as `ALL`.
+    ///
+    /// Note: Empty when no message matched, i.e., `RELEVANCY ()`.
+    Relevancy(Vec<RelevancyScore>),
+}
+
+/// Search correlator of an ESEARCH response.
+///
+/// Associates the response with the command (and, with MULTISEARCH, the mailbox) it belongs to.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct SearchCorrelator<'a> {
+    /// Tag of the command.
+    pub tag: Tag<'a>,
+    /// Mailbox and its UIDVALIDITY (RFC 7377).
+    pub mailbox: Option<(Mailbox<'a>, NonZeroU32)>,
+}
@@ -0,0 +1,84 @@
+//! IMAP4 Extension for Fuzzy Search (FUZZY)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc6203>.
+//!
+//! This extension extends ...
+//!
+//! * the [SearchKey](crate::search::SearchKey) enum with a new variant [SearchKey::Fuzzy](crate::search::SearchKey#variant.Fuzzy),
+//! * the [SearchReturnOption](crate::extensions::esearch::SearchReturnOption) enum with a new variant [SearchReturnOption::Relevancy](crate::extensions::esearch::SearchReturnOption#variant.Relevancy),
+//! * the [SearchReturnData](crate::extensions::esearch::SearchReturnData) enum with a new variant [SearchReturnData::Relevancy](crate::extensions::esearch::SearchReturnData#variant.Relevancy), and
+//! * the [Capability](crate::response::Capability) enum with a new variant [Capability::SearchFuzzy](crate::response::Capability#variant.SearchFuzzy).
+//!
+//! <div class="warning">
+//! This extension must only be used when the server advertised support for it sending the SEARCH=FUZZY capability.
+//! </div>
+
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ValidationError, ValidationErrorKind};
+
+/// Relevancy score of a message (`1..=100`).
+///
+/// A higher score means the message matches the search criteria better.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToStatic)]
+pub struct RelevancyScore(u8);
+
+impl RelevancyScore {
+    /// Validates if value is a valid relevancy score (`1..=100`).
+    pub fn validate(value: u8) -> Result<(), ValidationError> {
+        if !(1..=100).contains(&value) {
+            return Err(ValidationError::new(ValidationErrorKind::Invalid));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the inner value.
+    pub fn inner(&self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for RelevancyScore {
+    type Error = ValidationError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::validate(value)?;
+
+        Ok(Self(value))
+    }
+}
+
+impl Display for RelevancyScore {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for RelevancyScore {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.int_in_range(1..=100)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relevancy_score() {
+        assert!(RelevancyScore::try_from(0).is_err());
+        assert_eq!(RelevancyScore::try_from(1).unwrap().inner(), 1);
+        assert_eq!(RelevancyScore::try_from(100).unwrap().inner(), 100);
+        assert!(RelevancyScore::try_from(101).is_err());
+    }
+}
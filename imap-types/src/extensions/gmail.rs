@@ -0,0 +1,140 @@
+//! Gmail IMAP Extensions (`X-GM-EXT-1`)
+//!
+//! See <https://developers.google.com/gmail/imap/imap-extensions>.
+//!
+//! This module provides ...
+//!
+//! * the [`GmailLabel`] type,
+//! * the `X-GM-MSGID`, `X-GM-THRID`, and `X-GM-LABELS` message data items
+//!   (see [`MessageDataItemName`](crate::fetch::MessageDataItemName) and [`MessageDataItem`](crate::fetch::MessageDataItem)),
+//! * the `+X-GM-LABELS`/`-X-GM-LABELS` STORE command (see [`CommandBody::StoreGmailLabels`]),
+//! * and the `X-GM-RAW`, `X-GM-MSGID`, `X-GM-THRID`, and `X-GM-LABELS` search keys (see [`SearchKey`](crate::search::SearchKey)).
+//!
+//! <div class="warning">
+//! This extension must only be used when the server advertised support for it sending the X-GM-EXT-1 capability.
+//! </div>
+
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::CommandBody,
+    core::AString,
+    error::{ValidationError, ValidationErrorKind},
+    flag::{StoreResponse, StoreType},
+    sequence::SequenceSet,
+};
+
+/// A Gmail label.
+///
+/// Labels are either system labels, e.g., `\Inbox`, `\Sent`, or `\Important`, or user-defined labels.
+/// User-defined labels are transmitted as atoms or strings, e.g., `Work` or `"Muy Importante"`.
+/// System labels can be transmitted as strings, too, e.g., `"\\Inbox"` or `"\\Muy Importante"`.
+///
+/// Note: A user-defined label must not start with a `\` as it would be interpreted as a system
+/// label. Use [`GmailLabel::user`] to construct a validated user-defined label.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum GmailLabel<'a> {
+    /// A system label (without the leading `\`), e.g., `Inbox` for `\Inbox`.
+    System(AString<'a>),
+    /// A user-defined label.
+    User(AString<'a>),
+}
+
+impl<'a> GmailLabel<'a> {
+    pub fn system(astring: AString<'a>) -> Self {
+        Self::System(astring)
+    }
+
+    /// Construct a user-defined label.
+    ///
+    /// Fails when the label starts with a `\`, i.e., when it would be interpreted as a system label.
+    pub fn user(astring: AString<'a>) -> Result<Self, ValidationError> {
+        if astring.as_ref().starts_with(b"\\") {
+            return Err(ValidationError::new(ValidationErrorKind::InvalidByteAt {
+                byte: b'\\',
+                at: 0,
+            }));
+        }
+
+        Ok(Self::User(astring))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for GmailLabel<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Ok(if let Some(value) = value.strip_prefix('\\') {
+            if value.is_empty() {
+                return Err(ValidationError::new(ValidationErrorKind::Empty));
+            }
+
+            Self::System(AString::try_from(value)?)
+        } else {
+            Self::user(AString::try_from(value)?)?
+        })
+    }
+}
+
+impl<'a> CommandBody<'a> {
+    /// Construct a STORE command modifying the Gmail labels of messages (`[+|-]X-GM-LABELS`).
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the X-GM-EXT-1 capability.
+    /// </div>
+    pub fn store_gmail_labels<S>(
+        sequence_set: S,
+        kind: StoreType,
+        response: StoreResponse,
+        labels: Vec<GmailLabel<'a>>,
+        uid: bool,
+    ) -> Result<Self, S::Error>
+    where
+        S: TryInto<SequenceSet>,
+    {
+        let sequence_set = sequence_set.try_into()?;
+
+        Ok(CommandBody::StoreGmailLabels {
+            sequence_set,
+            kind,
+            response,
+            labels,
+            uid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_gmail_label() {
+        assert_eq!(
+            GmailLabel::try_from("\\Inbox").unwrap(),
+            GmailLabel::System(AString::try_from("Inbox").unwrap())
+        );
+        assert_eq!(
+            GmailLabel::try_from("\\Muy Importante").unwrap(),
+            GmailLabel::System(AString::try_from("Muy Importante").unwrap())
+        );
+        assert_eq!(
+            GmailLabel::try_from("Muy Importante").unwrap(),
+            GmailLabel::User(AString::try_from("Muy Importante").unwrap())
+        );
+        assert!(GmailLabel::try_from("\\").is_err());
+    }
+
+    #[test]
+    fn test_gmail_label_user() {
+        assert_eq!(
+            GmailLabel::user(AString::try_from("foo\\bar").unwrap()).unwrap(),
+            GmailLabel::User(AString::try_from("foo\\bar").unwrap())
+        );
+        assert!(GmailLabel::user(AString::try_from("\\Inbox").unwrap()).is_err());
+        assert!(GmailLabel::user(AString::try_from("\\").unwrap()).is_err());
+    }
+}
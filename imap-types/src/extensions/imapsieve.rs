@@ -0,0 +1,216 @@
+//! IMAP Events in Sieve (IMAPSIEVE)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc6785>.
+//!
+//! IMAPSIEVE attaches Sieve scripts to mailboxes (or to the server) through METADATA entries.
+//! This module provides typed accessors for the `/shared/imapsieve/script` and
+//! `/private/imapsieve/script` entries on top of [`Entry`] and [`EntryValue`].
+//!
+//! <div class="warning">
+//! This extension must only be used when the server advertised support for it sending the IMAPSIEVE=<url> capability.
+//! </div>
+
+use std::borrow::Cow;
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::CommandBody,
+    core::{AString, AtomExt, IString, NString, NString8, Vec1},
+    extensions::{
+        imapsieve::error::ImapSieveError,
+        metadata::{Entry, EntryValue},
+    },
+    mailbox::Mailbox,
+};
+
+const SHARED_SCRIPT: &str = "/shared/imapsieve/script";
+const PRIVATE_SCRIPT: &str = "/private/imapsieve/script";
+
+/// Scope of an IMAPSIEVE script entry.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
+pub enum ScriptScope {
+    /// `/shared/imapsieve/script`
+    ///
+    /// The script applies to all users with access to the mailbox.
+    Shared,
+    /// `/private/imapsieve/script`
+    ///
+    /// The script applies to the authenticated user only.
+    Private,
+}
+
+impl ScriptScope {
+    /// Returns the METADATA entry name of this scope.
+    pub fn entry_name(&self) -> &'static str {
+        match self {
+            Self::Shared => SHARED_SCRIPT,
+            Self::Private => PRIVATE_SCRIPT,
+        }
+    }
+}
+
+impl<'a> Entry<'a> {
+    /// Construct the IMAPSIEVE script entry for the given scope.
+    pub fn imapsieve_script(scope: ScriptScope) -> Self {
+        Self(AString::Atom(AtomExt(Cow::Borrowed(scope.entry_name()))))
+    }
+
+    /// Returns the scope if this is an IMAPSIEVE script entry.
+    ///
+    /// Note: Entry names are compared case-insensitively.
+    pub fn imapsieve_script_scope(&self) -> Option<ScriptScope> {
+        let entry = self.as_ref();
+
+        if entry.eq_ignore_ascii_case(SHARED_SCRIPT.as_bytes()) {
+            Some(ScriptScope::Shared)
+        } else if entry.eq_ignore_ascii_case(PRIVATE_SCRIPT.as_bytes()) {
+            Some(ScriptScope::Private)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> EntryValue<'a> {
+    /// Construct an IMAPSIEVE script entry value.
+    ///
+    /// `Some(script)` attaches the script (by name), `None` (`NIL`) removes the association.
+    pub fn imapsieve_script<S>(scope: ScriptScope, script: Option<S>) -> Result<Self, S::Error>
+    where
+        S: TryInto<IString<'a>>,
+    {
+        let script = match script {
+            Some(script) => Some(script.try_into()?),
+            None => None,
+        };
+
+        Ok(Self {
+            entry: Entry::imapsieve_script(scope),
+            value: NString8::NString(NString(script)),
+        })
+    }
+
+    /// Returns the scope and the script name if this is an IMAPSIEVE script entry value.
+    ///
+    /// The script name is `None` when no script is associated (`NIL`).
+    pub fn as_imapsieve_script(&self) -> Option<(ScriptScope, Option<&[u8]>)> {
+        let scope = self.entry.imapsieve_script_scope()?;
+
+        let script = match &self.value {
+            NString8::NString(NString(script)) => script.as_ref().map(AsRef::as_ref),
+            NString8::Literal8(literal8) => Some(literal8.data.as_ref()),
+        };
+
+        Some((scope, script))
+    }
+}
+
+impl<'a> CommandBody<'a> {
+    /// Construct a SETMETADATA command attaching (`Some`) or detaching (`None`) a Sieve script.
+    ///
+    /// Use the empty mailbox name (`""`) to set the server-wide script.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the IMAPSIEVE=<url> capability.
+    /// </div>
+    pub fn set_imapsieve_script<M, S>(
+        mailbox: M,
+        scope: ScriptScope,
+        script: Option<S>,
+    ) -> Result<Self, ImapSieveError<M::Error, S::Error>>
+    where
+        M: TryInto<Mailbox<'a>>,
+        S: TryInto<IString<'a>>,
+    {
+        Ok(CommandBody::SetMetadata {
+            mailbox: mailbox.try_into().map_err(ImapSieveError::Mailbox)?,
+            entry_values: Vec1::from(
+                EntryValue::imapsieve_script(scope, script).map_err(ImapSieveError::Script)?,
+            ),
+        })
+    }
+
+    /// Construct a GETMETADATA command retrieving both the shared and the private Sieve script.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the IMAPSIEVE=<url> capability.
+    /// </div>
+    pub fn get_imapsieve_scripts<M>(mailbox: M) -> Result<Self, M::Error>
+    where
+        M: TryInto<Mailbox<'a>>,
+    {
+        Ok(CommandBody::GetMetadata {
+            options: vec![],
+            mailbox: mailbox.try_into()?,
+            entries: Vec1::unvalidated(vec![
+                Entry::imapsieve_script(ScriptScope::Shared),
+                Entry::imapsieve_script(ScriptScope::Private),
+            ]),
+        })
+    }
+}
+
+/// Error-related types.
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum ImapSieveError<M, S> {
+        #[error("Invalid mailbox: {0}")]
+        Mailbox(M),
+        #[error("Invalid script: {0}")]
+        Script(S),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imapsieve_script_entry() {
+        let tests = [
+            ("/shared/imapsieve/script", Some(ScriptScope::Shared)),
+            ("/SHARED/IMAPSieve/Script", Some(ScriptScope::Shared)),
+            ("/private/imapsieve/script", Some(ScriptScope::Private)),
+            ("/private/imapsieve", None),
+            ("/private/imapsieve/script/", None),
+            ("/shared/comment", None),
+        ];
+
+        for (test, expected) in tests {
+            let entry = Entry::try_from(AString::try_from(test).unwrap()).unwrap();
+            assert_eq!(entry.imapsieve_script_scope(), expected);
+        }
+
+        for scope in [ScriptScope::Shared, ScriptScope::Private] {
+            assert_eq!(
+                Entry::imapsieve_script(scope).imapsieve_script_scope(),
+                Some(scope)
+            );
+        }
+    }
+
+    #[test]
+    fn test_imapsieve_script_entry_value() {
+        let value =
+            EntryValue::imapsieve_script(ScriptScope::Private, Some("spam-training")).unwrap();
+        assert_eq!(
+            value.as_imapsieve_script(),
+            Some((ScriptScope::Private, Some(b"spam-training".as_ref())))
+        );
+
+        let value = EntryValue::imapsieve_script::<&str>(ScriptScope::Shared, None).unwrap();
+        assert_eq!(
+            value.as_imapsieve_script(),
+            Some((ScriptScope::Shared, None))
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! IMAP MESSAGELIMIT Extension
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc9738>.
+//!
+//! This extension extends ...
+//!
+//! * the [Capability](crate::response::Capability) enum with the new variants [Capability::MessageLimit](crate::response::Capability#variant.MessageLimit) and [Capability::SaveLimit](crate::response::Capability#variant.SaveLimit), and
+//! * the semantics of [Code::Limit](crate::response::Code#variant.Limit).
+//!
+//! A server advertising `MESSAGELIMIT=<n>` processes at most `n` messages per command.
+//! UID commands exceeding the limit are partially processed (lowest UIDs first) and completed with `OK [LIMIT]`.
+//! [`SequenceSet::limit_follow_up`] computes the UIDs the client still needs to ask for.
+
+use std::num::NonZeroU32;
+
+use crate::{
+    core::Vec1,
+    sequence::{SeqOrUid, Sequence, SequenceSet},
+};
+
+impl SequenceSet {
+    /// Compute the follow-up UID set after a UID command was completed with `OK [LIMIT]`.
+    ///
+    /// `returned_uids` are the UIDs returned by the server for the partially processed command,
+    /// e.g., the UIDs of `FETCH` responses or the `SEARCH` result. All UIDs of `self` that are
+    /// greater than the highest returned UID are kept. Returns `None` when nothing remains.
+    ///
+    /// Note: `SEARCH` only returns matching UIDs. The follow-up set may thus include messages the
+    /// server already looked at, which is harmless. When no UID was returned, `self` is returned
+    /// unchanged.
+    ///
+    /// Note: A range ending in `*` is kept as `<n>:*`. Because `<n>:*` also matches the highest UID
+    /// when `<n>` is greater than it, the follow-up command may return the highest UID again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    ///
+    /// use imap_types::sequence::SequenceSet;
+    ///
+    /// let requested = SequenceSet::try_from("1:1000,2000:*").unwrap();
+    /// let returned = [1u32, 5, 300].map(|uid| NonZeroU32::new(uid).unwrap());
+    ///
+    /// assert_eq!(
+    ///     requested.limit_follow_up(returned),
+    ///     Some(SequenceSet::try_from("301:1000,2000:*").unwrap())
+    /// );
+    /// ```
+    pub fn limit_follow_up<I>(&self, returned_uids: I) -> Option<SequenceSet>
+    where
+        I: IntoIterator<Item = NonZeroU32>,
+    {
+        let highest = match returned_uids.into_iter().max() {
+            Some(highest) => highest,
+            None => return Some(self.clone()),
+        };
+
+        // Everything up to (and including) `u32::MAX` was processed.
+        let next = NonZeroU32::new(highest.get().checked_add(1)?).unwrap();
+
+        let remaining: Vec<Sequence> = self
+            .0
+            .as_ref()
+            .iter()
+            .filter_map(|sequence| match *sequence {
+                Sequence::Single(SeqOrUid::Value(uid)) => {
+                    (uid > highest).then_some(sequence.clone())
+                }
+                Sequence::Single(SeqOrUid::Asterisk) => Some(sequence.clone()),
+                Sequence::Range(SeqOrUid::Value(a), SeqOrUid::Value(b)) => {
+                    let (from, to) = if a <= b { (a, b) } else { (b, a) };
+
+                    if to <= highest {
+                        None
+                    } else if from > highest {
+                        Some(sequence.clone())
+                    } else if next == to {
+                        Some(Sequence::Single(SeqOrUid::Value(to)))
+                    } else {
+                        Some(Sequence::Range(SeqOrUid::Value(next), SeqOrUid::Value(to)))
+                    }
+                }
+                Sequence::Range(SeqOrUid::Value(from), SeqOrUid::Asterisk)
+                | Sequence::Range(SeqOrUid::Asterisk, SeqOrUid::Value(from)) => Some(
+                    Sequence::Range(SeqOrUid::Value(from.max(next)), SeqOrUid::Asterisk),
+                ),
+                Sequence::Range(SeqOrUid::Asterisk, SeqOrUid::Asterisk) => Some(sequence.clone()),
+            })
+            .collect();
+
+        Vec1::try_from(remaining).ok().map(SequenceSet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::Capability;
+
+    #[test]
+    fn test_limit_follow_up() {
+        let tests = [
+            ("1:*", vec![], Some("1:*")),
+            ("1:*", vec![1, 2, 3], Some("4:*")),
+            ("*:1", vec![1, 2, 3], Some("4:*")),
+            ("10:*", vec![3], Some("10:*")),
+            ("1:100", vec![42], Some("43:100")),
+            ("100:1", vec![42], Some("43:100")),
+            ("1:100", vec![99], Some("100")),
+            ("1:100", vec![100], None),
+            ("1,5,7,9", vec![5, 1], Some("7,9")),
+            ("1,5,7,9", vec![9], None),
+            ("1:5,20:30,*", vec![3, 22], Some("23:30,*")),
+            ("1:*", vec![u32::MAX], None),
+            ("*", vec![u32::MAX], None),
+        ];
+
+        for (requested, returned, expected) in tests {
+            let requested = SequenceSet::try_from(requested).unwrap();
+            let returned = returned
+                .into_iter()
+                .map(|uid| NonZeroU32::new(uid).unwrap());
+            let expected = expected.map(|expected| SequenceSet::try_from(expected).unwrap());
+
+            assert_eq!(requested.limit_follow_up(returned), expected);
+        }
+    }
+
+    #[test]
+    fn test_capability_limit() {
+        assert_eq!(
+            Capability::try_from("messagelimit=1000").unwrap(),
+            Capability::MessageLimit(NonZeroU32::new(1000).unwrap())
+        );
+        assert_eq!(
+            Capability::try_from("SAVELIMIT=1").unwrap(),
+            Capability::SaveLimit(NonZeroU32::new(1).unwrap())
+        );
+
+        // Non-canonical or invalid limits are kept as unknown capabilities.
+        for test in [
+            "MESSAGELIMIT=0",
+            "MESSAGELIMIT=01",
+            "MESSAGELIMIT=+1",
+            "SAVELIMIT=4294967296",
+            "SAVELIMIT=",
+        ] {
+            assert!(matches!(
+                Capability::try_from(test).unwrap(),
+                Capability::Other(_)
+            ));
+        }
+    }
+}
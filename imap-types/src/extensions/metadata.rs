@@ -21,7 +21,7 @@ pub struct EntryValue<'a> {
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
-pub struct Entry<'a>(AString<'a>);
+pub struct Entry<'a>(pub(crate) AString<'a>);
 
 impl<'a> Entry<'a> {
     pub fn inner(&self) -> &AString<'a> {
@@ -0,0 +1,85 @@
+//! IMAP4 Multimailbox SEARCH Extension (MULTISEARCH)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc7377>.
+//!
+//! This extension extends ...
+//!
+//! * the [CommandBody] enum with a new variant [CommandBody::ESearch],
+//! * the [Data](crate::response::Data) enum with a new variant [Data::ESearch](crate::response::Data#variant.ESearch), and
+//! * the [Capability](crate::response::Capability) enum with a new variant [Capability::MultiSearch](crate::response::Capability#variant.MultiSearch).
+//!
+//! <div class="warning">
+//! This extension must only be used when the server advertised support for it sending the MULTISEARCH capability.
+//! </div>
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::CommandBody,
+    core::{Charset, Vec1},
+    extensions::esearch::SearchReturnOption,
+    mailbox::Mailbox,
+    search::SearchKey,
+};
+
+/// Mailbox filter used to select the mailboxes to search.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum FilterMailboxes<'a> {
+    /// `selected`
+    ///
+    /// The currently selected mailbox.
+    Selected,
+    /// `inboxes`
+    ///
+    /// All selectable mailboxes in the user's personal namespace(s) to which messages may be delivered.
+    Inboxes,
+    /// `personal`
+    ///
+    /// All selectable mailboxes in the user's personal namespace(s).
+    Personal,
+    /// `subscribed`
+    ///
+    /// All mailboxes subscribed to by the user.
+    Subscribed,
+    /// `subtree`
+    ///
+    /// The given mailboxes and all selectable mailboxes subordinate to them.
+    Subtree(Vec1<Mailbox<'a>>),
+    /// `subtree-one`
+    ///
+    /// The given mailboxes and their immediate children.
+    SubtreeOne(Vec1<Mailbox<'a>>),
+    /// `mailboxes`
+    ///
+    /// The given mailboxes.
+    Mailboxes(Vec1<Mailbox<'a>>),
+}
+
+impl<'a> CommandBody<'a> {
+    /// Construct an ESEARCH command.
+    ///
+    /// `source` selects the mailboxes to search. `None` searches the currently selected mailbox.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the MULTISEARCH capability.
+    /// </div>
+    pub fn esearch(
+        source: Option<Vec1<FilterMailboxes<'a>>>,
+        return_options: Vec<SearchReturnOption>,
+        charset: Option<Charset<'a>>,
+        criteria: Vec1<SearchKey<'a>>,
+    ) -> Self {
+        CommandBody::ESearch {
+            source,
+            return_options,
+            charset,
+            criteria,
+        }
+    }
+}
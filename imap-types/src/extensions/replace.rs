@@ -0,0 +1,54 @@
+//! IMAP - REPLACE Extension
+
+use crate::{
+    command::CommandBody,
+    core::Literal,
+    datetime::DateTime,
+    extensions::{binary::LiteralOrLiteral8, replace::error::ReplaceError},
+    flag::Flag,
+    mailbox::Mailbox,
+    sequence::SeqOrUid,
+};
+
+impl<'a> CommandBody<'a> {
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the REPLACE capability.
+    /// </div>
+    pub fn replace<S, M, D>(
+        sequence: S,
+        mailbox: M,
+        flags: Vec<Flag<'a>>,
+        date: Option<DateTime>,
+        message: D,
+        uid: bool,
+    ) -> Result<Self, ReplaceError<S::Error, M::Error, D::Error>>
+    where
+        S: TryInto<SeqOrUid>,
+        M: TryInto<Mailbox<'a>>,
+        D: TryInto<Literal<'a>>,
+    {
+        Ok(CommandBody::Replace {
+            sequence: sequence.try_into().map_err(ReplaceError::Sequence)?,
+            mailbox: mailbox.try_into().map_err(ReplaceError::Mailbox)?,
+            flags,
+            date,
+            message: LiteralOrLiteral8::Literal(message.try_into().map_err(ReplaceError::Data)?),
+            uid,
+        })
+    }
+}
+
+/// Error-related types.
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum ReplaceError<S, M, D> {
+        #[error("Invalid sequence: {0}")]
+        Sequence(S),
+        #[error("Invalid mailbox: {0}")]
+        Mailbox(M),
+        #[error("Invalid data: {0}")]
+        Data(D),
+    }
+}
@@ -10,6 +10,10 @@ use serde::{Deserialize, Serialize};
 use crate::arbitrary::impl_arbitrary_try_from;
 use crate::core::Atom;
 
+#[cfg(feature = "ext_sort_thread_evaluate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ext_sort_thread_evaluate")))]
+pub mod evaluate;
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum SortAlgorithm<'a> {
@@ -0,0 +1,625 @@
+//! Evaluation of SORT (RFC 5256) criteria against messages.
+//!
+//! Implement [`Sortable`] for your message type and call [`sort`] (or [`uid_sort`]). The result
+//! can be wrapped in [`Data::Sort`](crate::response::Data::Sort).
+
+use std::{borrow::Cow, cmp::Ordering, num::NonZeroU32};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{
+    core::NString,
+    datetime::DateTime,
+    envelope::{Address, Envelope},
+    extensions::sort::{SortCriterion, SortKey},
+    utils::mime::decode_encoded_words,
+};
+
+/// A message that can be sorted.
+pub trait Sortable {
+    /// Message sequence number.
+    fn seq(&self) -> NonZeroU32;
+
+    /// Unique identifier.
+    fn uid(&self) -> NonZeroU32;
+
+    /// Internal date.
+    fn internal_date(&self) -> DateTime;
+
+    /// Size in octets ([RFC 2822] size).
+    ///
+    /// [RFC 2822]: https://datatracker.ietf.org/doc/html/rfc2822
+    fn size(&self) -> u32;
+
+    /// Envelope.
+    fn envelope(&self) -> Cow<Envelope<'_>>;
+}
+
+/// Comparator used for strings.
+///
+/// See [RFC 4790](https://datatracker.ietf.org/doc/html/rfc4790).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Comparator {
+    /// `i;ascii-casemap`
+    ///
+    /// ASCII letters are compared case-insensitively, everything else octet by octet.
+    #[default]
+    AsciiCasemap,
+    /// `i;unicode-casemap` ([RFC 5051](https://datatracker.ietf.org/doc/html/rfc5051))
+    ///
+    /// Strings are compared after simple titlecase mapping and NFKD decomposition.
+    UnicodeCasemap,
+}
+
+impl Comparator {
+    /// Compare `a` and `b`.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.key(a).cmp(&self.key(b))
+    }
+
+    /// Return the octets that are compared instead of `value`.
+    pub(crate) fn key(&self, value: &str) -> Vec<u8> {
+        match self {
+            Self::AsciiCasemap => value.bytes().map(|b| b.to_ascii_uppercase()).collect(),
+            Self::UnicodeCasemap => value
+                .chars()
+                .map(titlecase)
+                .nfkd()
+                .collect::<String>()
+                .into_bytes(),
+        }
+    }
+}
+
+/// Simple titlecase mapping.
+fn titlecase(c: char) -> char {
+    match c {
+        // Digraphs have a dedicated titlecase form.
+        '\u{01C4}'..='\u{01C6}' => '\u{01C5}',
+        '\u{01C7}'..='\u{01C9}' => '\u{01C8}',
+        '\u{01CA}'..='\u{01CC}' => '\u{01CB}',
+        '\u{01F1}'..='\u{01F3}' => '\u{01F2}',
+        // Georgian Mkhedruli letters are their own titlecase (but uppercase to Mtavruli).
+        '\u{10D0}'..='\u{10FA}' | '\u{10FD}'..='\u{10FF}' => c,
+        // Greek letters with ypogegrammeni. Their full uppercase mapping has two characters, but
+        // their simple titlecase mapping is the letter with prosgegrammeni.
+        '\u{1F80}'..='\u{1F87}' | '\u{1F90}'..='\u{1F97}' | '\u{1FA0}'..='\u{1FA7}' => {
+            char::from_u32(c as u32 + 8).unwrap_or(c)
+        }
+        '\u{1FB3}' => '\u{1FBC}',
+        '\u{1FC3}' => '\u{1FCC}',
+        '\u{1FF3}' => '\u{1FFC}',
+        _ => {
+            let mut upper = c.to_uppercase();
+
+            // All other characters with a multi-character uppercase mapping, e.g., `ß`, don't
+            // have a simple mapping.
+            match (upper.next(), upper.next()) {
+                (Some(upper), None) => upper,
+                _ => c,
+            }
+        }
+    }
+}
+
+/// Sort `messages` by `criteria` and return their sequence numbers.
+///
+/// Messages that compare equal under all criteria are ordered by sequence number.
+pub fn sort<M>(
+    messages: &[M],
+    criteria: &[SortCriterion],
+    comparator: Comparator,
+) -> Vec<NonZeroU32>
+where
+    M: Sortable,
+{
+    sorted(messages, criteria, comparator)
+        .into_iter()
+        .map(Sortable::seq)
+        .collect()
+}
+
+/// Sort `messages` by `criteria` and return their UIDs (`UID SORT`).
+///
+/// Messages that compare equal under all criteria are ordered by sequence number.
+pub fn uid_sort<M>(
+    messages: &[M],
+    criteria: &[SortCriterion],
+    comparator: Comparator,
+) -> Vec<NonZeroU32>
+where
+    M: Sortable,
+{
+    sorted(messages, criteria, comparator)
+        .into_iter()
+        .map(Sortable::uid)
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Value {
+    Number(i64),
+    Text(Vec<u8>),
+}
+
+fn sorted<'m, M>(
+    messages: &'m [M],
+    criteria: &[SortCriterion],
+    comparator: Comparator,
+) -> Vec<&'m M>
+where
+    M: Sortable,
+{
+    let mut keyed: Vec<_> = messages
+        .iter()
+        .map(|message| {
+            let envelope = message.envelope();
+            let values: Vec<_> = criteria
+                .iter()
+                .map(|criterion| value(message, &envelope, &criterion.key, comparator))
+                .collect();
+
+            (message, values)
+        })
+        .collect();
+
+    keyed.sort_by(|(a, a_values), (b, b_values)| {
+        criteria
+            .iter()
+            .zip(a_values.iter().zip(b_values))
+            .map(|(criterion, (a, b))| match criterion.reverse {
+                true => b.cmp(a),
+                false => a.cmp(b),
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.seq().cmp(&b.seq()))
+    });
+
+    keyed.into_iter().map(|(message, _)| message).collect()
+}
+
+fn value<M>(message: &M, envelope: &Envelope, key: &SortKey, comparator: Comparator) -> Value
+where
+    M: Sortable,
+{
+    match key {
+        SortKey::Arrival => Value::Number(message.internal_date().as_ref().timestamp()),
+        SortKey::Date => Value::Number(sent_date(message, envelope)),
+        SortKey::Size => Value::Number(i64::from(message.size())),
+        SortKey::Cc => Value::Text(comparator.key(&address_mailbox(&envelope.cc))),
+        SortKey::From => Value::Text(comparator.key(&address_mailbox(&envelope.from))),
+        SortKey::To => Value::Text(comparator.key(&address_mailbox(&envelope.to))),
+        SortKey::Subject => Value::Text(comparator.key(&base_subject(&nstring(&envelope.subject)))),
+        SortKey::DisplayFrom => Value::Text(comparator.key(&display_name(&envelope.from))),
+        SortKey::DisplayTo => Value::Text(comparator.key(&display_name(&envelope.to))),
+    }
+}
+
+/// Sent date as UNIX timestamp.
+///
+/// Falls back to the internal date when the `Date` header field is missing or can't be parsed.
+pub(crate) fn sent_date<M>(message: &M, envelope: &Envelope) -> i64
+where
+    M: Sortable + ?Sized,
+{
+    chrono::DateTime::parse_from_rfc2822(nstring(&envelope.date).trim())
+        .map(|date| date.timestamp())
+        .unwrap_or_else(|_| message.internal_date().as_ref().timestamp())
+}
+
+pub(crate) fn nstring<'a>(value: &'a NString) -> Cow<'a, str> {
+    match &value.0 {
+        Some(value) => String::from_utf8_lossy(value.as_ref()),
+        None => Cow::Borrowed(""),
+    }
+}
+
+/// First address, skipping group markers.
+fn first_address<'a, 'b>(addresses: &'a [Address<'b>]) -> Option<&'a Address<'b>> {
+    addresses.iter().find(|address| address.host.0.is_some())
+}
+
+/// `addr-mailbox` of the first address (RFC 5256).
+fn address_mailbox(addresses: &[Address]) -> String {
+    first_address(addresses)
+        .map(|address| nstring(&address.mailbox).into_owned())
+        .unwrap_or_default()
+}
+
+/// Display name of the first address (RFC 5957).
+///
+/// Falls back to `addr-mailbox@addr-host` when the address has no (or an empty) display name.
+fn display_name(addresses: &[Address]) -> String {
+    let address = match first_address(addresses) {
+        Some(address) => address,
+        None => return String::new(),
+    };
+
+    let name = decode_encoded_words(&nstring(&address.name));
+
+    match name.trim() {
+        "" => format!("{}@{}", nstring(&address.mailbox), nstring(&address.host)),
+        name => name.to_owned(),
+    }
+}
+
+/// Extract the base subject as defined in [RFC 5256, section 2.1].
+///
+/// [RFC 5256, section 2.1]: https://datatracker.ietf.org/doc/html/rfc5256#section-2.1
+pub fn base_subject(subject: &str) -> String {
+    base_subject_with_reply(subject).0
+}
+
+/// Like [`base_subject`], but additionally returns whether the subject indicates a reply or
+/// forward (i.e., a `Re:`/`Fwd:` leader, a `(fwd)` trailer, or a `[fwd: ...]` wrapper was
+/// removed).
+pub(crate) fn base_subject_with_reply(subject: &str) -> (String, bool) {
+    // (1) Decode encoded-words and normalize whitespace.
+    let mut subject = decode_encoded_words(subject)
+        .split(|c: char| c.is_ascii_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut is_reply = false;
+
+    loop {
+        // (2) Remove trailers.
+        loop {
+            let trimmed = subject.trim_end_matches(' ');
+
+            match strip_suffix_ignore_ascii_case(trimmed, "(fwd)") {
+                Some(rest) => {
+                    subject.truncate(rest.len());
+                    is_reply = true;
+                }
+                None => {
+                    subject.truncate(trimmed.len());
+                    break;
+                }
+            }
+        }
+
+        // (5) Repeat (3) and (4) until no more changes.
+        loop {
+            let before = subject.len();
+
+            // (3) Remove leaders.
+            loop {
+                if let Some(rest) = subject.strip_prefix(' ') {
+                    subject = rest.to_owned();
+                } else if let Some(rest) = strip_subj_refwd(&subject) {
+                    subject = rest.to_owned();
+                    is_reply = true;
+                } else {
+                    break;
+                }
+            }
+
+            // (4) Remove a leading blob if something remains.
+            if let Some(rest) = strip_subj_blob(&subject) {
+                if !rest.is_empty() {
+                    subject = rest.to_owned();
+                }
+            }
+
+            if subject.len() == before {
+                break;
+            }
+        }
+
+        // (6) Remove `[fwd: ...]` and repeat from (2).
+        match strip_prefix_ignore_ascii_case(&subject, "[fwd:")
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            Some(rest) => {
+                subject = rest.to_owned();
+                is_reply = true;
+            }
+            None => break,
+        }
+    }
+
+    (subject, is_reply)
+}
+
+/// ```abnf
+/// subj-blob = "[" *BLOBCHAR "]" *WSP
+/// ```
+fn strip_subj_blob(input: &str) -> Option<&str> {
+    let rest = input.strip_prefix('[')?;
+    let end = rest.find(|c: char| c == '[' || c == ']')?;
+
+    Some(rest[end..].strip_prefix(']')?.trim_start_matches(' '))
+}
+
+/// ```abnf
+/// subj-leader = (*subj-blob subj-refwd) / WSP
+/// subj-refwd  = ("re" / ("fw" ["d"])) *WSP [subj-blob] ":"
+/// ```
+fn strip_subj_refwd(input: &str) -> Option<&str> {
+    let mut rest = input;
+    while let Some(next) = strip_subj_blob(rest) {
+        rest = next;
+    }
+
+    let rest = strip_prefix_ignore_ascii_case(rest, "re")
+        .or_else(|| strip_prefix_ignore_ascii_case(rest, "fwd"))
+        .or_else(|| strip_prefix_ignore_ascii_case(rest, "fw"))?
+        .trim_start_matches(' ');
+    let rest = strip_subj_blob(rest).unwrap_or(rest);
+
+    rest.strip_prefix(':')
+}
+
+fn strip_prefix_ignore_ascii_case<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    match input.get(..prefix.len()) {
+        Some(head) if head.eq_ignore_ascii_case(prefix) => Some(&input[prefix.len()..]),
+        _ => None,
+    }
+}
+
+fn strip_suffix_ignore_ascii_case<'a>(input: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = input.len().checked_sub(suffix.len())?;
+
+    match input.get(split..) {
+        Some(tail) if tail.eq_ignore_ascii_case(suffix) => Some(&input[..split]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::IString;
+
+    #[test]
+    fn test_base_subject() {
+        let tests = [
+            ("", "", false),
+            ("Hello", "Hello", false),
+            ("  Hello \t  World  ", "Hello World", false),
+            ("Re: Hello", "Hello", true),
+            ("RE: Re: re: Hello", "Hello", true),
+            ("Fwd: Hello", "Hello", true),
+            ("fw: Hello", "Hello", true),
+            ("Re [2]: Hello", "Hello", true),
+            ("Re[2]:Hello", "Hello", true),
+            ("[list] Re: Hello", "Hello", true),
+            ("[list] Hello", "Hello", false),
+            ("[list]", "[list]", false),
+            ("Hello (fwd)", "Hello", true),
+            ("Hello (FWD) (fwd)  ", "Hello", true),
+            ("[Fwd: Re: Hello]", "Hello", true),
+            ("[fwd: [fwd: Hello (fwd)]]", "Hello", true),
+            ("Re: [list] [Fwd: Hello]", "Hello", true),
+            ("Reply", "Reply", false),
+            ("Re:", "", true),
+            ("=?UTF-8?Q?Re=3A_Gr=C3=BC=C3=9Fe?=", "Grüße", true),
+            (
+                "=?ISO-8859-1?B?SGVsbG8=?= =?UTF-8?Q?_W=C3=B6rld?=",
+                "Hello Wörld",
+                false,
+            ),
+        ];
+
+        for (test, expected, expected_is_reply) in tests {
+            assert_eq!(
+                base_subject_with_reply(test),
+                (expected.to_owned(), expected_is_reply),
+                "{test:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_comparator() {
+        let tests = [
+            (Comparator::AsciiCasemap, "abc", "ABC", Ordering::Equal),
+            (Comparator::AsciiCasemap, "a", "B", Ordering::Less),
+            (Comparator::AsciiCasemap, "ä", "Ä", Ordering::Greater),
+            (Comparator::UnicodeCasemap, "ä", "Ä", Ordering::Equal),
+            (
+                Comparator::UnicodeCasemap,
+                "a\u{0308}",
+                "Ä",
+                Ordering::Equal,
+            ),
+            (Comparator::UnicodeCasemap, "ǆ", "Ǆ", Ordering::Equal),
+            (
+                Comparator::UnicodeCasemap,
+                "\u{1F80}",
+                "\u{1F88}",
+                Ordering::Equal,
+            ),
+            (
+                Comparator::UnicodeCasemap,
+                "\u{1FA7}",
+                "\u{1FAF}",
+                Ordering::Equal,
+            ),
+            (
+                Comparator::UnicodeCasemap,
+                "\u{1FB3}",
+                "\u{1FBC}",
+                Ordering::Equal,
+            ),
+            (
+                Comparator::UnicodeCasemap,
+                "\u{1FF3}",
+                "\u{1FFC}",
+                Ordering::Equal,
+            ),
+            (Comparator::UnicodeCasemap, "ß", "SS", Ordering::Greater),
+            (
+                Comparator::UnicodeCasemap,
+                "\u{10D0}",
+                "\u{1C90}",
+                Ordering::Less,
+            ),
+            (Comparator::UnicodeCasemap, "a", "B", Ordering::Less),
+        ];
+
+        for (comparator, a, b, expected) in tests {
+            assert_eq!(
+                comparator.compare(a, b),
+                expected,
+                "{comparator:?} {a:?} {b:?}"
+            );
+        }
+    }
+
+    struct Message {
+        seq: u32,
+        internal_date: &'static str,
+        size: u32,
+        envelope: Envelope<'static>,
+    }
+
+    impl Sortable for Message {
+        fn seq(&self) -> NonZeroU32 {
+            NonZeroU32::new(self.seq).unwrap()
+        }
+
+        fn uid(&self) -> NonZeroU32 {
+            NonZeroU32::new(self.seq * 10).unwrap()
+        }
+
+        fn internal_date(&self) -> DateTime {
+            DateTime::try_from(chrono::DateTime::parse_from_rfc3339(self.internal_date).unwrap())
+                .unwrap()
+        }
+
+        fn size(&self) -> u32 {
+            self.size
+        }
+
+        fn envelope(&self) -> Cow<Envelope<'_>> {
+            Cow::Borrowed(&self.envelope)
+        }
+    }
+
+    fn nstr(value: Option<&'static str>) -> NString<'static> {
+        NString(value.map(|value| IString::try_from(value).unwrap()))
+    }
+
+    fn address(name: Option<&'static str>, mailbox: &'static str) -> Address<'static> {
+        Address {
+            name: nstr(name),
+            adl: nstr(None),
+            mailbox: nstr(Some(mailbox)),
+            host: nstr(Some("example.org")),
+        }
+    }
+
+    fn message(
+        seq: u32,
+        internal_date: &'static str,
+        size: u32,
+        date: Option<&'static str>,
+        subject: &'static str,
+        from: Address<'static>,
+    ) -> Message {
+        Message {
+            seq,
+            internal_date,
+            size,
+            envelope: Envelope {
+                date: nstr(date),
+                subject: nstr(Some(subject)),
+                from: vec![from],
+                sender: vec![],
+                reply_to: vec![],
+                to: vec![],
+                cc: vec![],
+                bcc: vec![],
+                in_reply_to: nstr(None),
+                message_id: nstr(None),
+            },
+        }
+    }
+
+    fn messages() -> Vec<Message> {
+        vec![
+            message(
+                1,
+                "2024-01-03T00:00:00Z",
+                300,
+                Some("Mon, 1 Jan 2024 10:00:00 +0000"),
+                "Re: beta",
+                address(Some("Zoe"), "alice"),
+            ),
+            message(
+                2,
+                "2024-01-01T00:00:00Z",
+                100,
+                Some("Mon, 1 Jan 2024 12:00:00 +0300"),
+                "Alpha",
+                address(None, "carol"),
+            ),
+            message(
+                3,
+                "2024-01-02T00:00:00Z",
+                100,
+                None,
+                "[list] Beta (fwd)",
+                address(Some("=?utf-8?q?Bob?="), "Bob"),
+            ),
+        ]
+    }
+
+    fn criteria(keys: &[(bool, SortKey)]) -> Vec<SortCriterion> {
+        keys.iter()
+            .map(|(reverse, key)| SortCriterion {
+                reverse: *reverse,
+                key: key.clone(),
+            })
+            .collect()
+    }
+
+    fn seqs(values: &[u32]) -> Vec<NonZeroU32> {
+        values
+            .iter()
+            .map(|value| NonZeroU32::new(*value).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_sort() {
+        let messages = messages();
+
+        let tests = [
+            (vec![(false, SortKey::Arrival)], vec![2, 3, 1]),
+            (vec![(true, SortKey::Arrival)], vec![1, 3, 2]),
+            // 12:00 +0300 is before 10:00 +0000. A missing date uses the internal date.
+            (vec![(false, SortKey::Date)], vec![2, 1, 3]),
+            (vec![(false, SortKey::Size)], vec![2, 3, 1]),
+            (vec![(true, SortKey::Size)], vec![1, 2, 3]),
+            (
+                vec![(false, SortKey::Size), (true, SortKey::Arrival)],
+                vec![3, 2, 1],
+            ),
+            (vec![(false, SortKey::Subject)], vec![2, 1, 3]),
+            (vec![(false, SortKey::From)], vec![1, 3, 2]),
+            (vec![(false, SortKey::DisplayFrom)], vec![3, 2, 1]),
+            (vec![(false, SortKey::To)], vec![1, 2, 3]),
+            (vec![], vec![1, 2, 3]),
+        ];
+
+        for (keys, expected) in tests {
+            assert_eq!(
+                sort(&messages, &criteria(&keys), Comparator::AsciiCasemap),
+                seqs(&expected),
+                "{keys:?}"
+            );
+        }
+
+        assert_eq!(
+            uid_sort(
+                &messages,
+                &criteria(&[(false, SortKey::Arrival)]),
+                Comparator::UnicodeCasemap
+            ),
+            seqs(&[20, 30, 10])
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Collected Extensions to IMAP4 ABNF (tagged-ext)
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc4466> and <https://datatracker.ietf.org/doc/html/rfc5258>.
+//!
+//! RFC 4466 defines a generic syntax for extension parameters of existing commands and
+//! responses. This allows to represent (and forward) parameters of extensions that are not
+//! implemented (yet).
+//!
+//! This extension extends ...
+//!
+//! * the [CommandBody::Select](crate::command::CommandBody#variant.Select) and [CommandBody::Examine](crate::command::CommandBody#variant.Examine) variants with `parameters`,
+//! * the [CommandBody::Rename](crate::command::CommandBody#variant.Rename) variant with `parameters`,
+//! * the [CommandBody::Fetch](crate::command::CommandBody#variant.Fetch) and [CommandBody::Store](crate::command::CommandBody#variant.Store) variants with `modifiers`,
+//! * the [CommandBody::Append](crate::command::CommandBody#variant.Append) variant with `extensions`,
+//! * the [CommandBody::List](crate::command::CommandBody#variant.List) variant with `select_options` and `return_options`,
+//! * the [Data::List](crate::response::Data#variant.List) variant with `extended_items`,
+//! * the [MessageDataItem](crate::fetch::MessageDataItem) enum with a new variant [MessageDataItem::Other](crate::fetch::MessageDataItem#variant.Other), and
+//! * the [StatusDataItem](crate::status::StatusDataItem) enum with a new variant [StatusDataItem::Other](crate::status::StatusDataItem#variant.Other).
+//!
+//! <div class="warning">
+//! Parameters must only be used when the server advertised support for the extension defining them.
+//! </div>
+
+use std::{
+    borrow::Cow,
+    fmt::{Debug, Display, Formatter},
+    str::from_utf8,
+};
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, Unstructured};
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "arbitrary")]
+use crate::sequence::{SeqOrUid, Sequence};
+use crate::{
+    core::{AString, Atom, NString, Vec1},
+    error::{ValidationError, ValidationErrorKind},
+    sequence::SequenceSet,
+    utils::indicators::{is_tagged_label_char, is_tagged_label_fchar},
+};
+
+/// Label of an extension parameter, e.g., `CONDSTORE` or `CHANGEDSINCE`.
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// tagged-ext-label   = tagged-label-fchar *tagged-label-char
+/// tagged-label-fchar = ALPHA / "-" / "_" / "."
+/// tagged-label-char  = tagged-label-fchar / DIGIT / ":"
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String"))]
+#[derive(PartialEq, Eq, Hash, Clone, ToStatic)]
+pub struct TaggedExtLabel<'a>(pub(crate) Cow<'a, str>);
+
+// We want a slightly more dense `Debug` implementation.
+impl<'a> Debug for TaggedExtLabel<'a> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "TaggedExtLabel({:?})", self.0)
+    }
+}
+
+impl<'a> Display for TaggedExtLabel<'a> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'a> TaggedExtLabel<'a> {
+    pub fn validate(value: impl AsRef<[u8]>) -> Result<(), ValidationError> {
+        let value = value.as_ref();
+
+        let first = match value.first() {
+            Some(first) => *first,
+            None => return Err(ValidationError::new(ValidationErrorKind::Empty)),
+        };
+
+        if !is_tagged_label_fchar(first) {
+            return Err(ValidationError::new(ValidationErrorKind::InvalidByteAt {
+                byte: first,
+                at: 0,
+            }));
+        }
+
+        if let Some(at) = value.iter().position(|b| !is_tagged_label_char(*b)) {
+            return Err(ValidationError::new(ValidationErrorKind::InvalidByteAt {
+                byte: value[at],
+                at,
+            }));
+        };
+
+        Ok(())
+    }
+
+    pub fn inner(&self) -> &str {
+        self.0.as_ref()
+    }
+
+    /// Constructs a label without validation.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `inner` is valid according to [`Self::validate`]. Failing to do
+    /// so may create invalid/unparsable IMAP messages, or even produce unintended protocol flows.
+    /// Do not call this constructor with untrusted data.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated<C>(inner: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
+        let inner = inner.into();
+
+        #[cfg(debug_assertions)]
+        Self::validate(inner.as_bytes()).unwrap();
+
+        Self(inner)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for TaggedExtLabel<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::validate(value)?;
+
+        // Safety: `unwrap` can't fail due to `validate`.
+        Ok(Self(Cow::Borrowed(from_utf8(value).unwrap())))
+    }
+}
+
+impl<'a> TryFrom<Vec<u8>> for TaggedExtLabel<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+
+        // Safety: `unwrap` can't fail due to `validate`.
+        Ok(Self(Cow::Owned(String::from_utf8(value).unwrap())))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TaggedExtLabel<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Self::validate(value)?;
+
+        Ok(Self(Cow::Borrowed(value)))
+    }
+}
+
+impl<'a> TryFrom<String> for TaggedExtLabel<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+
+        Ok(Self(Cow::Owned(value)))
+    }
+}
+
+impl<'a> AsRef<str> for TaggedExtLabel<'a> {
+    fn as_ref(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+/// Labeled extension value, e.g., `X-FOO (1 2)`.
+///
+/// Used for unknown APPEND extensions as well as unknown FETCH and STATUS data items.
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// tagged-ext = tagged-ext-label SP tagged-ext-val
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct TaggedExt<'a> {
+    pub label: TaggedExtLabel<'a>,
+    pub value: TaggedExtVal<'a>,
+}
+
+/// Labeled value of an unknown FETCH or STATUS data item, e.g., `MODSEQ (12345)` or `SAVEDATE NIL`.
+///
+/// Besides `tagged-ext-val`, some extensions use an `nstring` value (e.g., `SAVEDATE`, RFC 8514).
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// msg-att-other        = tagged-ext-label SP (tagged-ext-val / nstring) ; simplified
+/// status-att-val-other = tagged-ext-label SP (tagged-ext-val / nstring) ; simplified
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct TaggedExtItem<'a> {
+    pub label: TaggedExtLabel<'a>,
+    pub value: TaggedExtItemVal<'a>,
+}
+
+impl<'a> From<TaggedExt<'a>> for TaggedExtItem<'a> {
+    fn from(TaggedExt { label, value }: TaggedExt<'a>) -> Self {
+        Self {
+            label,
+            value: TaggedExtItemVal::TaggedExtVal(value),
+        }
+    }
+}
+
+/// Value of an unknown FETCH or STATUS data item.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum TaggedExtItemVal<'a> {
+    TaggedExtVal(TaggedExtVal<'a>),
+    NString(NString<'a>),
+}
+
+/// Extension parameter with optional value, e.g., `CONDSTORE` or `CHANGEDSINCE 12345`.
+///
+/// Used for SELECT, EXAMINE, and RENAME parameters as well as FETCH and STORE modifiers.
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// select-param   = select-param-name [SP select-param-value]
+/// rename-param   = rename-param-name [SP rename-param-value]
+/// fetch-modifier = fetch-modifier-name [SP fetch-modif-params]
+/// store-modifier = store-modifier-name [SP store-modif-params]
+///
+/// ; All names are `tagged-ext-label`s and all values are `tagged-ext-val`s.
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct TaggedExtParam<'a> {
+    pub name: TaggedExtLabel<'a>,
+    pub value: Option<TaggedExtVal<'a>>,
+}
+
+impl<'a> TaggedExtParam<'a> {
+    /// Construct a parameter without a value, e.g., `CONDSTORE`.
+    pub fn new<N>(name: N) -> Result<Self, N::Error>
+    where
+        N: TryInto<TaggedExtLabel<'a>>,
+    {
+        Ok(Self {
+            name: name.try_into()?,
+            value: None,
+        })
+    }
+
+    /// Construct a parameter with a value, e.g., `CHANGEDSINCE 12345`.
+    pub fn with_value<N>(name: N, value: TaggedExtVal<'a>) -> Result<Self, N::Error>
+    where
+        N: TryInto<TaggedExtLabel<'a>>,
+    {
+        Ok(Self {
+            name: name.try_into()?,
+            value: Some(value),
+        })
+    }
+}
+
+/// Extension value.
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// tagged-ext-val = tagged-ext-simple /
+///                  "(" [tagged-ext-comp] ")"
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum TaggedExtVal<'a> {
+    /// Sequence set or number.
+    Simple(TaggedExtSimple),
+    /// Parenthesized list of components.
+    Comp(Vec<TaggedExtComp<'a>>),
+}
+
+/// Simple extension value.
+///
+/// Note: A sequence set consisting of a single number is indistinguishable from a number.
+/// Such sequence sets are represented as [`TaggedExtSimple::Number`].
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// tagged-ext-simple = sequence-set / number / number64
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum TaggedExtSimple {
+    SequenceSet(SequenceSet),
+    Number(u64),
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for TaggedExtSimple {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0u8..=1)? {
+            0 => {
+                let sequence_set = SequenceSet::arbitrary(u)?;
+
+                match sequence_set.0.as_ref() {
+                    [Sequence::Single(SeqOrUid::Value(value))] => Self::Number(value.get() as u64),
+                    _ => Self::SequenceSet(sequence_set),
+                }
+            }
+            1 => Self::Number(u64::arbitrary(u)?),
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// Component of a parenthesized extension value.
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// tagged-ext-comp = astring /
+///                   tagged-ext-comp *(SP tagged-ext-comp) /
+///                   "(" tagged-ext-comp ")"
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum TaggedExtComp<'a> {
+    AString(AString<'a>),
+    List(Vec1<TaggedExtComp<'a>>),
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for TaggedExtComp<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_tagged_ext_comp_limited(u, 5)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+fn arbitrary_tagged_ext_comp_limited<'a>(
+    u: &mut Unstructured<'a>,
+    depth: u8,
+) -> arbitrary::Result<TaggedExtComp<'a>> {
+    if depth == 0 || bool::arbitrary(u)? {
+        return Ok(TaggedExtComp::AString(AString::arbitrary(u)?));
+    }
+
+    let mut comps = vec![arbitrary_tagged_ext_comp_limited(u, depth - 1)?];
+
+    for _ in 0..u.arbitrary_len::<TaggedExtComp>()? {
+        comps.push(arbitrary_tagged_ext_comp_limited(u, depth - 1)?);
+    }
+
+    Ok(TaggedExtComp::List(Vec1::unvalidated(comps)))
+}
+
+/// Selection or return option of an extended LIST command, e.g., `SUBSCRIBED` or `STATUS (MESSAGES)`.
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// list-select-opt = option-extension  ; simplified
+/// return-option   = option-extension  ; simplified
+///
+/// option-extension = (option-standard-tag / option-vendor-tag) [SP option-value]
+/// option-value     = "(" option-val-comp ")"
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct ListOption<'a> {
+    pub name: Atom<'a>,
+    pub value: Option<Vec1<TaggedExtComp<'a>>>,
+}
+
+/// Extended data item of a LIST response, e.g., `"CHILDINFO" ("SUBSCRIBED")`.
+///
+/// # ABNF definition
+///
+/// ```abnf
+/// mbox-list-extended-item     = mbox-list-extended-item-tag SP tagged-ext-val
+/// mbox-list-extended-item-tag = astring
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct ListExtendedItem<'a> {
+    pub tag: AString<'a>,
+    pub value: TaggedExtVal<'a>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_ext_label() {
+        for valid in ["CONDSTORE", "X-FOO", "_a", ".x:1", "a1"] {
+            assert!(TaggedExtLabel::try_from(valid).is_ok());
+        }
+
+        for invalid in ["", "1a", ":a", "a b", "a(", "a*"] {
+            assert!(TaggedExtLabel::try_from(invalid).is_err());
+        }
+    }
+}
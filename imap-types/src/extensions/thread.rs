@@ -13,6 +13,11 @@ use serde::{Deserialize, Serialize};
 use crate::arbitrary::impl_arbitrary_try_from;
 use crate::core::{Atom, Vec1, Vec2};
 
+#[cfg(feature = "ext_sort_thread_evaluate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ext_sort_thread_evaluate")))]
+pub mod evaluate;
+pub mod tree;
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Thread {
@@ -0,0 +1,887 @@
+//! Evaluation of THREAD (RFC 5256) algorithms against messages.
+//!
+//! Implement [`Threadable`] for your message type and call [`thread`] (or [`uid_thread`]). The
+//! result can be wrapped in [`Data::Thread`](crate::response::Data::Thread).
+
+use std::{borrow::Cow, collections::HashMap, num::NonZeroU32};
+
+use crate::{
+    core::{Vec1, Vec2},
+    datetime::DateTime,
+    extensions::{
+        sort::evaluate::{base_subject_with_reply, Comparator},
+        thread::{Thread, ThreadingAlgorithm},
+    },
+};
+
+/// A message that can be threaded.
+pub trait Threadable {
+    /// Message sequence number.
+    fn seq(&self) -> NonZeroU32;
+
+    /// Unique identifier.
+    fn uid(&self) -> NonZeroU32;
+
+    /// Internal date (used when the `Date` header field is missing or can't be parsed).
+    fn internal_date(&self) -> DateTime;
+
+    /// Value of the `Date` header field.
+    fn date(&self) -> Option<Cow<str>>;
+
+    /// Value of the `Subject` header field.
+    fn subject(&self) -> Option<Cow<str>>;
+
+    /// Value of the `Message-ID` header field.
+    fn message_id(&self) -> Option<Cow<str>>;
+
+    /// Value of the `In-Reply-To` header field.
+    fn in_reply_to(&self) -> Option<Cow<str>>;
+
+    /// Value of the `References` header field.
+    fn references(&self) -> Option<Cow<str>>;
+}
+
+/// Thread `messages` using `algorithm` and return threads of sequence numbers.
+///
+/// Base subjects are compared using `comparator`. Returns `None` for unsupported algorithms.
+pub fn thread<M>(
+    messages: &[M],
+    algorithm: &ThreadingAlgorithm,
+    comparator: Comparator,
+) -> Option<Vec<Thread>>
+where
+    M: Threadable,
+{
+    threads(messages, algorithm, comparator, Threadable::seq)
+}
+
+/// Thread `messages` using `algorithm` and return threads of UIDs (`UID THREAD`).
+///
+/// Base subjects are compared using `comparator`. Returns `None` for unsupported algorithms.
+pub fn uid_thread<M>(
+    messages: &[M],
+    algorithm: &ThreadingAlgorithm,
+    comparator: Comparator,
+) -> Option<Vec<Thread>>
+where
+    M: Threadable,
+{
+    threads(messages, algorithm, comparator, Threadable::uid)
+}
+
+fn threads<M>(
+    messages: &[M],
+    algorithm: &ThreadingAlgorithm,
+    comparator: Comparator,
+    identifier: fn(&M) -> NonZeroU32,
+) -> Option<Vec<Thread>>
+where
+    M: Threadable,
+{
+    let mut messages: Vec<_> = messages
+        .iter()
+        .map(|message| Prepared::new(message, comparator, identifier))
+        .collect();
+    messages.sort_by_key(|message| message.seq);
+
+    match algorithm {
+        ThreadingAlgorithm::OrderedSubject => Some(ordered_subject(&messages)),
+        ThreadingAlgorithm::References => Some(references(&messages)),
+        ThreadingAlgorithm::Other(_) => None,
+    }
+}
+
+/// Everything the algorithms need to know about a message.
+struct Prepared {
+    seq: NonZeroU32,
+    identifier: NonZeroU32,
+    /// Sent date as UNIX timestamp.
+    date: i64,
+    /// Comparator key of the base subject.
+    subject: Vec<u8>,
+    is_reply: bool,
+    message_id: Option<String>,
+    references: Vec<String>,
+}
+
+impl Prepared {
+    fn new<M>(message: &M, comparator: Comparator, identifier: fn(&M) -> NonZeroU32) -> Self
+    where
+        M: Threadable,
+    {
+        let date = message
+            .date()
+            .and_then(|date| chrono::DateTime::parse_from_rfc2822(date.trim()).ok())
+            .map(|date| date.timestamp())
+            .unwrap_or_else(|| message.internal_date().as_ref().timestamp());
+
+        let (subject, is_reply) = base_subject_with_reply(&message.subject().unwrap_or_default());
+
+        let references = match message.references().map(|value| message_ids(&value)) {
+            Some(references) if !references.is_empty() => references,
+            // Use the first Message ID of `In-Reply-To` if there are no references.
+            _ => message
+                .in_reply_to()
+                .and_then(|value| message_ids(&value).into_iter().next())
+                .into_iter()
+                .collect(),
+        };
+
+        Self {
+            seq: message.seq(),
+            identifier: identifier(message),
+            date,
+            subject: comparator.key(&subject),
+            is_reply,
+            message_id: message
+                .message_id()
+                .and_then(|value| message_ids(&value).into_iter().next()),
+            references,
+        }
+    }
+}
+
+/// Extract all `<...>` Message IDs (without angle brackets and whitespace).
+fn message_ids(value: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        let end = match rest.find(|c: char| c == '<' || c == '>') {
+            Some(end) => end,
+            None => break,
+        };
+
+        if rest[end..].starts_with('>') {
+            let id: String = rest[..end].split_whitespace().collect();
+
+            if !id.is_empty() {
+                ids.push(id);
+            }
+        }
+
+        rest = &rest[end..];
+    }
+
+    ids
+}
+
+/// ORDEREDSUBJECT ([RFC 5256, section 3])
+///
+/// [RFC 5256, section 3]: https://datatracker.ietf.org/doc/html/rfc5256#section-3
+fn ordered_subject(messages: &[Prepared]) -> Vec<Thread> {
+    // Sort by base subject, then by sent date (and sequence number).
+    let mut sorted: Vec<_> = messages.iter().collect();
+    sorted.sort_by(|a, b| (&a.subject, a.date, a.seq).cmp(&(&b.subject, b.date, b.seq)));
+
+    // Split into threads with the same base subject.
+    let mut groups: Vec<Vec<&Prepared>> = Vec::new();
+    for message in sorted {
+        match groups.last_mut() {
+            Some(group) if group[0].subject == message.subject => group.push(message),
+            _ => groups.push(vec![message]),
+        }
+    }
+
+    // Sort threads by the sent date of their first message.
+    groups.sort_by_key(|group| (group[0].date, group[0].seq));
+
+    groups
+        .into_iter()
+        .map(|group| {
+            let (first, rest) = group.split_first().unwrap();
+
+            // All other messages are children of the first message.
+            match rest {
+                [] => Thread::Members {
+                    prefix: Vec1::from(first.identifier),
+                    answers: None,
+                },
+                [second] => Thread::Members {
+                    prefix: Vec1::unvalidated(vec![first.identifier, second.identifier]),
+                    answers: None,
+                },
+                rest => Thread::Members {
+                    prefix: Vec1::from(first.identifier),
+                    answers: Some(Vec2::unvalidated(
+                        rest.iter()
+                            .map(|message| Thread::Members {
+                                prefix: Vec1::from(message.identifier),
+                                answers: None,
+                            })
+                            .collect(),
+                    )),
+                },
+            }
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Container {
+    /// Index into the prepared messages (`None` for dummies).
+    message: Option<usize>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// Arena of containers used by REFERENCES.
+struct Containers<'a> {
+    messages: &'a [Prepared],
+    containers: Vec<Container>,
+}
+
+impl<'a> Containers<'a> {
+    fn new(messages: &'a [Prepared]) -> Self {
+        Self {
+            messages,
+            containers: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, message: Option<usize>) -> usize {
+        self.containers.push(Container {
+            message,
+            ..Default::default()
+        });
+
+        self.containers.len() - 1
+    }
+
+    fn is_dummy(&self, id: usize) -> bool {
+        self.containers[id].message.is_none()
+    }
+
+    /// Returns whether `ancestor` is `id` or one of its ancestors.
+    ///
+    /// Walks up from `id` and down from `ancestor` in lockstep. Thus, the cost is bounded by the
+    /// smaller of the depth of `id` and the size of the subtree of `ancestor`.
+    fn is_ancestor(&self, ancestor: usize, id: usize) -> bool {
+        let mut up = Some(id);
+        let mut down = vec![ancestor];
+
+        loop {
+            match up {
+                Some(current) if current == ancestor => return true,
+                Some(current) => up = self.containers[current].parent,
+                None => return false,
+            }
+
+            match down.pop() {
+                Some(current) if current == id => return true,
+                Some(current) => down.extend(&self.containers[current].children),
+                None => return false,
+            }
+        }
+    }
+
+    /// Returns `roots` and all their descendants such that children come before their parents.
+    fn post_order(&self, roots: &[usize]) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut stack = roots.to_vec();
+
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(&self.containers[id].children);
+        }
+
+        order.reverse();
+        order
+    }
+
+    /// Make `child` a child of `parent` unless this would introduce a loop.
+    fn link(&mut self, parent: usize, child: usize) {
+        if self.is_ancestor(child, parent) {
+            return;
+        }
+
+        self.containers[child].parent = Some(parent);
+        self.containers[parent].children.push(child);
+    }
+
+    fn unlink(&mut self, child: usize) {
+        if let Some(parent) = self.containers[child].parent.take() {
+            self.containers[parent].children.retain(|id| *id != child);
+        }
+    }
+
+    /// The message representing the container, i.e., its own or (for dummies) its first child's.
+    fn representative(&self, id: usize) -> Option<&'a Prepared> {
+        let container = &self.containers[id];
+
+        match container.message {
+            Some(message) => Some(&self.messages[message]),
+            None => container
+                .children
+                .first()
+                .and_then(|child| self.containers[*child].message)
+                .map(|message| &self.messages[message]),
+        }
+    }
+
+    /// Remove dummies without children and promote the children of other dummies (RFC 5256,
+    /// section 3, step 4).
+    ///
+    /// Dummies in the root set are only kept when they have more than one child.
+    fn prune(&mut self, roots: Vec<usize>) -> Vec<usize> {
+        // Children are pruned before their parents, so promoted children are already pruned.
+        for id in self.post_order(&roots) {
+            let children = std::mem::take(&mut self.containers[id].children);
+            self.containers[id].children = self.promote(children, false);
+        }
+
+        self.promote(roots, true)
+    }
+
+    /// Replace dummies in `ids` by their children.
+    fn promote(&mut self, ids: Vec<usize>, is_root: bool) -> Vec<usize> {
+        let mut promoted = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            if !self.is_dummy(id) || (is_root && self.containers[id].children.len() > 1) {
+                promoted.push(id);
+            } else {
+                // Dummies without children are dropped, otherwise their children are promoted.
+                promoted.append(&mut self.containers[id].children);
+            }
+        }
+
+        promoted
+    }
+
+    /// Sort siblings by sent date, oldest first (RFC 5256, section 3, step 6).
+    ///
+    /// Children are sorted before their parents because the first child represents a dummy.
+    fn sort(&mut self, roots: &mut [usize]) {
+        for id in self.post_order(roots) {
+            let mut children = std::mem::take(&mut self.containers[id].children);
+            self.sort_siblings(&mut children);
+            self.containers[id].children = children;
+        }
+
+        self.sort_siblings(roots);
+    }
+
+    fn sort_siblings(&self, ids: &mut [usize]) {
+        ids.sort_by_key(|id| {
+            self.representative(*id)
+                .map(|message| (message.date, message.seq))
+        });
+    }
+
+    /// Convert `roots` and their descendants into threads.
+    fn threads(&self, roots: &[usize]) -> Vec<Thread> {
+        let mut partials: Vec<Option<Partial>> = Vec::new();
+        partials.resize_with(self.containers.len(), || None);
+
+        for id in self.post_order(roots) {
+            let container = &self.containers[id];
+
+            let mut children: Vec<Partial> = container
+                .children
+                .iter()
+                .map(|child| partials[*child].take().unwrap())
+                .collect();
+
+            let partial = match container.message {
+                None => Partial::Nested(children.into_iter().map(Partial::finish).collect()),
+                Some(message) => {
+                    let identifier = self.messages[message].identifier;
+
+                    match children.len() {
+                        0 => Partial::Members {
+                            prefix: vec![identifier],
+                            answers: None,
+                        },
+                        // A single child continues the prefix.
+                        1 => match children.pop().unwrap() {
+                            Partial::Members {
+                                mut prefix,
+                                answers,
+                            } => {
+                                prefix.push(identifier);
+
+                                Partial::Members { prefix, answers }
+                            }
+                            Partial::Nested(answers) => Partial::Members {
+                                prefix: vec![identifier],
+                                answers: Some(answers),
+                            },
+                        },
+                        _ => Partial::Members {
+                            prefix: vec![identifier],
+                            answers: Some(children.into_iter().map(Partial::finish).collect()),
+                        },
+                    }
+                }
+            };
+
+            partials[id] = Some(partial);
+        }
+
+        roots
+            .iter()
+            .map(|id| partials[*id].take().unwrap().finish())
+            .collect()
+    }
+}
+
+/// A thread under construction.
+enum Partial {
+    /// The prefix is stored in reverse order so that parents can be appended.
+    Members {
+        prefix: Vec<NonZeroU32>,
+        answers: Option<Vec<Thread>>,
+    },
+    Nested(Vec<Thread>),
+}
+
+impl Partial {
+    fn finish(self) -> Thread {
+        match self {
+            Self::Members {
+                mut prefix,
+                answers,
+            } => {
+                prefix.reverse();
+
+                Thread::Members {
+                    prefix: Vec1::unvalidated(prefix),
+                    answers: answers.map(Vec2::unvalidated),
+                }
+            }
+            Self::Nested(answers) => Thread::Nested {
+                answers: Vec2::unvalidated(answers),
+            },
+        }
+    }
+}
+
+/// REFERENCES ([RFC 5256, section 3])
+///
+/// [RFC 5256, section 3]: https://datatracker.ietf.org/doc/html/rfc5256#section-3
+fn references(messages: &[Prepared]) -> Vec<Thread> {
+    let mut containers = Containers::new(messages);
+    let mut id_table: HashMap<&str, usize> = HashMap::new();
+
+    // (1) Link messages by their references.
+    for (index, message) in messages.iter().enumerate() {
+        // Messages without (or with a duplicate) Message ID are not referenceable.
+        let own = match message.message_id.as_deref() {
+            Some(message_id) => match id_table.get(message_id) {
+                Some(&id) if containers.is_dummy(id) => {
+                    containers.containers[id].message = Some(index);
+                    id
+                }
+                Some(_) => containers.push(Some(index)),
+                None => {
+                    let id = containers.push(Some(index));
+                    id_table.insert(message_id, id);
+                    id
+                }
+            },
+            None => containers.push(Some(index)),
+        };
+
+        // (A) Link the references, keeping existing links.
+        let references: Vec<usize> = message
+            .references
+            .iter()
+            .map(|reference| {
+                *id_table
+                    .entry(reference.as_str())
+                    .or_insert_with(|| containers.push(None))
+            })
+            .collect();
+
+        for pair in references.windows(2) {
+            if containers.containers[pair[1]].parent.is_none() {
+                containers.link(pair[0], pair[1]);
+            }
+        }
+
+        // (B) Link the last reference to the message, replacing an existing link.
+        containers.unlink(own);
+        if let Some(&parent) = references.last() {
+            containers.link(parent, own);
+        }
+    }
+
+    // (2) Gather the root set.
+    let roots: Vec<usize> = (0..containers.containers.len())
+        .filter(|id| containers.containers[*id].parent.is_none())
+        .collect();
+
+    // (3) Discard the id table.
+    drop(id_table);
+
+    // (4) Prune dummies.
+    let roots = containers.prune(roots);
+
+    // (5) Group the root set by base subject.
+    let mut subject_table: HashMap<&[u8], usize> = HashMap::new();
+
+    for id in roots.iter().copied() {
+        let message = match containers.representative(id) {
+            Some(message) if !message.subject.is_empty() => message,
+            _ => continue,
+        };
+
+        match subject_table.get(message.subject.as_slice()) {
+            None => {
+                subject_table.insert(&message.subject, id);
+            }
+            Some(&existing) => {
+                let replace = !containers.is_dummy(existing)
+                    && (containers.is_dummy(id)
+                        || (containers.representative(existing).unwrap().is_reply
+                            && !message.is_reply));
+
+                if replace {
+                    subject_table.insert(&message.subject, id);
+                }
+            }
+        }
+    }
+
+    let mut merged: Vec<Option<usize>> = roots.into_iter().map(Some).collect();
+
+    for index in 0..merged.len() {
+        let id = match merged[index] {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let message = match containers.representative(id) {
+            Some(message) if !message.subject.is_empty() => message,
+            _ => continue,
+        };
+
+        let existing = subject_table[message.subject.as_slice()];
+
+        if existing == id {
+            continue;
+        }
+
+        match (containers.is_dummy(existing), containers.is_dummy(id)) {
+            (true, true) => {
+                let children = std::mem::take(&mut containers.containers[id].children);
+                containers.containers[existing].children.extend(children);
+            }
+            (true, false) => {
+                containers.containers[existing].children.push(id);
+            }
+            (false, _)
+                if message.is_reply && !containers.representative(existing).unwrap().is_reply =>
+            {
+                containers.containers[existing].children.push(id);
+            }
+            (false, _) => {
+                let dummy = containers.push(None);
+                containers.containers[dummy].children = vec![existing, id];
+
+                if let Some(slot) = merged.iter_mut().find(|slot| **slot == Some(existing)) {
+                    *slot = Some(dummy);
+                }
+                subject_table.insert(&message.subject, dummy);
+            }
+        }
+
+        merged[index] = None;
+    }
+
+    let mut roots: Vec<usize> = merged.into_iter().flatten().collect();
+
+    // (6) Sort.
+    containers.sort(&mut roots);
+
+    containers.threads(&roots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Message {
+        seq: u32,
+        date: &'static str,
+        subject: &'static str,
+        message_id: Option<String>,
+        in_reply_to: Option<String>,
+        references: Option<String>,
+    }
+
+    impl Threadable for Message {
+        fn seq(&self) -> NonZeroU32 {
+            NonZeroU32::new(self.seq).unwrap()
+        }
+
+        fn uid(&self) -> NonZeroU32 {
+            NonZeroU32::new(self.seq * 10).unwrap()
+        }
+
+        fn internal_date(&self) -> DateTime {
+            DateTime::try_from(
+                chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap(),
+            )
+            .unwrap()
+        }
+
+        fn date(&self) -> Option<Cow<str>> {
+            Some(Cow::Borrowed(self.date))
+        }
+
+        fn subject(&self) -> Option<Cow<str>> {
+            Some(Cow::Borrowed(self.subject))
+        }
+
+        fn message_id(&self) -> Option<Cow<str>> {
+            self.message_id.as_deref().map(Cow::Borrowed)
+        }
+
+        fn in_reply_to(&self) -> Option<Cow<str>> {
+            self.in_reply_to.as_deref().map(Cow::Borrowed)
+        }
+
+        fn references(&self) -> Option<Cow<str>> {
+            self.references.as_deref().map(Cow::Borrowed)
+        }
+    }
+
+    fn message(
+        seq: u32,
+        day: u32,
+        subject: &'static str,
+        message_id: Option<&str>,
+        references: Option<&str>,
+    ) -> Message {
+        const DATES: [&str; 10] = [
+            "1 Jan 2024 00:00:00 +0000",
+            "2 Jan 2024 00:00:00 +0000",
+            "3 Jan 2024 00:00:00 +0000",
+            "4 Jan 2024 00:00:00 +0000",
+            "5 Jan 2024 00:00:00 +0000",
+            "6 Jan 2024 00:00:00 +0000",
+            "7 Jan 2024 00:00:00 +0000",
+            "8 Jan 2024 00:00:00 +0000",
+            "9 Jan 2024 00:00:00 +0000",
+            "10 Jan 2024 00:00:00 +0000",
+        ];
+
+        Message {
+            seq,
+            date: DATES[day as usize - 1],
+            subject,
+            message_id: message_id.map(ToOwned::to_owned),
+            in_reply_to: None,
+            references: references.map(ToOwned::to_owned),
+        }
+    }
+
+    fn render(threads: &[Thread]) -> String {
+        threads.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_message_ids() {
+        let tests = [
+            ("<a@b>", vec!["a@b"]),
+            ("  <a@b>\r\n <c@d> <e@f>", vec!["a@b", "c@d", "e@f"]),
+            ("<a@b> (comment) <c @ d>", vec!["a@b", "c@d"]),
+            ("<broken <a@b>", vec!["a@b"]),
+            ("<>", vec![]),
+            ("<a@b", vec![]),
+            ("", vec![]),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(message_ids(test), expected, "{test:?}");
+        }
+    }
+
+    #[test]
+    fn test_ordered_subject() {
+        let messages = [
+            message(1, 3, "Hello", None, None),
+            message(2, 1, "Re: Hello", None, None),
+            message(3, 2, "Other", None, None),
+            message(4, 4, "RE: hello", None, None),
+            message(5, 5, "Other (fwd)", None, None),
+            message(6, 6, "Single", None, None),
+        ];
+
+        let threads = thread(
+            &messages,
+            &ThreadingAlgorithm::OrderedSubject,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(render(&threads), "(2 (1)(4))(3 5)(6)");
+
+        let threads = uid_thread(
+            &messages,
+            &ThreadingAlgorithm::OrderedSubject,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(render(&threads), "(20 (10)(40))(30 50)(60)");
+    }
+
+    #[test]
+    fn test_references() {
+        let messages = [
+            // A thread with a chain and a fork.
+            message(1, 1, "Thread", Some("<1@x>"), None),
+            message(2, 2, "Re: Thread", Some("<2@x>"), Some("<1@x>")),
+            message(3, 3, "Re: Thread", Some("<3@x>"), Some("<1@x> <2@x>")),
+            message(4, 4, "Re: Thread", Some("<4@x>"), Some("<1@x> <2@x>")),
+            // The parent <missing@x> is unknown, so two siblings share a dummy root.
+            message(5, 5, "Dummy", Some("<5@x>"), Some("<missing@x>")),
+            message(6, 6, "Re: Dummy", Some("<6@x>"), Some("<missing@x>")),
+            // A single child of an unknown parent is promoted.
+            message(7, 7, "Orphan", Some("<7@x>"), Some("<gone@x>")),
+        ];
+
+        let threads = thread(
+            &messages,
+            &ThreadingAlgorithm::References,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(render(&threads), "(1 2 (3)(4))((5)(6))(7)");
+    }
+
+    #[test]
+    fn test_references_in_reply_to() {
+        let mut reply = message(2, 2, "Something else", Some("<2@x>"), None);
+        reply.in_reply_to = Some("<1@x> <unrelated@x>".into());
+
+        let messages = [message(1, 1, "Hello", Some("<1@x>"), None), reply];
+
+        let threads = thread(
+            &messages,
+            &ThreadingAlgorithm::References,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(render(&threads), "(1 2)");
+    }
+
+    #[test]
+    fn test_references_loops_and_duplicates() {
+        let messages = [
+            // References itself.
+            message(1, 1, "A", Some("<1@x>"), Some("<1@x>")),
+            // Loop between 2 and 3.
+            message(2, 2, "B", Some("<2@x>"), Some("<3@x>")),
+            message(3, 3, "C", Some("<3@x>"), Some("<2@x>")),
+            // Duplicate Message ID.
+            message(4, 4, "D", Some("<1@x>"), None),
+            // No Message ID.
+            message(5, 5, "E", None, Some("<4@x> <5@x>")),
+        ];
+
+        let threads = thread(
+            &messages,
+            &ThreadingAlgorithm::References,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(render(&threads), "(1)(3 2)(4)(5)");
+    }
+
+    #[test]
+    fn test_references_subject_merging() {
+        let messages = [
+            message(1, 1, "Hello", Some("<1@x>"), None),
+            // Same base subject, not linked by references, and a reply.
+            message(2, 2, "Re: Hello", Some("<2@x>"), None),
+            // Same base subject, neither is a reply, so both get a dummy parent.
+            message(3, 3, "World", Some("<3@x>"), None),
+            message(4, 4, "World", Some("<4@x>"), None),
+            // Empty subjects are never merged.
+            message(5, 5, "", Some("<5@x>"), None),
+            message(6, 6, "Re:", Some("<6@x>"), None),
+        ];
+
+        let threads = thread(
+            &messages,
+            &ThreadingAlgorithm::References,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(render(&threads), "(1 2)((3)(4))(5)(6)");
+    }
+
+    #[test]
+    fn test_references_deep() {
+        const DEPTH: u32 = 100_000;
+
+        // A long chain of replies ...
+        let messages: Vec<_> = (1..=DEPTH)
+            .map(|seq| {
+                let message_id = format!("<{seq}@x>");
+                let references = format!("<{}@x>", seq - 1);
+
+                message(
+                    seq,
+                    1,
+                    "Deep",
+                    Some(&message_id),
+                    (seq > 1).then_some(references.as_str()),
+                )
+            })
+            .collect();
+
+        let threads = thread(
+            &messages,
+            &ThreadingAlgorithm::References,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(
+            threads,
+            vec![Thread::Members {
+                prefix: Vec1::unvalidated(
+                    (1..=DEPTH)
+                        .map(|seq| NonZeroU32::new(seq).unwrap())
+                        .collect()
+                ),
+                answers: None,
+            }]
+        );
+
+        // ... and a long chain of unknown references.
+        let references: String = (1..=DEPTH).map(|id| format!("<{id}@y> ")).collect();
+        let messages = [message(1, 1, "Deep", Some("<0@x>"), Some(&references))];
+
+        let threads = thread(
+            &messages,
+            &ThreadingAlgorithm::References,
+            Comparator::AsciiCasemap,
+        )
+        .unwrap();
+
+        assert_eq!(render(&threads), "(1)");
+    }
+
+    #[test]
+    fn test_other_algorithm() {
+        let messages = [message(1, 1, "Hello", None, None)];
+        let algorithm = ThreadingAlgorithm::from(crate::core::Atom::try_from("X").unwrap());
+
+        assert_eq!(
+            thread(&messages, &algorithm, Comparator::AsciiCasemap),
+            None
+        );
+    }
+}
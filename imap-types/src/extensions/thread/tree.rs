@@ -0,0 +1,540 @@
+//! Tree view over [`Thread`]s.
+//!
+//! [`Thread`] mirrors the wire format, i.e., chains of single replies are folded into a prefix
+//! and dummy parents are implicit. [`ThreadTree`] flattens threads into nodes (in display order)
+//! with explicit parents, children, and depths.
+//!
+//! ```rust
+//! use std::num::NonZeroU32;
+//!
+//! use imap_types::extensions::thread::{tree::ThreadTree, Thread};
+//!
+//! let n = |n| NonZeroU32::new(n).unwrap();
+//!
+//! // (1 2 (3)(4))
+//! let thread = Thread::Members {
+//!     prefix: vec![n(1), n(2)].try_into().unwrap(),
+//!     answers: Some(
+//!         vec![
+//!             Thread::Members {
+//!                 prefix: n(3).into(),
+//!                 answers: None,
+//!             },
+//!             Thread::Members {
+//!                 prefix: n(4).into(),
+//!                 answers: None,
+//!             },
+//!         ]
+//!         .try_into()
+//!         .unwrap(),
+//!     ),
+//! };
+//!
+//! let tree = ThreadTree::new(&[thread]);
+//! let two = tree.find(n(2)).unwrap();
+//!
+//! assert_eq!(tree.roots(), &[0]);
+//! assert_eq!(tree.nodes()[two].children(), &[2, 3]);
+//! assert_eq!(tree.nodes()[two].depth(), 1);
+//! assert_eq!(tree.sequence_set(), "1,2,3,4".try_into().ok());
+//! ```
+
+use std::num::NonZeroU32;
+
+use crate::{
+    core::{Vec1, Vec2},
+    extensions::thread::{tree::error::ThreadTreeError, Thread},
+    sequence::SequenceSet,
+};
+
+/// A node of a [`ThreadTree`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThreadNode {
+    message: Option<NonZeroU32>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    depth: usize,
+    /// End (exclusive) of the subtree in display order.
+    end: usize,
+}
+
+impl ThreadNode {
+    /// Message (sequence number or UID), or `None` for dummy parents.
+    pub fn message(&self) -> Option<NonZeroU32> {
+        self.message
+    }
+
+    /// Index of the parent node.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Indices of the child nodes (in display order).
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Depth of the node (roots have depth `0`).
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Whether the node is a dummy parent.
+    pub fn is_dummy(&self) -> bool {
+        self.message.is_none()
+    }
+}
+
+/// Threads as a tree of nodes.
+///
+/// Nodes are stored in display order, i.e., in the order they appear in a `THREAD` response,
+/// and identified by their index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ThreadTree {
+    nodes: Vec<ThreadNode>,
+    roots: Vec<usize>,
+}
+
+impl ThreadTree {
+    /// Build a tree from threads (as contained in [`Data::Thread`](crate::response::Data::Thread)).
+    pub fn new(threads: &[Thread]) -> Self {
+        let mut tree = Self::default();
+        let mut stack: Vec<(&Thread, Option<usize>)> =
+            threads.iter().rev().map(|thread| (thread, None)).collect();
+
+        while let Some((thread, parent)) = stack.pop() {
+            let (last, answers) = match thread {
+                Thread::Members { prefix, answers } => {
+                    let mut last = parent;
+                    for message in prefix.as_ref() {
+                        last = Some(tree.push(Some(*message), last));
+                    }
+
+                    (last, answers.as_ref().map(|answers| answers.as_ref()))
+                }
+                Thread::Nested { answers } => {
+                    (Some(tree.push(None, parent)), Some(answers.as_ref()))
+                }
+            };
+
+            for answer in answers.unwrap_or_default().iter().rev() {
+                stack.push((answer, last));
+            }
+        }
+
+        tree.finish();
+        tree
+    }
+
+    /// Build a tree from a generic parent-pointer tree.
+    ///
+    /// Every element of `nodes` is a message (or `None` for a dummy) and the index of its parent
+    /// in `nodes`. Children are ordered as they appear in `nodes`.
+    ///
+    /// The tree must be representable as [`Thread`]s, i.e., dummies need at least two children
+    /// and a message with a single child can't have a dummy child.
+    ///
+    /// Note: The nodes of the returned tree are in display order and may have different indices.
+    pub fn from_parents(
+        nodes: &[(Option<NonZeroU32>, Option<usize>)],
+    ) -> Result<Self, ThreadTreeError> {
+        let mut children = vec![Vec::new(); nodes.len()];
+        let mut roots = Vec::new();
+
+        for (index, (_, parent)) in nodes.iter().enumerate() {
+            match parent {
+                Some(parent) if *parent >= nodes.len() => {
+                    return Err(ThreadTreeError::UnknownParent {
+                        node: index,
+                        parent: *parent,
+                    });
+                }
+                Some(parent) => children[*parent].push(index),
+                None => roots.push(index),
+            }
+        }
+
+        for (index, (message, _)) in nodes.iter().enumerate() {
+            match (message, children[index].as_slice()) {
+                (None, [] | [_]) => return Err(ThreadTreeError::Dummy { node: index }),
+                (Some(_), [child]) if nodes[*child].0.is_none() => {
+                    return Err(ThreadTreeError::DummyAnswer { node: index });
+                }
+                _ => {}
+            }
+        }
+
+        let mut tree = Self::default();
+        let mut stack: Vec<(usize, Option<usize>)> =
+            roots.iter().rev().map(|root| (*root, None)).collect();
+
+        while let Some((index, parent)) = stack.pop() {
+            let id = tree.push(nodes[index].0, parent);
+
+            for child in children[index].iter().rev() {
+                stack.push((*child, Some(id)));
+            }
+        }
+
+        // Nodes that are not reachable from a root are part of a cycle.
+        if tree.nodes.len() != nodes.len() {
+            let mut reachable = vec![false; nodes.len()];
+            let mut stack = roots;
+
+            while let Some(index) = stack.pop() {
+                reachable[index] = true;
+                stack.extend(&children[index]);
+            }
+
+            let node = reachable.iter().position(|reachable| !reachable).unwrap();
+
+            return Err(ThreadTreeError::Cycle { node });
+        }
+
+        tree.finish();
+        Ok(tree)
+    }
+
+    /// Convert the tree into a generic parent-pointer tree (see [`Self::from_parents`]).
+    pub fn to_parents(&self) -> Vec<(Option<NonZeroU32>, Option<usize>)> {
+        self.nodes
+            .iter()
+            .map(|node| (node.message, node.parent))
+            .collect()
+    }
+
+    /// Convert the tree back into threads.
+    pub fn to_threads(&self) -> Vec<Thread> {
+        let mut threads: Vec<Option<Thread>> = vec![None; self.nodes.len()];
+
+        // Children come after their parents in display order, so build threads from the end.
+        for id in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[id];
+
+            if !self.is_chain_start(id) {
+                continue;
+            }
+
+            let thread = match node.message {
+                None => Thread::Nested {
+                    answers: Vec2::unvalidated(self.take(&mut threads, &node.children)),
+                },
+                Some(message) => {
+                    let mut prefix = vec![message];
+                    let mut last = node;
+
+                    while let [child] = last.children.as_slice() {
+                        last = &self.nodes[*child];
+                        prefix.push(last.message.unwrap());
+                    }
+
+                    Thread::Members {
+                        prefix: Vec1::unvalidated(prefix),
+                        answers: match last.children.len() {
+                            0 => None,
+                            _ => Some(Vec2::unvalidated(self.take(&mut threads, &last.children))),
+                        },
+                    }
+                }
+            };
+
+            threads[id] = Some(thread);
+        }
+
+        self.take(&mut threads, &self.roots)
+    }
+
+    /// All nodes in display order.
+    pub fn nodes(&self) -> &[ThreadNode] {
+        &self.nodes
+    }
+
+    /// Indices of the root nodes (in display order).
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    /// Index of the node containing `message`.
+    pub fn find(&self, message: NonZeroU32) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.message == Some(message))
+    }
+
+    /// Indices of all ancestors of `id`, starting with its parent.
+    pub fn ancestors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.nodes[id].parent, |id| self.nodes[*id].parent)
+    }
+
+    /// Indices of all descendants of `id` (in display order).
+    pub fn descendants(&self, id: usize) -> std::ops::Range<usize> {
+        id + 1..self.nodes[id].end
+    }
+
+    /// Index of the root of the thread containing `id`.
+    pub fn root(&self, id: usize) -> usize {
+        self.ancestors(id).last().unwrap_or(id)
+    }
+
+    /// All messages in display order.
+    pub fn messages(&self) -> Vec<NonZeroU32> {
+        self.nodes.iter().filter_map(ThreadNode::message).collect()
+    }
+
+    /// All messages as a sequence set, or `None` if there are no messages.
+    pub fn sequence_set(&self) -> Option<SequenceSet> {
+        SequenceSet::try_from(self.messages()).ok()
+    }
+
+    /// All messages of the subtree rooted at `id` (including `id`) as a sequence set.
+    pub fn subtree_sequence_set(&self, id: usize) -> Option<SequenceSet> {
+        let messages: Vec<_> = self.nodes[id..self.nodes[id].end]
+            .iter()
+            .filter_map(ThreadNode::message)
+            .collect();
+
+        SequenceSet::try_from(messages).ok()
+    }
+
+    fn push(&mut self, message: Option<NonZeroU32>, parent: Option<usize>) -> usize {
+        let id = self.nodes.len();
+
+        let depth = match parent {
+            Some(parent) => {
+                self.nodes[parent].children.push(id);
+                self.nodes[parent].depth + 1
+            }
+            None => {
+                self.roots.push(id);
+                0
+            }
+        };
+
+        self.nodes.push(ThreadNode {
+            message,
+            parent,
+            children: Vec::new(),
+            depth,
+            end: id + 1,
+        });
+
+        id
+    }
+
+    /// Compute the end of all subtrees.
+    fn finish(&mut self) {
+        for id in (0..self.nodes.len()).rev() {
+            let end = match self.nodes[id].children.last() {
+                Some(last) => self.nodes[*last].end,
+                None => id + 1,
+            };
+
+            self.nodes[id].end = end;
+        }
+    }
+
+    /// Whether `id` starts a new [`Thread`] (and isn't folded into its parent's prefix).
+    fn is_chain_start(&self, id: usize) -> bool {
+        match self.nodes[id].parent {
+            Some(parent) => {
+                let parent = &self.nodes[parent];
+                parent.is_dummy() || parent.children.len() != 1 || self.nodes[id].is_dummy()
+            }
+            None => true,
+        }
+    }
+
+    fn take(&self, threads: &mut [Option<Thread>], ids: &[usize]) -> Vec<Thread> {
+        ids.iter().map(|id| threads[*id].take().unwrap()).collect()
+    }
+}
+
+/// Error-related types.
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum ThreadTreeError {
+        #[error("Parent {parent} of node {node} does not exist")]
+        UnknownParent { node: usize, parent: usize },
+        #[error("Node {node} is part of a cycle")]
+        Cycle { node: usize },
+        #[error("Dummy node {node} must have at least two children")]
+        Dummy { node: usize },
+        #[error("Node {node} must not have a dummy as its only child")]
+        DummyAnswer { node: usize },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(n: u32) -> NonZeroU32 {
+        NonZeroU32::new(n).unwrap()
+    }
+
+    fn leaf(message: u32) -> Thread {
+        Thread::Members {
+            prefix: n(message).into(),
+            answers: None,
+        }
+    }
+
+    /// `(1 2 (3)(4 5))((6)(7 (8)(9)))(10)`
+    fn threads() -> Vec<Thread> {
+        vec![
+            Thread::Members {
+                prefix: Vec1::unvalidated(vec![n(1), n(2)]),
+                answers: Some(Vec2::from((
+                    leaf(3),
+                    Thread::Members {
+                        prefix: Vec1::unvalidated(vec![n(4), n(5)]),
+                        answers: None,
+                    },
+                ))),
+            },
+            Thread::Nested {
+                answers: Vec2::from((
+                    leaf(6),
+                    Thread::Members {
+                        prefix: n(7).into(),
+                        answers: Some(Vec2::from((leaf(8), leaf(9)))),
+                    },
+                )),
+            },
+            leaf(10),
+        ]
+    }
+
+    #[test]
+    fn test_display_order() {
+        let threads = threads();
+
+        assert_eq!(
+            threads.iter().map(ToString::to_string).collect::<String>(),
+            "(1 2 (3)(4 5))((6)(7 (8)(9)))(10)"
+        );
+
+        let tree = ThreadTree::new(&threads);
+
+        assert_eq!(
+            tree.nodes()
+                .iter()
+                .map(|node| (node.message().map(NonZeroU32::get), node.depth()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(1), 0),
+                (Some(2), 1),
+                (Some(3), 2),
+                (Some(4), 2),
+                (Some(5), 3),
+                (None, 0),
+                (Some(6), 1),
+                (Some(7), 1),
+                (Some(8), 2),
+                (Some(9), 2),
+                (Some(10), 0),
+            ]
+        );
+        assert_eq!(tree.roots(), &[0, 5, 10]);
+        assert_eq!(tree.messages(), (1..=10).map(n).collect::<Vec<_>>());
+        assert_eq!(
+            tree.sequence_set(),
+            SequenceSet::try_from("1,2,3,4,5,6,7,8,9,10").ok()
+        );
+        assert_eq!(ThreadTree::new(&[]).sequence_set(), None);
+    }
+
+    #[test]
+    fn test_navigation() {
+        let tree = ThreadTree::new(&threads());
+
+        let two = tree.find(n(2)).unwrap();
+        assert_eq!(tree.nodes()[two].parent(), Some(0));
+        assert_eq!(tree.nodes()[two].children(), &[2, 3]);
+
+        let five = tree.find(n(5)).unwrap();
+        assert_eq!(tree.ancestors(five).collect::<Vec<_>>(), vec![3, 1, 0]);
+        assert_eq!(tree.root(five), 0);
+        assert_eq!(tree.root(0), 0);
+
+        let dummy = tree.roots()[1];
+        assert!(tree.nodes()[dummy].is_dummy());
+        assert_eq!(tree.nodes()[dummy].children(), &[6, 7]);
+        assert_eq!(tree.descendants(dummy), 6..10);
+        assert_eq!(tree.descendants(10), 11..11);
+        assert_eq!(
+            tree.subtree_sequence_set(dummy),
+            SequenceSet::try_from("6,7,8,9").ok()
+        );
+        assert_eq!(tree.find(n(11)), None);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let threads = threads();
+        let tree = ThreadTree::new(&threads);
+
+        assert_eq!(tree.to_threads(), threads);
+
+        let parents = tree.to_parents();
+        assert_eq!(ThreadTree::from_parents(&parents).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_from_parents() {
+        // Children are ordered as they appear, parents may come after their children.
+        let parents = [
+            (Some(n(3)), Some(2)),
+            (Some(n(4)), Some(2)),
+            (Some(n(1)), None),
+            (Some(n(2)), Some(0)),
+        ];
+
+        let tree = ThreadTree::from_parents(&parents).unwrap();
+        assert_eq!(
+            tree.to_threads()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<String>(),
+            "(1 (3 2)(4))"
+        );
+    }
+
+    #[test]
+    fn test_from_parents_invalid() {
+        let tests = [
+            (
+                vec![(Some(n(1)), Some(1))],
+                ThreadTreeError::UnknownParent { node: 0, parent: 1 },
+            ),
+            (
+                vec![(Some(n(1)), Some(1)), (Some(n(2)), Some(0))],
+                ThreadTreeError::Cycle { node: 0 },
+            ),
+            (
+                vec![(Some(n(1)), None), (Some(n(2)), Some(1))],
+                ThreadTreeError::Cycle { node: 1 },
+            ),
+            (vec![(None, None)], ThreadTreeError::Dummy { node: 0 }),
+            (
+                vec![(None, None), (Some(n(1)), Some(0))],
+                ThreadTreeError::Dummy { node: 0 },
+            ),
+            (
+                vec![
+                    (Some(n(1)), None),
+                    (None, Some(0)),
+                    (Some(n(2)), Some(1)),
+                    (Some(n(3)), Some(1)),
+                ],
+                ThreadTreeError::DummyAnswer { node: 0 },
+            ),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(ThreadTree::from_parents(&test), Err(expected), "{test:?}");
+        }
+    }
+}
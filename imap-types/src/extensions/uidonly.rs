@@ -0,0 +1,40 @@
+//! IMAP Extension for Using and Returning Unique Identifiers (UIDs) Only
+//!
+//! See <https://datatracker.ietf.org/doc/html/rfc9586>.
+//!
+//! This extension extends ...
+//!
+//! * the [Capability](crate::response::Capability) enum with a new variant [Capability::UidOnly](crate::response::Capability#variant.UidOnly),
+//! * the [CapabilityEnable](crate::extensions::enable::CapabilityEnable) enum with a new variant [CapabilityEnable::UidOnly](crate::extensions::enable::CapabilityEnable#variant.UidOnly),
+//! * the [Data] enum with a new variant [Data::UidFetch], and
+//! * the [Code](crate::response::Code) enum with a new variant [Code::UidRequired](crate::response::Code#variant.UidRequired).
+//!
+//! <div class="warning">
+//! This extension must only be used after it was enabled via `ENABLE UIDONLY`.
+//! </div>
+
+use std::num::NonZeroU32;
+
+use crate::{
+    core::Vec1,
+    fetch::MessageDataItem,
+    response::{error::FetchError, Data},
+};
+
+impl<'a> Data<'a> {
+    /// Construct a UIDFETCH response, i.e., a FETCH response identifying the message by `uid`.
+    ///
+    /// <div class="warning">
+    /// This response must only be sent after UIDONLY was enabled.
+    /// </div>
+    pub fn uid_fetch<U, I>(uid: U, items: I) -> Result<Self, FetchError<U::Error, I::Error>>
+    where
+        U: TryInto<NonZeroU32>,
+        I: TryInto<Vec1<MessageDataItem<'a>>>,
+    {
+        let uid = uid.try_into().map_err(FetchError::SeqOrUid)?;
+        let items = items.try_into().map_err(FetchError::InvalidItems)?;
+
+        Ok(Self::UidFetch { uid, items })
+    }
+}
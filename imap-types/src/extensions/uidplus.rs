@@ -6,7 +6,7 @@ use bounded_static_derive::ToStatic;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::core::Vec1;
+use crate::{core::Vec1, sequence::Ranges};
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -20,3 +20,134 @@ pub enum UidElement {
     Single(NonZeroU32),
     Range(NonZeroU32, NonZeroU32),
 }
+
+impl UidSet {
+    /// Sort and merge all UIDs and ranges.
+    pub fn compact(&self) -> UidSet {
+        Self::from_ranges(self.ranges()).unwrap()
+    }
+
+    /// Union of `self` and `other`.
+    pub fn union(&self, other: &UidSet) -> UidSet {
+        Self::from_ranges(self.ranges().union(&other.ranges())).unwrap()
+    }
+
+    /// Intersection of `self` and `other` (or `None` if empty).
+    pub fn intersection(&self, other: &UidSet) -> Option<UidSet> {
+        Self::from_ranges(self.ranges().intersection(&other.ranges()))
+    }
+
+    /// All UIDs of `self` not contained in `other` (or `None` if empty).
+    pub fn difference(&self, other: &UidSet) -> Option<UidSet> {
+        Self::from_ranges(self.ranges().difference(&other.ranges()))
+    }
+
+    /// Returns whether all UIDs of `self` are contained in `other`.
+    pub fn is_subset(&self, other: &UidSet) -> bool {
+        self.ranges().difference(&other.ranges()).is_empty()
+    }
+
+    /// Returns whether `uid` is contained in the set.
+    pub fn contains(&self, uid: NonZeroU32) -> bool {
+        self.ranges().contains(u64::from(uid.get()))
+    }
+
+    /// Returns the number of distinct UIDs in the set.
+    pub fn count(&self) -> u64 {
+        self.ranges().count()
+    }
+
+    /// Compact the set and split it into sets whose encoded length (in bytes) is at most
+    /// `max_length`.
+    ///
+    /// See [`SequenceSet::chunks`](crate::sequence::SequenceSet::chunks).
+    pub fn chunks(&self, max_length: usize) -> Vec<UidSet> {
+        self.ranges()
+            .chunks(max_length)
+            .into_iter()
+            .map(|ranges| Self::from_ranges(ranges).unwrap())
+            .collect()
+    }
+
+    fn ranges(&self) -> Ranges {
+        Ranges::new(self.0.as_ref().iter().map(|element| match element {
+            UidElement::Single(uid) => (u64::from(uid.get()), u64::from(uid.get())),
+            UidElement::Range(from, to) => (u64::from(from.get()), u64::from(to.get())),
+        }))
+    }
+
+    fn from_ranges(ranges: Ranges) -> Option<UidSet> {
+        // There is no `*` in a `UidSet`. Thus, all values fit into a `NonZeroU32`.
+        let uid = |value: u64| NonZeroU32::new(u32::try_from(value).unwrap()).unwrap();
+
+        let elements = ranges
+            .iter()
+            .map(|(from, to)| {
+                if from == to {
+                    UidElement::Single(uid(from))
+                } else {
+                    UidElement::Range(uid(from), uid(to))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Vec1::try_from(elements).ok().map(UidSet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uid_set(elements: &[(u32, u32)]) -> UidSet {
+        let uid = |value| NonZeroU32::new(value).unwrap();
+
+        UidSet(
+            Vec1::try_from(
+                elements
+                    .iter()
+                    .map(|&(from, to)| {
+                        if from == to {
+                            UidElement::Single(uid(from))
+                        } else {
+                            UidElement::Range(uid(from), uid(to))
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_uid_set_algebra() {
+        let a = uid_set(&[(20, 10), (1, 1), (2, 2), (u32::MAX, 21)]);
+        let b = uid_set(&[(5, 15), (100, 200)]);
+
+        assert_eq!(a.compact(), uid_set(&[(1, 2), (10, u32::MAX)]));
+        assert_eq!(a.count(), 2 + u64::from(u32::MAX - 9));
+        assert!(a.contains(NonZeroU32::new(42).unwrap()));
+        assert!(!a.contains(NonZeroU32::new(3).unwrap()));
+
+        assert_eq!(a.union(&b), uid_set(&[(1, 2), (5, u32::MAX)]));
+        assert_eq!(a.intersection(&b), Some(uid_set(&[(10, 15), (100, 200)])));
+        assert_eq!(
+            a.difference(&b),
+            Some(uid_set(&[(1, 2), (16, 99), (201, u32::MAX)]))
+        );
+        assert_eq!(b.difference(&a), Some(uid_set(&[(5, 9)])));
+        assert!(!b.is_subset(&a));
+        assert!(uid_set(&[(12, 14)]).is_subset(&b));
+        assert_eq!(uid_set(&[(3, 4)]).intersection(&b), None);
+    }
+
+    #[test]
+    fn test_uid_set_chunks() {
+        let set = uid_set(&[(1, 1), (3, 3), (5, 5), (100, 200)]);
+
+        assert_eq!(
+            set.chunks(5),
+            vec![uid_set(&[(1, 1), (3, 3), (5, 5)]), uid_set(&[(100, 200)])]
+        );
+    }
+}
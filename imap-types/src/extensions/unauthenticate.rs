@@ -0,0 +1,12 @@
+//! The IMAP UNAUTHENTICATE command
+
+use crate::command::CommandBody;
+
+impl CommandBody<'_> {
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the UNAUTHENTICATE capability.
+    /// </div>
+    pub fn unauthenticate() -> Self {
+        CommandBody::Unauthenticate
+    }
+}
@@ -11,11 +11,18 @@ use bounded_static_derive::ToStatic;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::GmailLabel;
 use crate::{
     body::BodyStructure,
     core::{AString, NString, NString8, Vec1},
     datetime::DateTime,
     envelope::Envelope,
+    error::{ValidationError, ValidationErrorKind},
+    extensions::{
+        convert::Conversion,
+        tagged_ext::{TaggedExt, TaggedExtItem},
+    },
     flag::FlagFetch,
 };
 
@@ -235,6 +242,65 @@ pub enum MessageDataItemName<'a> {
     BinarySize {
         section: Vec<NonZeroU32>,
     },
+
+    /// The (binary) content of a section converted to another media type (RFC 5259).
+    ///
+    /// ```imap
+    /// BINARY[<section-binary> (<conversion>)]<<partial>>
+    /// ```
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the CONVERT capability.
+    /// </div>
+    BinaryConvert {
+        section: Vec<NonZeroU32>,
+        conversion: Conversion<'a>,
+        partial: Option<(u32, NonZeroU32)>,
+        peek: bool,
+    },
+
+    /// A server-generated, brief text abstract of a message (RFC 8970).
+    ///
+    /// ```imap
+    /// PREVIEW [(LAZY)]
+    /// ```
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the PREVIEW capability.
+    /// </div>
+    Preview {
+        /// Use the `LAZY` modifier.
+        ///
+        /// The server may return `NIL` instead of generating a preview that is not yet available.
+        lazy: bool,
+    },
+
+    /// The Gmail message ID, a unique (64-bit) identifier of a message across all mailboxes.
+    ///
+    /// ```imap
+    /// X-GM-MSGID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailMessageId,
+
+    /// The Gmail thread ID, a unique (64-bit) identifier of a conversation.
+    ///
+    /// ```imap
+    /// X-GM-THRID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailThreadId,
+
+    /// The Gmail labels of a message.
+    ///
+    /// ```imap
+    /// X-GM-LABELS
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailLabels,
 }
 
 /// Message data item.
@@ -371,6 +437,123 @@ pub enum MessageDataItem<'a> {
         section: Vec<NonZeroU32>,
         size: u32,
     },
+
+    /// The (binary) content of a section converted to another media type (RFC 5259).
+    ///
+    /// ```imap
+    /// BINARY[<section-binary> (<conversion>)]
+    /// ```
+    BinaryConvert {
+        section: Vec<NonZeroU32>,
+        conversion: Conversion<'a>,
+        value: NString8<'a>,
+    },
+
+    /// A server-generated, brief text abstract of a message (RFC 8970).
+    ///
+    /// `NIL` is returned when no preview is available (yet), e.g., when `PREVIEW (LAZY)` was
+    /// requested.
+    ///
+    /// ```imap
+    /// PREVIEW
+    /// ```
+    Preview(NString<'a>),
+
+    /// The Gmail message ID.
+    ///
+    /// ```imap
+    /// X-GM-MSGID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailMessageId(u64),
+
+    /// The Gmail thread ID.
+    ///
+    /// ```imap
+    /// X-GM-THRID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailThreadId(u64),
+
+    /// The Gmail labels of a message.
+    ///
+    /// ```imap
+    /// X-GM-LABELS
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailLabels(Vec<GmailLabel<'a>>),
+
+    /// A message data item of an unknown extension (RFC 4466).
+    ///
+    /// ```imap
+    /// <label> <value>
+    /// ```
+    Other(MessageDataItemOther<'a>),
+}
+
+/// A message data item of an unknown extension.
+///
+/// It's guaranteed that this type can't represent any message data item from [`MessageDataItem`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TaggedExtItem<'a>"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct MessageDataItemOther<'a>(pub(crate) TaggedExtItem<'a>);
+
+impl<'a> MessageDataItemOther<'a> {
+    pub fn validate(value: &TaggedExtItem) -> Result<(), ValidationError> {
+        // Labels of message data items known to `MessageDataItem`.
+        match value.label.inner().to_ascii_uppercase().as_str() {
+            "BINARY" | "BINARY.SIZE" | "BODY" | "BODYSTRUCTURE" | "ENVELOPE" | "FLAGS"
+            | "INTERNALDATE" | "PREVIEW" | "RFC822" | "RFC822.HEADER" | "RFC822.SIZE"
+            | "RFC822.TEXT" | "UID" => Err(ValidationError::new(ValidationErrorKind::Invalid)),
+            #[cfg(feature = "ext_gmail")]
+            "X-GM-LABELS" | "X-GM-MSGID" | "X-GM-THRID" => {
+                Err(ValidationError::new(ValidationErrorKind::Invalid))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn inner(&self) -> &TaggedExtItem<'a> {
+        &self.0
+    }
+
+    /// Constructs an unknown message data item without validation.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `value` is valid according to [`Self::validate`]. Failing to do
+    /// so may create invalid/unparsable IMAP messages, or even produce unintended protocol flows.
+    /// Do not call this constructor with untrusted data.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated(value: TaggedExtItem<'a>) -> Self {
+        #[cfg(debug_assertions)]
+        Self::validate(&value).unwrap();
+
+        Self(value)
+    }
+}
+
+impl<'a> TryFrom<TaggedExtItem<'a>> for MessageDataItemOther<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: TaggedExtItem<'a>) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+
+        Ok(Self(value))
+    }
+}
+
+impl<'a> TryFrom<TaggedExt<'a>> for MessageDataItemOther<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: TaggedExt<'a>) -> Result<Self, Self::Error> {
+        Self::try_from(TaggedExtItem::from(value))
+    }
 }
 
 /// A part specifier is either a part number or one of the following:
@@ -112,19 +112,30 @@
 //! | IMAP4 UIDPLUS extension ([RFC 2359], [RFC 4315])                                                        |
 //! | IMAP4 Binary Content Extension ([RFC 3516])                                                             |
 //! | Internet Message Access Protocol - SORT and THREAD Extensions ([RFC 5256], [RFC 5957])                  |
+//! | IMAP REPLACE Extension ([RFC 8508])                                                                     |
+//! | IMAP Extension for Returning a Message Preview ([RFC 8970])                                             |
+//! | IMAP UNAUTHENTICATE Extension for Connection Reuse ([RFC 8437])                                         |
+//! | IMAP CONVERT Extension ([RFC 5259])                                                                     |
+//! | IMAP Extension for Using and Returning Unique Identifiers (UIDs) Only ([RFC 9586])                      |
+//! | IMAP MESSAGELIMIT Extension ([RFC 9738])                                                                |
+//! | IMAP4 Multimailbox SEARCH Extension ([RFC 7377])                                                        |
+//! | IMAP4 Extension for Fuzzy Search ([RFC 6203])                                                           |
+//! | Collected Extensions to IMAP4 ABNF ([RFC 4466]) and IMAP4 LIST Command Extensions ([RFC 5258])          |
 //!
 //! # Features
 //!
 //! This crate uses the following features to enable experimental IMAP extensions:
 //!
-//! | Feature               | Description                                                                                                                  | Status     |
-//! |-----------------------|------------------------------------------------------------------------------------------------------------------------------|------------|
-//! | starttls              | IMAP4rev1 ([RFC 3501]; section 6.2.1)                                                                                        |            |
-//! | ext_condstore_qresync | IMAP Extensions: Quick Flag Changes Resynchronization (CONDSTORE) and Quick Mailbox Resynchronization (QRESYNC) ([RFC 7162]) | Unfinished |
-//! | ext_id                | IMAP4 ID extension ([RFC 2971])                                                                                              | Unfinished |
-//! | ext_login_referrals   | IMAP4 Login Referrals ([RFC 2221])                                                                                           | Unfinished |
-//! | ext_mailbox_referrals | IMAP4 Mailbox Referrals ([RFC 2193])                                                                                         | Unfinished |
-//! | ext_metadata          | The IMAP METADATA Extension ([RFC 5464])                                                                                     | Unfinished |
+//! | Feature                  | Description                                                                                                                  | Status     |
+//! |--------------------------|------------------------------------------------------------------------------------------------------------------------------|------------|
+//! | starttls                 | IMAP4rev1 ([RFC 3501]; section 6.2.1)                                                                                        |            |
+//! | ext_condstore_qresync    | IMAP Extensions: Quick Flag Changes Resynchronization (CONDSTORE) and Quick Mailbox Resynchronization (QRESYNC) ([RFC 7162]) | Unfinished |
+//! | ext_gmail                | Gmail IMAP Extensions ([X-GM-EXT-1])                                                                                         | Unfinished |
+//! | ext_id                   | IMAP4 ID extension ([RFC 2971])                                                                                              | Unfinished |
+//! | ext_login_referrals      | IMAP4 Login Referrals ([RFC 2221])                                                                                           | Unfinished |
+//! | ext_mailbox_referrals    | IMAP4 Mailbox Referrals ([RFC 2193])                                                                                         | Unfinished |
+//! | ext_metadata             | The IMAP METADATA Extension ([RFC 5464]) and IMAP Events in Sieve ([RFC 6785])                                               | Unfinished |
+//! | ext_sort_thread_evaluate | Evaluation of SORT and THREAD ([RFC 5256]) against messages                                                                  |            |
 //!
 //! STARTTLS is not an IMAP extension but feature-gated because it [should be avoided](https://nostarttls.secvuln.info/).
 //! For better performance and security, use "implicit TLS", i.e., IMAP-over-TLS on port 993, and don't use STARTTLS at all.
@@ -134,6 +145,7 @@
 //! | Feature          | Description                                                   | Enabled by default |
 //! |------------------|---------------------------------------------------------------|--------------------|
 //! | arbitrary        | Derive `Arbitrary` implementations                            | No                 |
+//! | sasl             | Provide SASL mechanism state machines                         | No                 |
 //! | serde            | Derive `serde`s `Serialize` and `Deserialize` implementations | No                 |
 //! | tag_generator    | Provide a generator for randomized `Tag`s                     | No                 |
 //!
@@ -142,6 +154,7 @@
 //! (See, e.g., `imap-types/fuzz/fuzz_targets/to_static.rs`)
 //! When the `serde` feature is used, all types implement [Serde](https://serde.rs/)'s [Serialize](https://docs.serde.rs/serde/trait.Serialize.html) and
 //! [Deserialize](https://docs.serde.rs/serde/trait.Deserialize.html) traits. (Try running `cargo run --example serde_json`.)
+//! Using `sasl` unlocks the [`sasl`] module implementing common SASL mechanisms, e.g., SCRAM.
 //! Using `tag_generator` unlocks a `TagGenerator` to generate random tags.
 //! This may help to prevent attacks that depend on the knowledge of the next tag.
 //!
@@ -157,16 +170,28 @@
 //! [RFC 3516]: https://datatracker.ietf.org/doc/html/rfc3516
 //! [RFC 3691]: https://datatracker.ietf.org/doc/html/rfc3691
 //! [RFC 4315]: https://datatracker.ietf.org/doc/html/rfc4315
+//! [RFC 4466]: https://datatracker.ietf.org/doc/html/rfc4466
 //! [RFC 4959]: https://datatracker.ietf.org/doc/html/rfc4959
 //! [RFC 4978]: https://datatracker.ietf.org/doc/html/rfc4978
 //! [RFC 5161]: https://datatracker.ietf.org/doc/html/rfc5161
 //! [RFC 5256]: https://datatracker.ietf.org/doc/html/rfc5256
+//! [RFC 5258]: https://datatracker.ietf.org/doc/html/rfc5258
+//! [RFC 5259]: https://datatracker.ietf.org/doc/html/rfc5259
 //! [RFC 5464]: https://datatracker.ietf.org/doc/html/rfc5464
 //! [RFC 5957]: https://datatracker.ietf.org/doc/html/rfc5957
+//! [RFC 6203]: https://datatracker.ietf.org/doc/html/rfc6203
+//! [RFC 6785]: https://datatracker.ietf.org/doc/html/rfc6785
 //! [RFC 6851]: https://datatracker.ietf.org/doc/html/rfc6851
 //! [RFC 7162]: https://datatracker.ietf.org/doc/html/rfc7162
+//! [RFC 7377]: https://datatracker.ietf.org/doc/html/rfc7377
 //! [RFC 7888]: https://datatracker.ietf.org/doc/html/rfc7888
+//! [RFC 8437]: https://datatracker.ietf.org/doc/html/rfc8437
+//! [RFC 8508]: https://datatracker.ietf.org/doc/html/rfc8508
+//! [RFC 8970]: https://datatracker.ietf.org/doc/html/rfc8970
 //! [RFC 9208]: https://datatracker.ietf.org/doc/html/rfc9208
+//! [RFC 9586]: https://datatracker.ietf.org/doc/html/rfc9586
+//! [RFC 9738]: https://datatracker.ietf.org/doc/html/rfc9738
+//! [X-GM-EXT-1]: https://developers.google.com/gmail/imap/imap-extensions
 
 #![forbid(unsafe_code)]
 #![deny(missing_debug_implementations)]
@@ -195,8 +220,12 @@ pub mod fetch;
 pub mod flag;
 pub mod mailbox;
 pub mod response;
+#[cfg(feature = "sasl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sasl")))]
+pub mod sasl;
 pub mod search;
 pub mod secret;
+pub mod selected;
 pub mod sequence;
 pub mod state;
 pub mod status;
@@ -24,8 +24,10 @@ use crate::{
     extensions::{
         compress::CompressionAlgorithm,
         enable::CapabilityEnable,
+        esearch::{SearchCorrelator, SearchReturnData},
         quota::{QuotaGet, Resource},
         sort::SortAlgorithm,
+        tagged_ext::ListExtendedItem,
         thread::{Thread, ThreadingAlgorithm},
         uidplus::UidSet,
     },
@@ -383,6 +385,8 @@ pub enum Data<'a> {
         delimiter: Option<QuotedChar>,
         /// Name
         mailbox: Mailbox<'a>,
+        /// Extended data items, e.g., `CHILDINFO` (RFC 5258)
+        extended_items: Vec<ListExtendedItem<'a>>,
     },
 
     /// ### 7.2.3. LSUB Response
@@ -427,6 +431,19 @@ pub enum Data<'a> {
 
     Thread(Vec<Thread>),
 
+    /// ESEARCH response (RFC 4731, RFC 7377).
+    ///
+    /// Returns the result of an ESEARCH command. With MULTISEARCH, one response is sent per
+    /// searched mailbox and the mailbox is identified via the correlator.
+    ESearch {
+        /// Search correlator.
+        correlator: Option<SearchCorrelator<'a>>,
+        /// Whether the returned numbers are UIDs.
+        uid: bool,
+        /// Search return data.
+        data: Vec<SearchReturnData>,
+    },
+
     /// ### 7.2.6.  FLAGS Response
     ///
     /// * Contents: flag parenthesized list
@@ -555,6 +572,29 @@ pub enum Data<'a> {
         roots: Vec<AString<'a>>,
     },
 
+    /// UIDFETCH response (RFC 9586).
+    ///
+    /// Replaces the FETCH response when UIDONLY is enabled. Messages are identified by their UID
+    /// instead of their sequence number.
+    UidFetch {
+        /// Unique identifier.
+        uid: NonZeroU32,
+        /// Message data items.
+        items: Vec1<MessageDataItem<'a>>,
+    },
+
+    /// CONVERTED response (RFC 5259).
+    ///
+    /// Returns the converted message data items requested by a CONVERT command.
+    Converted {
+        /// Sequence number.
+        seq: NonZeroU32,
+        /// Tag of the CONVERT command this response belongs to.
+        tag: Tag<'a>,
+        /// Message data items.
+        items: Vec1<MessageDataItem<'a>>,
+    },
+
     #[cfg(feature = "ext_id")]
     /// ID Response
     Id {
@@ -876,6 +916,117 @@ pub enum Code<'a> {
 
     UidNotSticky,
 
+    /// Conversion parameters are invalid or not supported (RFC 5259).
+    BadParams,
+
+    /// The requested conversion is not supported for (some of) the message(s) (RFC 5259).
+    NoConvert,
+
+    /// Maximum number of messages that can be converted by a single CONVERT command (RFC 5259).
+    MaxConvertMessages(NonZeroU32),
+
+    /// Maximum number of body parts that can be converted by a single CONVERT command (RFC 5259).
+    MaxConvertParts(NonZeroU32),
+
+    /// `UNAVAILABLE` (RFC 5530)
+    ///
+    /// Temporary failure because a subsystem is down.
+    Unavailable,
+
+    /// `AUTHENTICATIONFAILED` (RFC 5530)
+    ///
+    /// Authentication failed for some reason on which the server is unwilling to elaborate.
+    AuthenticationFailed,
+
+    /// `AUTHORIZATIONFAILED` (RFC 5530)
+    ///
+    /// Authentication succeeded in using the authentication identity, but the server cannot or will
+    /// not allow the authentication identity to act as the requested authorization identity.
+    AuthorizationFailed,
+
+    /// `EXPIRED` (RFC 5530)
+    ///
+    /// Either authentication succeeded or the server no longer had the necessary data; either way,
+    /// access is no longer permitted using that passphrase.
+    Expired,
+
+    /// `PRIVACYREQUIRED` (RFC 5530)
+    ///
+    /// The operation is not permitted due to a lack of privacy, e.g., because TLS is not in use.
+    PrivacyRequired,
+
+    /// `CONTACTADMIN` (RFC 5530)
+    ///
+    /// The user should contact the system administrator or support desk.
+    ContactAdmin,
+
+    /// `NOPERM` (RFC 5530)
+    ///
+    /// The access control system (e.g., ACL) does not permit this user to carry out an operation.
+    NoPerm,
+
+    /// `INUSE` (RFC 5530)
+    ///
+    /// An operation has not been carried out because it involves sawing off a branch someone else
+    /// is sitting on, e.g., the mailbox is locked by another session.
+    InUse,
+
+    /// `EXPUNGEISSUED` (RFC 5530)
+    ///
+    /// Someone else has issued an EXPUNGE for the same mailbox.
+    ExpungeIssued,
+
+    /// `CORRUPTION` (RFC 5530)
+    ///
+    /// The server discovered that some relevant data (e.g., the mailbox) are corrupt.
+    Corruption,
+
+    /// `SERVERBUG` (RFC 5530)
+    ///
+    /// The server encountered a bug in itself or violated one of its own invariants.
+    ServerBug,
+
+    /// `CLIENTBUG` (RFC 5530)
+    ///
+    /// The server has detected a client bug.
+    ClientBug,
+
+    /// `CANNOT` (RFC 5530)
+    ///
+    /// The operation violates some invariant of the server and can never succeed.
+    Cannot,
+
+    /// `LIMIT` (RFC 5530, RFC 9738)
+    ///
+    /// The operation ran up against an implementation limit of some kind.
+    ///
+    /// With MESSAGELIMIT (RFC 9738), a UID command (e.g., `UID FETCH`, `UID STORE`, or `UID SEARCH`)
+    /// touching more messages than allowed is partially processed, lowest UIDs first, and completed
+    /// with `OK [LIMIT]`. The client is expected to re-issue the command for the remaining UIDs,
+    /// see [`SequenceSet::limit_follow_up`](crate::sequence::SequenceSet::limit_follow_up).
+    /// The same command using message sequence numbers fails with `NO [LIMIT]` instead.
+    Limit,
+
+    /// `ALREADYEXISTS` (RFC 5530)
+    ///
+    /// The operation attempts to create something that already exists.
+    AlreadyExists,
+
+    /// `NONEXISTENT` (RFC 5530)
+    ///
+    /// The operation attempts to delete something that does not exist.
+    NonExistent,
+
+    /// `UIDREQUIRED` (RFC 9586)
+    ///
+    /// The command uses message sequence numbers but UIDONLY is enabled.
+    UidRequired,
+
+    /// `INPROGRESS` (RFC 9585)
+    ///
+    /// A command is still in progress. Sent in untagged OK responses during long-running commands.
+    InProgress(Option<Progress<'a>>),
+
     /// Additional response codes defined by particular client or server
     /// implementations SHOULD be prefixed with an "X" until they are
     /// added to a revision of this protocol.  Client implementations
@@ -893,6 +1044,37 @@ pub enum Code<'a> {
 }
 
 impl<'a> Code<'a> {
+    /// Classify a failure response code as transient or permanent.
+    ///
+    /// Transient failures may succeed when the command is retried later (without changes). Permanent
+    /// failures won't, e.g., because the client needs to change the request or the user has to
+    /// take action first.
+    ///
+    /// Returns `None` for codes that don't describe a failure or whose nature is unknown. This
+    /// includes `INPROGRESS`, which reports the progress of a running command (in an untagged
+    /// `OK`) and is not a failure.
+    pub fn failure_kind(&self) -> Option<FailureKind> {
+        match self {
+            Self::Unavailable | Self::InUse | Self::ExpungeIssued => Some(FailureKind::Transient),
+            Self::AuthenticationFailed
+            | Self::AuthorizationFailed
+            | Self::Expired
+            | Self::PrivacyRequired
+            | Self::ContactAdmin
+            | Self::NoPerm
+            | Self::Corruption
+            | Self::ServerBug
+            | Self::ClientBug
+            | Self::Cannot
+            | Self::Limit
+            | Self::AlreadyExists
+            | Self::NonExistent
+            | Self::UidRequired
+            | Self::OverQuota => Some(FailureKind::Permanent),
+            _ => None,
+        }
+    }
+
     pub fn badcharset(allowed: Vec<Charset<'a>>) -> Self {
         Self::BadCharset { allowed }
     }
@@ -921,6 +1103,34 @@ impl<'a> Code<'a> {
     }
 }
 
+/// Progress information of a long-running command (RFC 9585).
+///
+/// ```imap
+/// INPROGRESS ("A1" 17 42)
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct Progress<'a> {
+    /// Tag of the command in progress (`NIL` when unknown).
+    pub tag: Option<Tag<'a>>,
+    /// Amount of work done so far (`NIL` when unknown).
+    pub done: Option<u32>,
+    /// Total amount of work (`NIL` when unknown).
+    pub goal: Option<u32>,
+}
+
+/// Classification of a failure, see [`Code::failure_kind`].
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
+pub enum FailureKind {
+    /// Retrying the same command later may succeed.
+    Transient,
+    /// Retrying the same command won't succeed.
+    Permanent,
+}
+
 /// An (unknown) code.
 ///
 /// It's guaranteed that this type can't represent any code from [`Code`].
@@ -1004,6 +1214,32 @@ pub enum Capability<'a> {
     LiteralMinus,
     /// See RFC 6851.
     Move,
+    /// See RFC 8508.
+    Replace,
+    /// See RFC 8970.
+    Preview,
+    /// See RFC 8437.
+    Unauthenticate,
+    /// See RFC 5259.
+    Convert,
+    /// See RFC 9586.
+    UidOnly,
+    /// See RFC 7377.
+    MultiSearch,
+    /// See RFC 6203.
+    SearchFuzzy,
+    /// See RFC 9738.
+    ///
+    /// Maximum number of messages the server processes in a single command.
+    MessageLimit(NonZeroU32),
+    /// See RFC 9738.
+    ///
+    /// Maximum number of messages the server saves for `SEARCH RETURN (SAVE)`.
+    SaveLimit(NonZeroU32),
+    /// Gmail IMAP Extensions (`X-GM-EXT-1`).
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailExt1,
     #[cfg(feature = "ext_id")]
     /// See RFC 2971.
     Id,
@@ -1017,10 +1253,19 @@ pub enum Capability<'a> {
     #[cfg(feature = "ext_metadata")]
     /// Server supports (only) server annotations.
     MetadataServer,
+    #[cfg(feature = "ext_metadata")]
+    /// See RFC 6785.
+    ///
+    /// Contains the URL of the ManageSieve server used to manage the scripts.
+    ImapSieve(Atom<'a>),
     /// IMAP4 Binary Content Extension
     Binary,
     /// UIDPLUS extension (RFC 4351)
     UidPlus,
+    /// See RFC 7162.
+    CondStore,
+    /// See RFC 5258.
+    ListExtended,
     /// Other/Unknown
     Other(CapabilityOther<'a>),
 }
@@ -1047,6 +1292,17 @@ impl<'a> Display for Capability<'a> {
             Self::LiteralPlus => write!(f, "LITERAL+"),
             Self::LiteralMinus => write!(f, "LITERAL-"),
             Self::Move => write!(f, "MOVE"),
+            Self::Replace => write!(f, "REPLACE"),
+            Self::Preview => write!(f, "PREVIEW"),
+            Self::Unauthenticate => write!(f, "UNAUTHENTICATE"),
+            Self::Convert => write!(f, "CONVERT"),
+            Self::UidOnly => write!(f, "UIDONLY"),
+            Self::MultiSearch => write!(f, "MULTISEARCH"),
+            Self::SearchFuzzy => write!(f, "SEARCH=FUZZY"),
+            Self::MessageLimit(limit) => write!(f, "MESSAGELIMIT={}", limit),
+            Self::SaveLimit(limit) => write!(f, "SAVELIMIT={}", limit),
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailExt1 => write!(f, "X-GM-EXT-1"),
             #[cfg(feature = "ext_id")]
             Self::Id => write!(f, "ID"),
             Self::Unselect => write!(f, "UNSELECT"),
@@ -1057,8 +1313,12 @@ impl<'a> Display for Capability<'a> {
             Self::Metadata => write!(f, "METADATA"),
             #[cfg(feature = "ext_metadata")]
             Self::MetadataServer => write!(f, "METADATA-SERVER"),
+            #[cfg(feature = "ext_metadata")]
+            Self::ImapSieve(url) => write!(f, "IMAPSIEVE={}", url),
             Self::Binary => write!(f, "BINARY"),
             Self::UidPlus => write!(f, "UIDPLUS"),
+            Self::CondStore => write!(f, "CONDSTORE"),
+            Self::ListExtended => write!(f, "LIST-EXTENDED"),
             Self::Other(other) => write!(f, "{}", other.0),
         }
     }
@@ -1094,6 +1354,15 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
             }
         }
 
+        // Only accept the canonical form (no sign, no leading zeros) so that `Display` round-trips.
+        fn parse_limit(value: &str) -> Option<NonZeroU32> {
+            if value.starts_with('0') || !value.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+
+            value.parse().ok()
+        }
+
         let cow = atom.into_inner();
 
         match cow.to_ascii_lowercase().as_ref() {
@@ -1113,6 +1382,14 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
             "literal+" => Self::LiteralPlus,
             "literal-" => Self::LiteralMinus,
             "move" => Self::Move,
+            "replace" => Self::Replace,
+            "preview" => Self::Preview,
+            "unauthenticate" => Self::Unauthenticate,
+            "convert" => Self::Convert,
+            "uidonly" => Self::UidOnly,
+            "multisearch" => Self::MultiSearch,
+            #[cfg(feature = "ext_gmail")]
+            "x-gm-ext-1" => Self::GmailExt1,
             #[cfg(feature = "ext_id")]
             "id" => Self::Id,
             "sort" => Self::Sort(None),
@@ -1123,6 +1400,8 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
             "binary" => Self::Binary,
             "unselect" => Self::Unselect,
             "uidplus" => Self::UidPlus,
+            "condstore" => Self::CondStore,
+            "list-extended" => Self::ListExtended,
             _ => {
                 // TODO(efficiency)
                 if let Some((left, right)) = split_once_cow(cow.clone(), "=") {
@@ -1159,6 +1438,27 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
                                 return Self::Thread(ThreadingAlgorithm::from(atom));
                             }
                         }
+                        "search" => {
+                            if right.eq_ignore_ascii_case("fuzzy") {
+                                return Self::SearchFuzzy;
+                            }
+                        }
+                        "messagelimit" => {
+                            if let Some(limit) = parse_limit(right.as_ref()) {
+                                return Self::MessageLimit(limit);
+                            }
+                        }
+                        "savelimit" => {
+                            if let Some(limit) = parse_limit(right.as_ref()) {
+                                return Self::SaveLimit(limit);
+                            }
+                        }
+                        #[cfg(feature = "ext_metadata")]
+                        "imapsieve" => {
+                            if let Ok(atom) = Atom::try_from(right) {
+                                return Self::ImapSieve(atom);
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -0,0 +1,94 @@
+//! SASL mechanisms.
+//!
+//! IMAP authenticates via `AUTHENTICATE` using SASL mechanisms (see [`AuthMechanism`]).
+//! This module implements the (sans-IO) mechanism state machines on top of
+//! [`CommandBody::Authenticate`](crate::command::CommandBody::Authenticate),
+//! [`CommandContinuationRequest`](crate::response::CommandContinuationRequest), and
+//! [`AuthenticateData`](crate::auth::AuthenticateData).
+//!
+//! * [`client`]: PLAIN, LOGIN, XOAUTH2, OAUTHBEARER, and SCRAM-SHA-1/256(-PLUS).
+//! * [`server`]: The same mechanisms for servers. Credentials are obtained through
+//!   [`CredentialLookup`](server::CredentialLookup).
+//!
+//! Channel binding data (for `-PLUS` mechanisms) must be obtained from the TLS library and is
+//! passed in via [`ChannelBinding`].
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::auth::AuthMechanism;
+
+pub mod client;
+mod scram;
+pub mod server;
+
+/// Hash function used by SCRAM.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
+pub enum ScramHash {
+    /// SHA-1 (RFC 5802)
+    Sha1,
+    /// SHA-256 (RFC 7677)
+    Sha256,
+}
+
+impl ScramHash {
+    /// Returns the SCRAM mechanism using this hash function.
+    pub fn mechanism(&self, plus: bool) -> AuthMechanism<'static> {
+        match (self, plus) {
+            (Self::Sha1, false) => AuthMechanism::ScramSha1,
+            (Self::Sha1, true) => AuthMechanism::ScramSha1Plus,
+            (Self::Sha256, false) => AuthMechanism::ScramSha256,
+            (Self::Sha256, true) => AuthMechanism::ScramSha256Plus,
+        }
+    }
+}
+
+/// Channel binding used by SCRAM.
+///
+/// The channel binding data is obtained from the TLS library.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum ChannelBinding {
+    /// Channel binding isn't supported (`n`).
+    None,
+    /// Channel binding is supported but wasn't offered by the server (`y`).
+    ///
+    /// Use this when a TLS channel binding would be available but the server didn't advertise a
+    /// `-PLUS` mechanism. This allows the server to detect a downgrade.
+    NotOffered,
+    /// `tls-exporter` channel binding (RFC 9266).
+    TlsExporter(Vec<u8>),
+    /// `tls-server-end-point` channel binding (RFC 5929).
+    TlsServerEndPoint(Vec<u8>),
+}
+
+impl ChannelBinding {
+    /// Returns whether a `-PLUS` mechanism is used.
+    pub fn is_plus(&self) -> bool {
+        matches!(self, Self::TlsExporter(_) | Self::TlsServerEndPoint(_))
+    }
+
+    /// `gs2-cbind-flag`
+    pub(crate) fn gs2_cbind_flag(&self) -> &'static str {
+        match self {
+            Self::None => "n",
+            Self::NotOffered => "y",
+            Self::TlsExporter(_) => "p=tls-exporter",
+            Self::TlsServerEndPoint(_) => "p=tls-server-end-point",
+        }
+    }
+
+    /// `cbind-data`
+    pub(crate) fn data(&self) -> &[u8] {
+        match self {
+            Self::None | Self::NotOffered => &[],
+            Self::TlsExporter(data) | Self::TlsServerEndPoint(data) => data,
+        }
+    }
+}
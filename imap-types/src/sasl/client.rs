@@ -0,0 +1,820 @@
+//! Client-side SASL mechanisms.
+//!
+//! A [`SaslClient`] produces the `AUTHENTICATE` command (see [`SaslClient::authenticate`]),
+//! answers the server's challenges (see [`SaslClient::challenge`]), and checks that the
+//! authentication was completed correctly before a tagged `OK` is accepted
+//! (see [`SaslClient::finish`]).
+//!
+//! # Example
+//!
+//! ```rust
+//! use imap_types::{
+//!     auth::AuthenticateData,
+//!     response::CommandContinuationRequest,
+//!     sasl::client::SaslClient,
+//! };
+//!
+//! let mut client = SaslClient::plain(None, "alice", "password");
+//!
+//! // Without SASL-IR, the initial response is sent after the first (empty) challenge.
+//! let command_body = client.authenticate(false);
+//!
+//! // S: +
+//! let data = client
+//!     .challenge(&CommandContinuationRequest::base64(b"".as_ref()))
+//!     .unwrap();
+//! assert_eq!(data, AuthenticateData::r#continue(b"\x00alice\x00password".to_vec()));
+//!
+//! // S: A1 OK ...
+//! assert!(client.finish().is_ok());
+//! ```
+
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+use rand::distributions::{Alphanumeric, DistString};
+use thiserror::Error;
+
+use crate::{
+    auth::{AuthMechanism, AuthenticateData},
+    command::CommandBody,
+    response::CommandContinuationRequest,
+    sasl::{
+        scram::{attributes, h, hi, hmac, hmac_verify, is_valid_nonce, saslname_encode, xor},
+        ChannelBinding, ScramHash,
+    },
+    secret::Secret,
+};
+
+/// Default maximum SCRAM iteration count accepted from the server.
+///
+/// See [`SaslClient::set_max_iterations`].
+pub const DEFAULT_MAX_ITERATIONS: u32 = 1_000_000;
+
+/// Client-side SASL state machine.
+#[derive(Debug)]
+pub struct SaslClient {
+    mechanism: Mechanism,
+}
+
+#[derive(Debug)]
+enum Mechanism {
+    /// PLAIN, XOAUTH2, and OAUTHBEARER send a single (initial) response.
+    InitialResponse {
+        mechanism: AuthMechanism<'static>,
+        response: Secret<Vec<u8>>,
+        /// Response to an error challenge.
+        error_response: &'static [u8],
+        state: InitialResponseState,
+    },
+    Login {
+        username: Secret<Vec<u8>>,
+        password: Secret<Vec<u8>>,
+        state: LoginState,
+    },
+    Scram(Box<ScramClient>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InitialResponseState {
+    Start,
+    Sent,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoginState {
+    Username,
+    Password,
+    Done,
+}
+
+impl SaslClient {
+    /// PLAIN (RFC 4616)
+    ///
+    /// `authzid` is the authorization identity. Use `None` to act as `authcid`.
+    pub fn plain(authzid: Option<&str>, authcid: &str, password: &str) -> Self {
+        let response = format!("{}\x00{authcid}\x00{password}", authzid.unwrap_or_default());
+
+        Self::initial_response(AuthMechanism::Plain, response.into_bytes(), b"")
+    }
+
+    /// LOGIN (draft-murchison-sasl-login-00)
+    pub fn login(username: &str, password: &str) -> Self {
+        Self {
+            mechanism: Mechanism::Login {
+                username: Secret::new(username.as_bytes().to_vec()),
+                password: Secret::new(password.as_bytes().to_vec()),
+                state: LoginState::Username,
+            },
+        }
+    }
+
+    /// XOAUTH2
+    pub fn xoauth2(user: &str, token: &str) -> Self {
+        let response = format!("user={user}\x01auth=Bearer {token}\x01\x01");
+
+        // "the client sends an empty response" after an error.
+        Self::initial_response(AuthMechanism::XOAuth2, response.into_bytes(), b"")
+    }
+
+    /// OAUTHBEARER (RFC 7628)
+    pub fn oauthbearer(user: &str, host: Option<&str>, port: Option<u16>, token: &str) -> Self {
+        let mut response = format!("n,a={},\x01", saslname_encode(user));
+
+        if let Some(host) = host {
+            response.push_str(&format!("host={host}\x01"));
+        }
+
+        if let Some(port) = port {
+            response.push_str(&format!("port={port}\x01"));
+        }
+
+        response.push_str(&format!("auth=Bearer {token}\x01\x01"));
+
+        // "[...] the client MUST then send either an additional client response consisting of a
+        // single %x01 (control A) character [...]"
+        Self::initial_response(AuthMechanism::OAuthBearer, response.into_bytes(), b"\x01")
+    }
+
+    /// SCRAM-SHA-1(-PLUS) (RFC 5802) and SCRAM-SHA-256(-PLUS) (RFC 7677)
+    ///
+    /// The `-PLUS` variant is used when `channel_binding` contains channel binding data.
+    /// The password is prepared using SASLprep.
+    pub fn scram(
+        hash: ScramHash,
+        username: &str,
+        password: &str,
+        channel_binding: ChannelBinding,
+    ) -> Result<Self, SaslClientError> {
+        let nonce = Alphanumeric.sample_string(&mut rand::thread_rng(), 24);
+
+        Self::scram_with_nonce(hash, username, password, channel_binding, nonce)
+    }
+
+    pub(crate) fn scram_with_nonce(
+        hash: ScramHash,
+        username: &str,
+        password: &str,
+        channel_binding: ChannelBinding,
+        nonce: String,
+    ) -> Result<Self, SaslClientError> {
+        let password =
+            stringprep::saslprep(password).map_err(|_| SaslClientError::InvalidPassword)?;
+
+        let gs2_header = format!("{},,", channel_binding.gs2_cbind_flag());
+        let client_first_bare = format!("n={},r={nonce}", saslname_encode(username));
+
+        Ok(Self {
+            mechanism: Mechanism::Scram(Box::new(ScramClient {
+                hash,
+                password: Secret::new(password.into_owned()),
+                channel_binding,
+                gs2_header,
+                client_first_bare,
+                nonce,
+                max_iterations: DEFAULT_MAX_ITERATIONS,
+                state: ScramState::Start,
+            })),
+        })
+    }
+
+    fn initial_response(
+        mechanism: AuthMechanism<'static>,
+        response: Vec<u8>,
+        error_response: &'static [u8],
+    ) -> Self {
+        Self {
+            mechanism: Mechanism::InitialResponse {
+                mechanism,
+                response: Secret::new(response),
+                error_response,
+                state: InitialResponseState::Start,
+            },
+        }
+    }
+
+    /// Set the maximum SCRAM iteration count accepted from the server.
+    ///
+    /// The iteration count is chosen by the server and determines the cost of the key
+    /// derivation. A larger iteration count is rejected with [`SaslClientError::TooManyIterations`].
+    /// Defaults to [`DEFAULT_MAX_ITERATIONS`]. Has no effect for other mechanisms.
+    pub fn set_max_iterations(&mut self, max_iterations: u32) {
+        if let Mechanism::Scram(scram) = &mut self.mechanism {
+            scram.max_iterations = max_iterations;
+        }
+    }
+
+    /// Returns the SASL mechanism.
+    pub fn mechanism(&self) -> AuthMechanism<'static> {
+        match &self.mechanism {
+            Mechanism::InitialResponse { mechanism, .. } => mechanism.clone(),
+            Mechanism::Login { .. } => AuthMechanism::Login,
+            Mechanism::Scram(scram) => scram.hash.mechanism(scram.channel_binding.is_plus()),
+        }
+    }
+
+    /// Construct the `AUTHENTICATE` command.
+    ///
+    /// When `sasl_ir` is `true` (i.e., the server advertised `SASL-IR`), the initial response is
+    /// sent along with the command for mechanisms that start with a client response.
+    pub fn authenticate(&mut self, sasl_ir: bool) -> CommandBody<'static> {
+        let mechanism = self.mechanism();
+
+        if !sasl_ir {
+            return CommandBody::authenticate(mechanism);
+        }
+
+        match &mut self.mechanism {
+            Mechanism::InitialResponse {
+                response, state, ..
+            } => {
+                *state = InitialResponseState::Sent;
+
+                CommandBody::authenticate_with_ir(mechanism, response.declassify().clone())
+            }
+            Mechanism::Login { .. } => CommandBody::authenticate(mechanism),
+            Mechanism::Scram(scram) => {
+                CommandBody::authenticate_with_ir(mechanism, scram.client_first())
+            }
+        }
+    }
+
+    /// Answer a challenge (command continuation request) of the server.
+    ///
+    /// When an error is returned, the client should cancel the authentication
+    /// (see [`AuthenticateData::Cancel`]) and must not consider itself authenticated.
+    pub fn challenge(
+        &mut self,
+        request: &CommandContinuationRequest,
+    ) -> Result<AuthenticateData<'static>, SaslClientError> {
+        let challenge: &[u8] = match request {
+            CommandContinuationRequest::Base64(data) => data,
+            // Some servers send a human-readable text instead of an empty challenge.
+            CommandContinuationRequest::Basic(_) => b"",
+        };
+
+        match &mut self.mechanism {
+            Mechanism::InitialResponse {
+                response,
+                error_response,
+                state,
+                ..
+            } => match state {
+                InitialResponseState::Start => {
+                    *state = InitialResponseState::Sent;
+
+                    Ok(AuthenticateData::r#continue(response.declassify().clone()))
+                }
+                // The server reports an error (e.g., a JSON document for XOAUTH2 and OAUTHBEARER)
+                // and expects a (dummy) response before it sends the tagged `NO`.
+                InitialResponseState::Sent => {
+                    *state = InitialResponseState::Failed;
+
+                    Ok(AuthenticateData::r#continue(error_response.to_vec()))
+                }
+                InitialResponseState::Failed => Err(SaslClientError::UnexpectedChallenge),
+            },
+            Mechanism::Login {
+                username,
+                password,
+                state,
+            } => match state {
+                LoginState::Username => {
+                    *state = LoginState::Password;
+
+                    Ok(AuthenticateData::r#continue(username.declassify().clone()))
+                }
+                LoginState::Password => {
+                    *state = LoginState::Done;
+
+                    Ok(AuthenticateData::r#continue(password.declassify().clone()))
+                }
+                LoginState::Done => Err(SaslClientError::UnexpectedChallenge),
+            },
+            Mechanism::Scram(scram) => scram.challenge(challenge),
+        }
+    }
+
+    /// Check that the authentication was completed before accepting a tagged `OK`.
+    ///
+    /// For SCRAM, this ensures that the server proved its knowledge of the password. A tagged
+    /// `OK` without a (valid) server signature must be treated as a failed authentication.
+    pub fn finish(&self) -> Result<(), SaslClientError> {
+        let complete = match &self.mechanism {
+            Mechanism::InitialResponse { state, .. } => *state == InitialResponseState::Sent,
+            Mechanism::Login { state, .. } => *state == LoginState::Done,
+            Mechanism::Scram(scram) => scram.state == ScramState::Verified,
+        };
+
+        if complete {
+            Ok(())
+        } else {
+            Err(SaslClientError::Incomplete)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ScramClient {
+    hash: ScramHash,
+    password: Secret<String>,
+    channel_binding: ChannelBinding,
+    gs2_header: String,
+    client_first_bare: String,
+    nonce: String,
+    max_iterations: u32,
+    state: ScramState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ScramState {
+    Start,
+    ClientFirstSent,
+    ClientFinalSent {
+        server_key: Secret<Vec<u8>>,
+        auth_message: String,
+    },
+    Verified,
+    Failed,
+}
+
+impl ScramClient {
+    /// `client-first-message`
+    fn client_first(&mut self) -> Vec<u8> {
+        self.state = ScramState::ClientFirstSent;
+
+        format!("{}{}", self.gs2_header, self.client_first_bare).into_bytes()
+    }
+
+    fn challenge(
+        &mut self,
+        challenge: &[u8],
+    ) -> Result<AuthenticateData<'static>, SaslClientError> {
+        match std::mem::replace(&mut self.state, ScramState::Failed) {
+            ScramState::Start => {
+                // Without SASL-IR, the server sends an empty challenge first.
+                if !challenge.is_empty() {
+                    return Err(SaslClientError::UnexpectedChallenge);
+                }
+
+                Ok(AuthenticateData::r#continue(self.client_first()))
+            }
+            ScramState::ClientFirstSent => {
+                let server_first =
+                    std::str::from_utf8(challenge).map_err(|_| SaslClientError::Malformed)?;
+
+                let (client_final, server_key, auth_message) = self.client_final(server_first)?;
+
+                self.state = ScramState::ClientFinalSent {
+                    server_key: Secret::new(server_key),
+                    auth_message,
+                };
+
+                Ok(AuthenticateData::r#continue(client_final.into_bytes()))
+            }
+            ScramState::ClientFinalSent {
+                server_key,
+                auth_message,
+            } => {
+                let server_final =
+                    std::str::from_utf8(challenge).map_err(|_| SaslClientError::Malformed)?;
+
+                match attributes(server_final).as_deref() {
+                    Some([('v', signature), ..]) => {
+                        let signature = base64
+                            .decode(signature)
+                            .map_err(|_| SaslClientError::Malformed)?;
+
+                        if !hmac_verify(
+                            self.hash,
+                            server_key.declassify(),
+                            auth_message.as_bytes(),
+                            &signature,
+                        ) {
+                            return Err(SaslClientError::InvalidServerSignature);
+                        }
+
+                        self.state = ScramState::Verified;
+
+                        Ok(AuthenticateData::r#continue(Vec::new()))
+                    }
+                    Some([('e', error), ..]) => Err(SaslClientError::Server(error.to_string())),
+                    _ => Err(SaslClientError::Malformed),
+                }
+            }
+            ScramState::Verified | ScramState::Failed => Err(SaslClientError::UnexpectedChallenge),
+        }
+    }
+
+    /// Process the `server-first-message` and return the `client-final-message`, the `ServerKey`,
+    /// and the `AuthMessage`.
+    fn client_final(
+        &self,
+        server_first: &str,
+    ) -> Result<(String, Vec<u8>, String), SaslClientError> {
+        let (nonce, salt, iterations) = match attributes(server_first).as_deref() {
+            Some([('m', _), ..]) => return Err(SaslClientError::UnsupportedExtension),
+            Some([('r', nonce), ('s', salt), ('i', iterations), ..]) => {
+                (*nonce, *salt, *iterations)
+            }
+            _ => return Err(SaslClientError::Malformed),
+        };
+
+        // The server appends its part to our nonce.
+        if !is_valid_nonce(nonce)
+            || nonce.len() <= self.nonce.len()
+            || !nonce.starts_with(&self.nonce)
+        {
+            return Err(SaslClientError::InvalidNonce);
+        }
+
+        let salt = base64
+            .decode(salt)
+            .map_err(|_| SaslClientError::Malformed)?;
+
+        let iterations = match iterations.parse::<u32>() {
+            Ok(parsed) if parsed > 0 && !iterations.starts_with('0') => parsed,
+            _ => return Err(SaslClientError::Malformed),
+        };
+
+        if iterations > self.max_iterations {
+            return Err(SaslClientError::TooManyIterations(iterations));
+        }
+
+        let channel_binding = {
+            let mut input = self.gs2_header.as_bytes().to_vec();
+            input.extend_from_slice(self.channel_binding.data());
+            base64.encode(input)
+        };
+
+        let client_final_without_proof = format!("c={channel_binding},r={nonce}");
+        let auth_message = format!(
+            "{},{server_first},{client_final_without_proof}",
+            self.client_first_bare
+        );
+
+        let salted_password = hi(
+            self.hash,
+            self.password.declassify().as_bytes(),
+            &salt,
+            iterations,
+        );
+        let client_key = hmac(self.hash, &salted_password, b"Client Key");
+        let stored_key = h(self.hash, &client_key);
+        let client_signature = hmac(self.hash, &stored_key, auth_message.as_bytes());
+        let client_proof = xor(&client_key, &client_signature);
+        let server_key = hmac(self.hash, &salted_password, b"Server Key");
+
+        Ok((
+            format!(
+                "{client_final_without_proof},p={}",
+                base64.encode(client_proof)
+            ),
+            server_key,
+            auth_message,
+        ))
+    }
+}
+
+/// Error during client-side SASL authentication.
+#[derive(Clone, Debug, Eq, Error, Hash, PartialEq)]
+pub enum SaslClientError {
+    #[error("Unexpected challenge")]
+    UnexpectedChallenge,
+    #[error("Malformed challenge")]
+    Malformed,
+    #[error("Unsupported mandatory extension")]
+    UnsupportedExtension,
+    #[error("Invalid nonce")]
+    InvalidNonce,
+    #[error("Invalid server signature")]
+    InvalidServerSignature,
+    #[error("Server error: {0}")]
+    Server(String),
+    #[error("Too many iterations: {0}")]
+    TooManyIterations(u32),
+    #[error("Invalid password (SASLprep)")]
+    InvalidPassword,
+    #[error("Authentication is incomplete")]
+    Incomplete,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base64_challenge(data: &[u8]) -> CommandContinuationRequest<'static> {
+        CommandContinuationRequest::base64(data.to_vec())
+    }
+
+    fn continue_data(data: &[u8]) -> AuthenticateData<'static> {
+        AuthenticateData::r#continue(data.to_vec())
+    }
+
+    #[test]
+    fn test_plain() {
+        // SASL-IR
+        let mut client = SaslClient::plain(Some("admin"), "alice", "password");
+        assert_eq!(
+            client.authenticate(true),
+            CommandBody::authenticate_with_ir(
+                AuthMechanism::Plain,
+                b"admin\x00alice\x00password".as_ref()
+            )
+        );
+        assert!(client.finish().is_ok());
+
+        // Without SASL-IR
+        let mut client = SaslClient::plain(None, "alice", "password");
+        assert_eq!(
+            client.authenticate(false),
+            CommandBody::authenticate(AuthMechanism::Plain)
+        );
+        assert_eq!(client.finish(), Err(SaslClientError::Incomplete));
+        assert_eq!(
+            client.challenge(&CommandContinuationRequest::basic(None, "Go ahead").unwrap()),
+            Ok(continue_data(b"\x00alice\x00password"))
+        );
+        assert!(client.finish().is_ok());
+    }
+
+    #[test]
+    fn test_login() {
+        let mut client = SaslClient::login("alice", "password");
+        assert_eq!(
+            client.authenticate(true),
+            CommandBody::authenticate(AuthMechanism::Login)
+        );
+        assert_eq!(
+            client.challenge(&base64_challenge(b"Username:")),
+            Ok(continue_data(b"alice"))
+        );
+        assert_eq!(client.finish(), Err(SaslClientError::Incomplete));
+        assert_eq!(
+            client.challenge(&base64_challenge(b"Password:")),
+            Ok(continue_data(b"password"))
+        );
+        assert!(client.finish().is_ok());
+        assert_eq!(
+            client.challenge(&base64_challenge(b"")),
+            Err(SaslClientError::UnexpectedChallenge)
+        );
+    }
+
+    #[test]
+    fn test_xoauth2_error() {
+        let mut client = SaslClient::xoauth2("alice@example.org", "token");
+        assert_eq!(
+            client.authenticate(true),
+            CommandBody::authenticate_with_ir(
+                AuthMechanism::XOAuth2,
+                b"user=alice@example.org\x01auth=Bearer token\x01\x01".as_ref()
+            )
+        );
+
+        // The server reports an error and expects an empty response.
+        assert_eq!(
+            client.challenge(&base64_challenge(b"{\"status\":\"401\"}")),
+            Ok(continue_data(b""))
+        );
+        assert_eq!(client.finish(), Err(SaslClientError::Incomplete));
+    }
+
+    #[test]
+    fn test_oauthbearer() {
+        let mut client = SaslClient::oauthbearer(
+            "user@example.com",
+            Some("server.example.com"),
+            Some(143),
+            "vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==",
+        );
+        assert_eq!(client.mechanism(), AuthMechanism::OAuthBearer);
+        assert_eq!(
+            client.challenge(&base64_challenge(b"")),
+            Ok(continue_data(
+                b"n,a=user@example.com,\x01host=server.example.com\x01port=143\x01auth=Bearer vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==\x01\x01"
+            ))
+        );
+
+        // The server reports an error and expects a single `%x01`.
+        assert_eq!(
+            client.challenge(&base64_challenge(b"{\"status\":\"invalid_token\"}")),
+            Ok(continue_data(b"\x01"))
+        );
+    }
+
+    #[test]
+    fn test_scram() {
+        let tests = [
+            // RFC 5802, section 5
+            (
+                ScramHash::Sha1,
+                "fyko+d2lbbFgONRv9qkxdawL",
+                "r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096",
+                "c=biws,r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,p=v0X8v3Bz2T0CJGbJQyF0X+HI4Ts=",
+                "v=rmF9pqV8S7suAoZWja4dJRkFsKQ=",
+            ),
+            // RFC 7677, section 3
+            (
+                ScramHash::Sha256,
+                "rOprNGfwEbeRWgbNEkqO",
+                "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+                "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ=",
+                "v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=",
+            ),
+        ];
+
+        for (hash, nonce, server_first, client_final, server_final) in tests {
+            let mut client = SaslClient::scram_with_nonce(
+                hash,
+                "user",
+                "pencil",
+                ChannelBinding::None,
+                nonce.to_string(),
+            )
+            .unwrap();
+
+            assert_eq!(
+                client.authenticate(true),
+                CommandBody::authenticate_with_ir(
+                    hash.mechanism(false),
+                    format!("n,,n=user,r={nonce}").into_bytes()
+                )
+            );
+            assert_eq!(
+                client.challenge(&base64_challenge(server_first.as_bytes())),
+                Ok(continue_data(client_final.as_bytes()))
+            );
+            assert_eq!(client.finish(), Err(SaslClientError::Incomplete));
+            assert_eq!(
+                client.challenge(&base64_challenge(server_final.as_bytes())),
+                Ok(continue_data(b""))
+            );
+            assert!(client.finish().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_scram_errors() {
+        let client = || {
+            let mut client = SaslClient::scram_with_nonce(
+                ScramHash::Sha1,
+                "user",
+                "pencil",
+                ChannelBinding::None,
+                "fyko+d2lbbFgONRv9qkxdawL".to_string(),
+            )
+            .unwrap();
+            assert_eq!(
+                client.challenge(&base64_challenge(b"")),
+                Ok(continue_data(b"n,,n=user,r=fyko+d2lbbFgONRv9qkxdawL"))
+            );
+            client
+        };
+
+        let tests = [
+            (
+                "m=ext,r=fyko+d2lbbFgONRv9qkxdawL3rfc,s=QSXCR+Q6sek8bf92,i=4096",
+                SaslClientError::UnsupportedExtension,
+            ),
+            (
+                "r=fyko+d2lbbFgONRv9qkxdawL,s=QSXCR+Q6sek8bf92,i=4096",
+                SaslClientError::InvalidNonce,
+            ),
+            (
+                "r=xxxx+d2lbbFgONRv9qkxdawL3rfc,s=QSXCR+Q6sek8bf92,i=4096",
+                SaslClientError::InvalidNonce,
+            ),
+            (
+                "r=fyko+d2lbbFgONRv9qkxdawL3rfc,s=QSXCR+Q6sek8bf92,i=0",
+                SaslClientError::Malformed,
+            ),
+            (
+                "r=fyko+d2lbbFgONRv9qkxdawL3rfc,i=4096,s=QSXCR+Q6sek8bf92",
+                SaslClientError::Malformed,
+            ),
+            (
+                "r=fyko+d2lbbFgONRv9qkxdawL3rfc,s=QSXCR+Q6sek8bf92,i=4294967295",
+                SaslClientError::TooManyIterations(4294967295),
+            ),
+            ("e=unknown-user", SaslClientError::Malformed),
+        ];
+
+        for (server_first, expected) in tests {
+            assert_eq!(
+                client().challenge(&base64_challenge(server_first.as_bytes())),
+                Err(expected)
+            );
+        }
+
+        // Wrong server signature
+        let mut client = client();
+        client
+            .challenge(&base64_challenge(
+                b"r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096",
+            ))
+            .unwrap();
+        assert_eq!(
+            client.challenge(&base64_challenge(b"v=AAAAAAAAAAAAAAAAAAAAAAAAAAA=")),
+            Err(SaslClientError::InvalidServerSignature)
+        );
+        assert_eq!(client.finish(), Err(SaslClientError::Incomplete));
+
+        // Server error
+        let mut client = client();
+        client
+            .challenge(&base64_challenge(
+                b"r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096",
+            ))
+            .unwrap();
+        assert_eq!(
+            client.challenge(&base64_challenge(b"e=invalid-proof")),
+            Err(SaslClientError::Server("invalid-proof".into()))
+        );
+    }
+
+    #[test]
+    fn test_scram_max_iterations() {
+        let client = |max_iterations| {
+            let mut client = SaslClient::scram_with_nonce(
+                ScramHash::Sha1,
+                "user",
+                "pencil",
+                ChannelBinding::None,
+                "fyko+d2lbbFgONRv9qkxdawL".to_string(),
+            )
+            .unwrap();
+            client.set_max_iterations(max_iterations);
+            client.authenticate(true);
+            client
+        };
+
+        let server_first = base64_challenge(
+            b"r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096",
+        );
+
+        assert_eq!(
+            client(4095).challenge(&server_first),
+            Err(SaslClientError::TooManyIterations(4096))
+        );
+        assert!(client(4096).challenge(&server_first).is_ok());
+    }
+
+    #[test]
+    fn test_scram_channel_binding() {
+        let tests = [
+            (
+                ChannelBinding::None,
+                AuthMechanism::ScramSha256,
+                "n,,",
+                "biws",
+            ),
+            (
+                ChannelBinding::NotOffered,
+                AuthMechanism::ScramSha256,
+                "y,,",
+                "eSws",
+            ),
+            (
+                ChannelBinding::TlsExporter(b"\x00\x01".to_vec()),
+                AuthMechanism::ScramSha256Plus,
+                "p=tls-exporter,,",
+                "cD10bHMtZXhwb3J0ZXIsLAAB",
+            ),
+            (
+                ChannelBinding::TlsServerEndPoint(b"\x00\x01".to_vec()),
+                AuthMechanism::ScramSha256Plus,
+                "p=tls-server-end-point,,",
+                "cD10bHMtc2VydmVyLWVuZC1wb2ludCwsAAE=",
+            ),
+        ];
+
+        for (channel_binding, mechanism, gs2_header, c) in tests {
+            let mut client = SaslClient::scram_with_nonce(
+                ScramHash::Sha256,
+                "user",
+                "pencil",
+                channel_binding,
+                "abc".to_string(),
+            )
+            .unwrap();
+            assert_eq!(client.mechanism(), mechanism);
+            assert_eq!(
+                client.authenticate(true),
+                CommandBody::authenticate_with_ir(
+                    mechanism,
+                    format!("{gs2_header}n=user,r=abc").into_bytes()
+                )
+            );
+
+            let AuthenticateData::Continue(client_final) = client
+                .challenge(&base64_challenge(b"r=abcdef,s=QSXCR+Q6sek8bf92,i=4096"))
+                .unwrap()
+            else {
+                panic!("Expected data");
+            };
+            assert!(client_final
+                .declassify()
+                .starts_with(format!("c={c},r=abcdef,p=").as_bytes()));
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Building blocks of SCRAM (RFC 5802).
+
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::sasl::ScramHash;
+
+/// `Hi(str, salt, i)`
+pub(crate) fn hi(hash: ScramHash, password: &[u8], salt: &[u8], iterations: u32) -> Vec<u8> {
+    match hash {
+        ScramHash::Sha1 => {
+            let mut output = [0u8; 20];
+            pbkdf2_hmac::<Sha1>(password, salt, iterations, &mut output);
+            output.to_vec()
+        }
+        ScramHash::Sha256 => {
+            let mut output = [0u8; 32];
+            pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut output);
+            output.to_vec()
+        }
+    }
+}
+
+/// `HMAC(key, str)`
+pub(crate) fn hmac(hash: ScramHash, key: &[u8], data: &[u8]) -> Vec<u8> {
+    match hash {
+        ScramHash::Sha1 => {
+            let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        ScramHash::Sha256 => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+    }
+}
+
+/// Compare `HMAC(key, str)` with `expected` in constant time.
+pub(crate) fn hmac_verify(hash: ScramHash, key: &[u8], data: &[u8], expected: &[u8]) -> bool {
+    match hash {
+        ScramHash::Sha1 => {
+            let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(data);
+            mac.verify_slice(expected).is_ok()
+        }
+        ScramHash::Sha256 => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(data);
+            mac.verify_slice(expected).is_ok()
+        }
+    }
+}
+
+/// `H(str)`
+pub(crate) fn h(hash: ScramHash, data: &[u8]) -> Vec<u8> {
+    match hash {
+        ScramHash::Sha1 => Sha1::digest(data).to_vec(),
+        ScramHash::Sha256 => Sha256::digest(data).to_vec(),
+    }
+}
+
+/// `XOR`
+pub(crate) fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+/// Compare `a` and `b` in constant time (for equal lengths).
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Encode a `saslname`, i.e., replace `=` with `=3D` and `,` with `=2C`.
+pub(crate) fn saslname_encode(name: &str) -> String {
+    name.replace('=', "=3D").replace(',', "=2C")
+}
+
+/// Decode a `saslname`.
+///
+/// Returns `None` if `name` contains a `=` that isn't followed by `3D` or `2C`.
+pub(crate) fn saslname_decode(name: &str) -> Option<String> {
+    let mut output = String::with_capacity(name.len());
+    let mut rest = name;
+
+    while let Some(position) = rest.find('=') {
+        output.push_str(&rest[..position]);
+
+        match rest.get(position..position + 3) {
+            Some("=3D") => output.push('='),
+            Some("=2C") => output.push(','),
+            _ => return None,
+        }
+
+        rest = &rest[position + 3..];
+    }
+
+    output.push_str(rest);
+
+    Some(output)
+}
+
+/// Split a SCRAM message into its `<attr>=<value>` attributes.
+///
+/// Returns `None` if any attribute is malformed.
+pub(crate) fn attributes(message: &str) -> Option<Vec<(char, &str)>> {
+    message
+        .split(',')
+        .map(|attribute| {
+            let mut chars = attribute.chars();
+            let name = chars.next()?;
+
+            if !name.is_ascii_alphabetic() {
+                return None;
+            }
+
+            let value = chars.as_str().strip_prefix('=')?;
+
+            Some((name, value))
+        })
+        .collect()
+}
+
+/// `printable` excluding `,`.
+pub(crate) fn is_valid_nonce(nonce: &str) -> bool {
+    !nonce.is_empty()
+        && nonce
+            .bytes()
+            .all(|b| matches!(b, 0x21..=0x2b | 0x2d..=0x7e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saslname() {
+        let tests = [
+            ("alice", "alice"),
+            ("a=b", "a=3Db"),
+            ("a,b", "a=2Cb"),
+            ("=,", "=3D=2C"),
+        ];
+
+        for (decoded, encoded) in tests {
+            assert_eq!(saslname_encode(decoded), encoded);
+            assert_eq!(saslname_decode(encoded).as_deref(), Some(decoded));
+        }
+
+        for test in ["=", "a=", "a=3", "a=2D", "=3d"] {
+            assert_eq!(saslname_decode(test), None);
+        }
+    }
+
+    #[test]
+    fn test_attributes() {
+        assert_eq!(
+            attributes("r=abc,s=QSXCR+Q6sek8bf92,i=4096"),
+            Some(vec![('r', "abc"), ('s', "QSXCR+Q6sek8bf92"), ('i', "4096")])
+        );
+        assert_eq!(attributes("r=a=b"), Some(vec![('r', "a=b")]));
+        assert_eq!(attributes(""), None);
+        assert_eq!(attributes("r"), None);
+        assert_eq!(attributes("r=a,,s=b"), None);
+        assert_eq!(attributes("1=a"), None);
+    }
+}
@@ -0,0 +1,1321 @@
+//! Server-side SASL mechanisms.
+//!
+//! A [`SaslServer`] is created for an `AUTHENTICATE` command and consumes the client's
+//! [`AuthenticateData`] lines (e.g., as decoded by imap-codec's `AuthenticateDataCodec`).
+//! Every call returns a [`Step`] telling the server what to send next. Credentials are obtained
+//! through the [`CredentialLookup`] trait.
+//!
+//! # Example
+//!
+//! ```rust
+//! use imap_types::{
+//!     auth::{AuthMechanism, AuthenticateData},
+//!     response::CommandContinuationRequest,
+//!     sasl::{
+//!         server::{CredentialLookup, SaslServer, Step},
+//!         ChannelBinding,
+//!     },
+//! };
+//!
+//! struct Users;
+//!
+//! impl CredentialLookup for Users {
+//!     fn verify_password(&self, authcid: &str, password: &str) -> bool {
+//!         authcid == "alice" && password == "password"
+//!     }
+//! }
+//!
+//! // C: A1 AUTHENTICATE PLAIN
+//! let mut server = SaslServer::new(&AuthMechanism::Plain, ChannelBinding::None).unwrap();
+//!
+//! // S: +
+//! assert_eq!(
+//!     server.start(&Users, None),
+//!     Step::Challenge(CommandContinuationRequest::base64(b"".as_ref()))
+//! );
+//!
+//! // C: AGFsaWNlAHBhc3N3b3Jk
+//! assert_eq!(
+//!     server.step(
+//!         &Users,
+//!         &AuthenticateData::r#continue(b"\x00alice\x00password".as_ref())
+//!     ),
+//!     Step::Success {
+//!         identity: "alice".into()
+//!     }
+//! );
+//! ```
+
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+use rand::{
+    distributions::{Alphanumeric, DistString},
+    Rng,
+};
+use thiserror::Error;
+
+use crate::{
+    auth::{AuthMechanism, AuthenticateData},
+    core::{Tag, Text},
+    response::{Code, CommandContinuationRequest, Status, StatusBody, StatusKind, Tagged},
+    sasl::{
+        scram::{attributes, constant_time_eq, h, hi, hmac, is_valid_nonce, saslname_decode, xor},
+        ChannelBinding, ScramHash,
+    },
+    secret::Secret,
+};
+
+/// Recommended SCRAM iteration count (RFC 7677, section 4).
+pub const DEFAULT_SCRAM_ITERATIONS: u32 = 4096;
+
+/// Lookup of credentials.
+///
+/// Implement the methods required by the offered mechanisms. By default, nothing is accepted.
+pub trait CredentialLookup {
+    /// Verify the password of `authcid` (PLAIN and LOGIN).
+    fn verify_password(&self, _authcid: &str, _password: &str) -> bool {
+        false
+    }
+
+    /// Returns the stored SCRAM credentials of `username` (SCRAM).
+    fn scram_credentials(&self, _username: &str, _hash: ScramHash) -> Option<ScramCredentials> {
+        None
+    }
+
+    /// Returns the secret used to derive the SCRAM salt of unknown users (SCRAM).
+    ///
+    /// Unknown users are answered with mocked credentials so that they can't be told apart from
+    /// known users. The mocked salt is derived from the username using this secret. Thus, the
+    /// secret must not be disclosed and should be the same across connections. By default, a
+    /// random secret is generated once per process.
+    fn scram_mock_secret(&self) -> Vec<u8> {
+        process_secret()
+    }
+
+    /// Returns the iteration count of mocked SCRAM credentials (SCRAM).
+    ///
+    /// Use the iteration count of stored credentials (see [`ScramCredentials::derive`]).
+    fn scram_mock_iterations(&self, _hash: ScramHash) -> u32 {
+        DEFAULT_SCRAM_ITERATIONS
+    }
+
+    /// Verify a bearer token (OAUTHBEARER and XOAUTH2) and return the authenticated identity.
+    ///
+    /// `user` is the user name sent by the client (if any). For OAUTHBEARER, this is the authzid,
+    /// which is additionally checked using [`CredentialLookup::authorize`].
+    fn verify_bearer(&self, _user: Option<&str>, _token: &str) -> Result<String, BearerError> {
+        Err(BearerError::invalid_token())
+    }
+
+    /// Returns whether `authcid` may act as `authzid`.
+    ///
+    /// By default, only `authzid == authcid` is allowed.
+    fn authorize(&self, authcid: &str, authzid: &str) -> bool {
+        authcid == authzid
+    }
+}
+
+/// Stored SCRAM credentials (RFC 5802, section 3).
+///
+/// Servers should store these instead of passwords.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScramCredentials {
+    pub salt: Vec<u8>,
+    pub iterations: u32,
+    pub stored_key: Secret<Vec<u8>>,
+    pub server_key: Secret<Vec<u8>>,
+}
+
+impl ScramCredentials {
+    /// Derive SCRAM credentials from a password.
+    ///
+    /// The password is prepared using SASLprep.
+    pub fn derive(
+        hash: ScramHash,
+        password: &str,
+        salt: Vec<u8>,
+        iterations: u32,
+    ) -> Result<Self, SaslServerError> {
+        if iterations == 0 {
+            return Err(SaslServerError::InvalidPassword);
+        }
+
+        let password =
+            stringprep::saslprep(password).map_err(|_| SaslServerError::InvalidPassword)?;
+
+        let salted_password = hi(hash, password.as_bytes(), &salt, iterations);
+        let client_key = hmac(hash, &salted_password, b"Client Key");
+        let server_key = hmac(hash, &salted_password, b"Server Key");
+
+        Ok(Self {
+            salt,
+            iterations,
+            stored_key: Secret::new(h(hash, &client_key)),
+            server_key: Secret::new(server_key),
+        })
+    }
+
+    /// Credentials used for unknown users so that they are indistinguishable from known users
+    /// until the proof is checked.
+    ///
+    /// The salt is derived from `username` so that repeated attempts see the same salt.
+    fn mock(hash: ScramHash, username: &str, secret: &[u8], iterations: u32) -> Self {
+        let mut rng = rand::thread_rng();
+        let length = h(hash, b"").len();
+
+        let mut salt = hmac(hash, secret, username.as_bytes());
+        salt.truncate(16);
+
+        Self {
+            salt,
+            iterations,
+            stored_key: Secret::new((0..length).map(|_| rng.gen()).collect()),
+            server_key: Secret::new((0..length).map(|_| rng.gen()).collect()),
+        }
+    }
+}
+
+/// Error reported for a rejected bearer token (RFC 7628, section 3.2.2).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BearerError {
+    /// Status, e.g., `invalid_token`.
+    pub status: String,
+    /// OAuth scope required to access the server.
+    pub scope: Option<String>,
+    /// URL of the OpenID Connect discovery document.
+    pub openid_configuration: Option<String>,
+}
+
+impl BearerError {
+    /// `{"status":"invalid_token"}`
+    pub fn invalid_token() -> Self {
+        Self {
+            status: "invalid_token".into(),
+            scope: None,
+            openid_configuration: None,
+        }
+    }
+
+    /// Serialize to JSON.
+    pub fn to_json(&self) -> String {
+        fn escape(value: &str) -> String {
+            let mut escaped = String::with_capacity(value.len());
+
+            for c in value.chars() {
+                match c {
+                    '"' => escaped.push_str("\\\""),
+                    '\\' => escaped.push_str("\\\\"),
+                    c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => escaped.push(c),
+                }
+            }
+
+            escaped
+        }
+
+        let mut json = format!("{{\"status\":\"{}\"", escape(&self.status));
+
+        if let Some(scope) = &self.scope {
+            json.push_str(&format!(",\"scope\":\"{}\"", escape(scope)));
+        }
+
+        if let Some(openid_configuration) = &self.openid_configuration {
+            json.push_str(&format!(
+                ",\"openid-configuration\":\"{}\"",
+                escape(openid_configuration)
+            ));
+        }
+
+        json.push('}');
+
+        json
+    }
+}
+
+/// Next step of the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Send the challenge and wait for the next [`AuthenticateData`].
+    Challenge(CommandContinuationRequest<'static>),
+    /// The client is authenticated as `identity`. Send a tagged `OK`.
+    Success { identity: String },
+    /// The authentication failed. Send the response returned by [`SaslServerError::status`].
+    Failure(SaslServerError),
+}
+
+/// Server-side SASL state machine.
+#[derive(Debug)]
+pub struct SaslServer {
+    mechanism: AuthMechanism<'static>,
+    state: State,
+}
+
+#[derive(Debug)]
+enum State {
+    Plain,
+    LoginUsername,
+    LoginPassword {
+        username: String,
+    },
+    Bearer,
+    /// The error challenge was sent. Waiting for the client's (dummy) response.
+    BearerFailed,
+    ScramClientFirst {
+        hash: ScramHash,
+        /// Whether a `-PLUS` mechanism was chosen.
+        plus: bool,
+        channel_binding: ChannelBinding,
+    },
+    ScramClientFinal(Box<ScramServer>),
+    /// The server signature was sent. Waiting for the client's (empty) response.
+    ScramServerFinal {
+        identity: String,
+    },
+    Done,
+}
+
+#[derive(Debug)]
+struct ScramServer {
+    hash: ScramHash,
+    channel_binding: ChannelBinding,
+    credentials: ScramCredentials,
+    /// Whether `credentials` are mocked, i.e., the user is unknown.
+    unknown: bool,
+    username: String,
+    /// Requested authorization identity (only checked after the proof was verified).
+    authzid: Option<String>,
+    gs2_header: String,
+    client_first_bare: String,
+    server_first: String,
+    nonce: String,
+}
+
+impl SaslServer {
+    /// Start the server side of `mechanism`.
+    ///
+    /// `channel_binding` is the server's channel binding (if any). `-PLUS` mechanisms are only
+    /// supported when it holds channel binding data and require the client to use it.
+    pub fn new(
+        mechanism: &AuthMechanism,
+        channel_binding: ChannelBinding,
+    ) -> Result<Self, SaslServerError> {
+        let state = match (mechanism, channel_binding.is_plus()) {
+            (AuthMechanism::Plain, _) => State::Plain,
+            (AuthMechanism::Login, _) => State::LoginUsername,
+            (AuthMechanism::OAuthBearer | AuthMechanism::XOAuth2, _) => State::Bearer,
+            (AuthMechanism::ScramSha1, _) => State::ScramClientFirst {
+                hash: ScramHash::Sha1,
+                plus: false,
+                channel_binding,
+            },
+            (AuthMechanism::ScramSha1Plus, true) => State::ScramClientFirst {
+                hash: ScramHash::Sha1,
+                plus: true,
+                channel_binding,
+            },
+            (AuthMechanism::ScramSha256, _) => State::ScramClientFirst {
+                hash: ScramHash::Sha256,
+                plus: false,
+                channel_binding,
+            },
+            (AuthMechanism::ScramSha256Plus, true) => State::ScramClientFirst {
+                hash: ScramHash::Sha256,
+                plus: true,
+                channel_binding,
+            },
+            _ => return Err(SaslServerError::UnsupportedMechanism),
+        };
+
+        Ok(Self {
+            mechanism: mechanism.clone().into_static(),
+            state,
+        })
+    }
+
+    /// Returns the SASL mechanism.
+    pub fn mechanism(&self) -> &AuthMechanism<'static> {
+        &self.mechanism
+    }
+
+    /// Process the initial response (SASL-IR) of the `AUTHENTICATE` command (if any).
+    pub fn start<C>(&mut self, credentials: &C, initial_response: Option<&[u8]>) -> Step
+    where
+        C: CredentialLookup + ?Sized,
+    {
+        match initial_response {
+            Some(initial_response) => self.process(credentials, initial_response),
+            None => match self.state {
+                State::LoginUsername => challenge(b"Username:"),
+                _ => challenge(b""),
+            },
+        }
+    }
+
+    /// Process the client's response to the last challenge.
+    pub fn step<C>(&mut self, credentials: &C, data: &AuthenticateData) -> Step
+    where
+        C: CredentialLookup + ?Sized,
+    {
+        match data {
+            AuthenticateData::Continue(data) => self.process(credentials, data.declassify()),
+            // "If the client wishes to cancel an authentication exchange, it issues a line
+            // consisting of a single "*". If the server receives such a response, it MUST reject
+            // the AUTHENTICATE command by sending a tagged BAD response." (RFC 3501)
+            AuthenticateData::Cancel => {
+                self.state = State::Done;
+
+                Step::Failure(SaslServerError::Cancelled)
+            }
+        }
+    }
+
+    fn process<C>(&mut self, credentials: &C, data: &[u8]) -> Step
+    where
+        C: CredentialLookup + ?Sized,
+    {
+        let result = match std::mem::replace(&mut self.state, State::Done) {
+            State::Plain => plain(credentials, data),
+            State::LoginUsername => match std::str::from_utf8(data) {
+                Ok(username) => {
+                    self.state = State::LoginPassword {
+                        username: username.to_owned(),
+                    };
+
+                    return challenge(b"Password:");
+                }
+                Err(_) => Err(SaslServerError::Malformed),
+            },
+            State::LoginPassword { username } => match std::str::from_utf8(data) {
+                Ok(password) if credentials.verify_password(&username, password) => Ok(username),
+                Ok(_) => Err(SaslServerError::InvalidCredentials),
+                Err(_) => Err(SaslServerError::Malformed),
+            },
+            State::Bearer => {
+                let parsed = if self.mechanism == AuthMechanism::XOAuth2 {
+                    xoauth2(data)
+                } else {
+                    oauthbearer(data)
+                };
+
+                match parsed {
+                    Some((user, token)) => match credentials.verify_bearer(user, token) {
+                        // OAUTHBEARER sends an authzid instead of a user name.
+                        Ok(identity) if self.mechanism == AuthMechanism::OAuthBearer => {
+                            authorize(credentials, &identity, user.unwrap_or_default())
+                        }
+                        Ok(identity) => Ok(identity),
+                        Err(error) => {
+                            self.state = State::BearerFailed;
+
+                            return challenge(error.to_json().as_bytes());
+                        }
+                    },
+                    None => Err(SaslServerError::Malformed),
+                }
+            }
+            State::BearerFailed => Err(SaslServerError::InvalidCredentials),
+            State::ScramClientFirst {
+                hash,
+                plus,
+                channel_binding,
+            } => {
+                let server_nonce = Alphanumeric.sample_string(&mut rand::thread_rng(), 24);
+
+                match ScramServer::new(
+                    credentials,
+                    hash,
+                    plus,
+                    channel_binding,
+                    data,
+                    &server_nonce,
+                ) {
+                    Ok(scram) => {
+                        let server_first = scram.server_first.clone();
+                        self.state = State::ScramClientFinal(Box::new(scram));
+
+                        return challenge(server_first.as_bytes());
+                    }
+                    Err(error) => Err(error),
+                }
+            }
+            State::ScramClientFinal(scram) => match scram.verify(credentials, data) {
+                Ok((identity, server_final)) => {
+                    self.state = State::ScramServerFinal { identity };
+
+                    return challenge(server_final.as_bytes());
+                }
+                Err(error) => Err(error),
+            },
+            State::ScramServerFinal { identity } => {
+                if data.is_empty() {
+                    Ok(identity)
+                } else {
+                    Err(SaslServerError::Malformed)
+                }
+            }
+            State::Done => Err(SaslServerError::UnexpectedData),
+        };
+
+        match result {
+            Ok(identity) => Step::Success { identity },
+            Err(error) => Step::Failure(error),
+        }
+    }
+}
+
+/// Random secret generated once per process (see [`CredentialLookup::scram_mock_secret`]).
+fn process_secret() -> Vec<u8> {
+    static SECRET: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+
+    let mut secret = SECRET
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    secret
+        .get_or_insert_with(|| rand::thread_rng().gen())
+        .to_vec()
+}
+
+fn challenge(data: &[u8]) -> Step {
+    Step::Challenge(CommandContinuationRequest::base64(data.to_vec()))
+}
+
+/// `[authzid] NUL authcid NUL passwd`
+fn plain<C>(credentials: &C, data: &[u8]) -> Result<String, SaslServerError>
+where
+    C: CredentialLookup + ?Sized,
+{
+    let data = std::str::from_utf8(data).map_err(|_| SaslServerError::Malformed)?;
+
+    let mut parts = data.split('\x00');
+    let (authzid, authcid, password) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(authzid), Some(authcid), Some(password)) if parts.next().is_none() => {
+            (authzid, authcid, password)
+        }
+        _ => return Err(SaslServerError::Malformed),
+    };
+
+    if !credentials.verify_password(authcid, password) {
+        return Err(SaslServerError::InvalidCredentials);
+    }
+
+    authorize(credentials, authcid, authzid)
+}
+
+fn authorize<C>(credentials: &C, authcid: &str, authzid: &str) -> Result<String, SaslServerError>
+where
+    C: CredentialLookup + ?Sized,
+{
+    if authzid.is_empty() {
+        Ok(authcid.to_owned())
+    } else if credentials.authorize(authcid, authzid) {
+        Ok(authzid.to_owned())
+    } else {
+        Err(SaslServerError::AuthorizationFailed)
+    }
+}
+
+/// `user=<user>\x01auth=Bearer <token>\x01\x01`
+fn xoauth2(data: &[u8]) -> Option<(Option<&str>, &str)> {
+    let data = std::str::from_utf8(data).ok()?;
+    let data = data.strip_suffix("\x01\x01")?;
+
+    let mut user = None;
+    let mut token = None;
+
+    for pair in data.split('\x01') {
+        if let Some(value) = pair.strip_prefix("user=") {
+            user = Some(value);
+        } else if let Some(value) = pair.strip_prefix("auth=") {
+            token = Some(bearer_token(value)?);
+        }
+    }
+
+    Some((Some(user?), token?))
+}
+
+/// `gs2-header kvsep *(kvpair) kvsep`
+fn oauthbearer(data: &[u8]) -> Option<(Option<&str>, &str)> {
+    let data = std::str::from_utf8(data).ok()?;
+    let data = data.strip_suffix("\x01\x01")?;
+    let (gs2_header, kvpairs) = data.split_once('\x01')?;
+
+    // Channel binding isn't supported by OAUTHBEARER.
+    let authzid = match gs2_header
+        .strip_prefix("n,")
+        .or(gs2_header.strip_prefix("y,"))?
+    {
+        "," => None,
+        authzid => Some(authzid.strip_prefix("a=")?.strip_suffix(',')?),
+    };
+
+    let token = kvpairs
+        .split('\x01')
+        .find_map(|pair| pair.strip_prefix("auth="))
+        .and_then(bearer_token)?;
+
+    // The `authzid` is a `saslname`. Escaped `,` and `=` are not supported.
+    if authzid.map_or(false, |authzid| authzid.contains('=')) {
+        return None;
+    }
+
+    Some((authzid, token))
+}
+
+/// `"Bearer" 1*SP b64token`
+fn bearer_token(value: &str) -> Option<&str> {
+    let (scheme, token) = value.split_once(' ')?;
+
+    if !scheme.eq_ignore_ascii_case("Bearer") {
+        return None;
+    }
+
+    let token = token.trim_start_matches(' ');
+
+    if token.is_empty() {
+        None
+    } else {
+        Some(token)
+    }
+}
+
+impl ScramServer {
+    /// Process the `client-first-message`.
+    fn new<C>(
+        credentials: &C,
+        hash: ScramHash,
+        plus: bool,
+        channel_binding: ChannelBinding,
+        data: &[u8],
+        server_nonce: &str,
+    ) -> Result<Self, SaslServerError>
+    where
+        C: CredentialLookup + ?Sized,
+    {
+        let data = std::str::from_utf8(data).map_err(|_| SaslServerError::Malformed)?;
+
+        // gs2-header = gs2-cbind-flag "," [ authzid ] ","
+        let (cbind_flag, rest) = data.split_once(',').ok_or(SaslServerError::Malformed)?;
+        let (raw_authzid, client_first_bare) =
+            rest.split_once(',').ok_or(SaslServerError::Malformed)?;
+        let gs2_header = format!("{cbind_flag},{raw_authzid},");
+
+        match (cbind_flag, plus) {
+            // `-PLUS` mechanisms require channel binding.
+            (flag, true) if flag == channel_binding.gs2_cbind_flag() => {}
+            ("n", false) => {}
+            // The client supports channel binding but thinks we don't. When we do, this
+            // indicates a downgrade attack.
+            ("y", false) if !channel_binding.is_plus() => {}
+            _ => return Err(SaslServerError::ChannelBinding),
+        }
+
+        let authzid = match raw_authzid {
+            "" => None,
+            authzid => Some(
+                authzid
+                    .strip_prefix("a=")
+                    .and_then(saslname_decode)
+                    .ok_or(SaslServerError::Malformed)?,
+            ),
+        };
+
+        let (username, client_nonce) = match attributes(client_first_bare).as_deref() {
+            Some([('m', _), ..]) => return Err(SaslServerError::Malformed),
+            Some([('n', username), ('r', nonce), ..]) => (
+                saslname_decode(username).ok_or(SaslServerError::Malformed)?,
+                *nonce,
+            ),
+            _ => return Err(SaslServerError::Malformed),
+        };
+
+        if !is_valid_nonce(client_nonce) {
+            return Err(SaslServerError::Malformed);
+        }
+
+        let (stored, unknown) = match credentials.scram_credentials(&username, hash) {
+            Some(scram_credentials) => (scram_credentials, false),
+            None => (
+                ScramCredentials::mock(
+                    hash,
+                    &username,
+                    &credentials.scram_mock_secret(),
+                    credentials.scram_mock_iterations(hash),
+                ),
+                true,
+            ),
+        };
+
+        let nonce = format!("{client_nonce}{server_nonce}");
+        let server_first = format!(
+            "r={nonce},s={},i={}",
+            base64.encode(&stored.salt),
+            stored.iterations
+        );
+
+        Ok(Self {
+            hash,
+            gs2_header,
+            channel_binding,
+            credentials: stored,
+            unknown,
+            username,
+            authzid,
+            client_first_bare: client_first_bare.to_owned(),
+            server_first,
+            nonce,
+        })
+    }
+
+    /// Verify the `client-final-message` and return the identity and the `server-final-message`.
+    ///
+    /// The authorization identity is only checked after the proof was verified.
+    fn verify<C>(&self, credentials: &C, data: &[u8]) -> Result<(String, String), SaslServerError>
+    where
+        C: CredentialLookup + ?Sized,
+    {
+        let data = std::str::from_utf8(data).map_err(|_| SaslServerError::Malformed)?;
+
+        // client-final-message-without-proof "," proof
+        let (client_final_without_proof, proof) =
+            data.rsplit_once(",p=").ok_or(SaslServerError::Malformed)?;
+
+        let (channel_binding, nonce) = match attributes(client_final_without_proof).as_deref() {
+            Some([('c', channel_binding), ('r', nonce), ..]) => (*channel_binding, *nonce),
+            _ => return Err(SaslServerError::Malformed),
+        };
+
+        let expected_channel_binding = {
+            let mut input = self.gs2_header.as_bytes().to_vec();
+
+            if self.gs2_header.starts_with("p=") {
+                input.extend_from_slice(self.channel_binding.data());
+            }
+
+            base64.encode(input)
+        };
+
+        if channel_binding != expected_channel_binding {
+            return Err(SaslServerError::ChannelBinding);
+        }
+
+        if nonce != self.nonce {
+            return Err(SaslServerError::Malformed);
+        }
+
+        let proof = base64
+            .decode(proof)
+            .map_err(|_| SaslServerError::Malformed)?;
+
+        let auth_message = format!(
+            "{},{},{client_final_without_proof}",
+            self.client_first_bare, self.server_first
+        );
+
+        let stored_key = self.credentials.stored_key.declassify();
+        let client_signature = hmac(self.hash, stored_key, auth_message.as_bytes());
+
+        if proof.len() != client_signature.len() {
+            return Err(SaslServerError::InvalidCredentials);
+        }
+
+        let client_key = xor(&proof, &client_signature);
+
+        if !constant_time_eq(&h(self.hash, &client_key), stored_key) || self.unknown {
+            return Err(SaslServerError::InvalidCredentials);
+        }
+
+        let identity = authorize(
+            credentials,
+            &self.username,
+            self.authzid.as_deref().unwrap_or_default(),
+        )?;
+
+        let server_signature = hmac(
+            self.hash,
+            self.credentials.server_key.declassify(),
+            auth_message.as_bytes(),
+        );
+
+        Ok((identity, format!("v={}", base64.encode(server_signature))))
+    }
+}
+
+/// Error during server-side SASL authentication.
+#[derive(Clone, Debug, Eq, Error, Hash, PartialEq)]
+pub enum SaslServerError {
+    #[error("Unsupported mechanism")]
+    UnsupportedMechanism,
+    #[error("Authentication cancelled")]
+    Cancelled,
+    #[error("Malformed data")]
+    Malformed,
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Authorization failed")]
+    AuthorizationFailed,
+    #[error("Channel binding failed")]
+    ChannelBinding,
+    #[error("Unexpected data")]
+    UnexpectedData,
+    #[error("Invalid password (SASLprep)")]
+    InvalidPassword,
+}
+
+impl SaslServerError {
+    /// Construct the tagged status response.
+    ///
+    /// A cancelled authentication is rejected with `BAD` (RFC 3501). Other failures are
+    /// rejected with `NO [AUTHENTICATIONFAILED]` or `NO [AUTHORIZATIONFAILED]` (RFC 5530).
+    pub fn status<'a>(&self, tag: Tag<'a>) -> Status<'a> {
+        let (kind, code, text) = match self {
+            Self::Cancelled => (StatusKind::Bad, None, "Authentication cancelled"),
+            Self::UnsupportedMechanism => {
+                (StatusKind::No, Some(Code::Cannot), "Unsupported mechanism")
+            }
+            Self::AuthorizationFailed => (
+                StatusKind::No,
+                Some(Code::AuthorizationFailed),
+                "Authorization failed",
+            ),
+            _ => (
+                StatusKind::No,
+                Some(Code::AuthenticationFailed),
+                "Authentication failed",
+            ),
+        };
+
+        Status::Tagged(Tagged {
+            tag,
+            body: StatusBody {
+                kind,
+                code,
+                text: Text::unvalidated(text),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{command::CommandBody, sasl::client::SaslClient};
+
+    struct Users;
+
+    impl CredentialLookup for Users {
+        fn verify_password(&self, authcid: &str, password: &str) -> bool {
+            authcid == "user" && password == "pencil"
+        }
+
+        fn scram_credentials(&self, username: &str, hash: ScramHash) -> Option<ScramCredentials> {
+            if username != "user" {
+                return None;
+            }
+
+            let salt = match hash {
+                ScramHash::Sha1 => base64.decode("QSXCR+Q6sek8bf92").unwrap(),
+                ScramHash::Sha256 => base64.decode("W22ZaJ0SNY7soEsUEjb6gQ==").unwrap(),
+            };
+
+            ScramCredentials::derive(hash, "pencil", salt, DEFAULT_SCRAM_ITERATIONS).ok()
+        }
+
+        fn verify_bearer(&self, _user: Option<&str>, token: &str) -> Result<String, BearerError> {
+            match token {
+                "vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==" => Ok("user@example.com".into()),
+                _ => Err(BearerError {
+                    status: "invalid_token".into(),
+                    scope: Some("example_scope".into()),
+                    openid_configuration: Some(
+                        "https://example.com/.well-known/openid-configuration".into(),
+                    ),
+                }),
+            }
+        }
+
+        fn authorize(&self, authcid: &str, authzid: &str) -> bool {
+            authcid == authzid
+                || (authcid == "user" && authzid == "admin")
+                || (authcid == "user@example.com" && authzid == "admin@example.com")
+        }
+    }
+
+    fn base64_challenge(data: &[u8]) -> Step {
+        Step::Challenge(CommandContinuationRequest::base64(data.to_vec()))
+    }
+
+    fn continue_data(data: &[u8]) -> AuthenticateData<'static> {
+        AuthenticateData::r#continue(data.to_vec())
+    }
+
+    fn success(identity: &str) -> Step {
+        Step::Success {
+            identity: identity.into(),
+        }
+    }
+
+    #[test]
+    fn test_plain() {
+        let tests = [
+            (b"\x00user\x00pencil".as_ref(), success("user")),
+            (b"admin\x00user\x00pencil", success("admin")),
+            (
+                b"root\x00user\x00pencil",
+                Step::Failure(SaslServerError::AuthorizationFailed),
+            ),
+            (
+                b"\x00user\x00wrong",
+                Step::Failure(SaslServerError::InvalidCredentials),
+            ),
+            (b"user\x00pencil", Step::Failure(SaslServerError::Malformed)),
+        ];
+
+        for (data, expected) in tests {
+            // SASL-IR
+            let mut server = SaslServer::new(&AuthMechanism::Plain, ChannelBinding::None).unwrap();
+            assert_eq!(server.start(&Users, Some(data)), expected);
+
+            // Without SASL-IR
+            let mut server = SaslServer::new(&AuthMechanism::Plain, ChannelBinding::None).unwrap();
+            assert_eq!(server.start(&Users, None), base64_challenge(b""));
+            assert_eq!(server.step(&Users, &continue_data(data)), expected);
+        }
+    }
+
+    #[test]
+    fn test_login() {
+        let mut server = SaslServer::new(&AuthMechanism::Login, ChannelBinding::None).unwrap();
+        assert_eq!(server.start(&Users, None), base64_challenge(b"Username:"));
+        assert_eq!(
+            server.step(&Users, &continue_data(b"user")),
+            base64_challenge(b"Password:")
+        );
+        assert_eq!(
+            server.step(&Users, &continue_data(b"pencil")),
+            success("user")
+        );
+        assert_eq!(
+            server.step(&Users, &continue_data(b"")),
+            Step::Failure(SaslServerError::UnexpectedData)
+        );
+    }
+
+    #[test]
+    fn test_oauthbearer() {
+        // RFC 7628, section 4.1
+        let mut server =
+            SaslServer::new(&AuthMechanism::OAuthBearer, ChannelBinding::None).unwrap();
+        assert_eq!(
+            server.start(
+                &Users,
+                Some(b"n,a=user@example.com,\x01host=server.example.com\x01port=143\x01auth=Bearer vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==\x01\x01")
+            ),
+            success("user@example.com")
+        );
+
+        // RFC 7628, section 4.3
+        let mut server =
+            SaslServer::new(&AuthMechanism::OAuthBearer, ChannelBinding::None).unwrap();
+        assert_eq!(
+            server.start(
+                &Users,
+                Some(b"n,a=user@example.com,\x01host=server.example.com\x01port=143\x01auth=Bearer vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCh==\x01\x01")
+            ),
+            base64_challenge(b"{\"status\":\"invalid_token\",\"scope\":\"example_scope\",\"openid-configuration\":\"https://example.com/.well-known/openid-configuration\"}")
+        );
+        assert_eq!(
+            server.step(&Users, &continue_data(b"\x01")),
+            Step::Failure(SaslServerError::InvalidCredentials)
+        );
+
+        // The authzid must be authorized.
+        let tests = [
+            (b"n,,".as_ref(), success("user@example.com")),
+            (b"n,a=admin@example.com,", success("admin@example.com")),
+            (
+                b"n,a=root@example.com,",
+                Step::Failure(SaslServerError::AuthorizationFailed),
+            ),
+        ];
+
+        for (gs2_header, expected) in tests {
+            let mut data = gs2_header.to_vec();
+            data.extend_from_slice(
+                b"\x01auth=Bearer vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==\x01\x01",
+            );
+
+            let mut server =
+                SaslServer::new(&AuthMechanism::OAuthBearer, ChannelBinding::None).unwrap();
+            assert_eq!(server.start(&Users, Some(&data)), expected);
+        }
+    }
+
+    #[test]
+    fn test_xoauth2() {
+        let mut server = SaslServer::new(&AuthMechanism::XOAuth2, ChannelBinding::None).unwrap();
+        assert_eq!(server.start(&Users, None), base64_challenge(b""));
+        assert_eq!(
+            server.step(
+                &Users,
+                &continue_data(b"user=user@example.com\x01auth=Bearer vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==\x01\x01")
+            ),
+            success("user@example.com")
+        );
+
+        let mut server = SaslServer::new(&AuthMechanism::XOAuth2, ChannelBinding::None).unwrap();
+        assert_eq!(
+            server.start(
+                &Users,
+                Some(b"user=user@example.com\x01auth=Basic abc\x01\x01")
+            ),
+            Step::Failure(SaslServerError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_bearer_error_json() {
+        assert_eq!(
+            BearerError::invalid_token().to_json(),
+            "{\"status\":\"invalid_token\"}"
+        );
+        assert_eq!(
+            BearerError {
+                status: "a\"b\\c\n".into(),
+                scope: None,
+                openid_configuration: None,
+            }
+            .to_json(),
+            "{\"status\":\"a\\\"b\\\\c\\u000a\"}"
+        );
+    }
+
+    #[test]
+    fn test_scram() {
+        let tests = [
+            // RFC 5802, section 5
+            (
+                ScramHash::Sha1,
+                "n,,n=user,r=fyko+d2lbbFgONRv9qkxdawL",
+                "3rfcNHYJY1ZVvWVs7j",
+                "r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096",
+                "c=biws,r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,p=v0X8v3Bz2T0CJGbJQyF0X+HI4Ts=",
+                "v=rmF9pqV8S7suAoZWja4dJRkFsKQ=",
+            ),
+            // RFC 7677, section 3
+            (
+                ScramHash::Sha256,
+                "n,,n=user,r=rOprNGfwEbeRWgbNEkqO",
+                "%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0",
+                "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+                "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ=",
+                "v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=",
+            ),
+        ];
+
+        for (hash, client_first, server_nonce, server_first, client_final, server_final) in tests {
+            let scram = ScramServer::new(
+                &Users,
+                hash,
+                false,
+                ChannelBinding::None,
+                client_first.as_bytes(),
+                server_nonce,
+            )
+            .unwrap();
+            assert_eq!(scram.server_first, server_first);
+            assert_eq!(
+                scram.verify(&Users, client_final.as_bytes()),
+                Ok(("user".into(), server_final.into()))
+            );
+
+            // Wrong proof
+            let client_final = client_final.replace(",p=", ",p=AAAA");
+            assert_eq!(
+                scram.verify(&Users, client_final.as_bytes()),
+                Err(SaslServerError::InvalidCredentials)
+            );
+        }
+    }
+
+    /// Compute the `client-final-message` for `server_first`.
+    fn scram_client_final(
+        hash: ScramHash,
+        password: &str,
+        gs2_header: &str,
+        client_first_bare: &str,
+        server_first: &str,
+    ) -> String {
+        let (nonce, salt, iterations) = match attributes(server_first).as_deref() {
+            Some([('r', nonce), ('s', salt), ('i', iterations)]) => (
+                nonce.to_string(),
+                base64.decode(salt).unwrap(),
+                iterations.parse().unwrap(),
+            ),
+            _ => panic!("Expected server-first-message"),
+        };
+
+        let salted_password = hi(hash, password.as_bytes(), &salt, iterations);
+        let client_key = hmac(hash, &salted_password, b"Client Key");
+        let stored_key = h(hash, &client_key);
+
+        let without_proof = format!("c={},r={nonce}", base64.encode(gs2_header));
+        let auth_message = format!("{client_first_bare},{server_first},{without_proof}");
+        let client_signature = hmac(hash, &stored_key, auth_message.as_bytes());
+
+        format!(
+            "{without_proof},p={}",
+            base64.encode(xor(&client_key, &client_signature))
+        )
+    }
+
+    #[test]
+    fn test_scram_unknown_user() {
+        let server_first = |username: &str| {
+            let scram = ScramServer::new(
+                &Users,
+                ScramHash::Sha256,
+                false,
+                ChannelBinding::None,
+                format!("n,,n={username},r=rOprNGfwEbeRWgbNEkqO").as_bytes(),
+                "abc",
+            )
+            .unwrap();
+
+            // Strip `r=<nonce>,`.
+            scram.server_first.split_once(',').unwrap().1.to_owned()
+        };
+
+        // Unknown users get the same salt and iteration count on every attempt ...
+        assert_eq!(server_first("unknown"), server_first("unknown"));
+        assert!(server_first("unknown").ends_with(",i=4096"));
+        // ... but not the same salt as other (unknown) users.
+        assert_ne!(server_first("unknown"), server_first("other"));
+    }
+
+    #[test]
+    fn test_scram_authzid() {
+        let tests = [
+            ("user", "pencil", "a=admin", Ok("admin")),
+            (
+                "user",
+                "pencil",
+                "a=root",
+                Err(SaslServerError::AuthorizationFailed),
+            ),
+            // The authzid is only checked after the proof ...
+            (
+                "user",
+                "wrong",
+                "a=root",
+                Err(SaslServerError::InvalidCredentials),
+            ),
+            // ... and unknown users fail the same way.
+            (
+                "unknown",
+                "pencil",
+                "a=root",
+                Err(SaslServerError::InvalidCredentials),
+            ),
+            (
+                "unknown",
+                "pencil",
+                "a=unknown",
+                Err(SaslServerError::InvalidCredentials),
+            ),
+        ];
+
+        for (username, password, authzid, expected) in tests {
+            let gs2_header = format!("n,{authzid},");
+            let client_first_bare = format!("n={username},r=rOprNGfwEbeRWgbNEkqO");
+
+            let scram = ScramServer::new(
+                &Users,
+                ScramHash::Sha256,
+                false,
+                ChannelBinding::None,
+                format!("{gs2_header}{client_first_bare}").as_bytes(),
+                "3rfcNHYJY1ZVvWVs7j",
+            )
+            .unwrap();
+
+            let client_final = scram_client_final(
+                ScramHash::Sha256,
+                password,
+                &gs2_header,
+                &client_first_bare,
+                &scram.server_first,
+            );
+
+            assert_eq!(
+                scram
+                    .verify(&Users, client_final.as_bytes())
+                    .map(|(identity, _)| identity),
+                expected.map(String::from)
+            );
+        }
+    }
+
+    #[test]
+    fn test_scram_roundtrip() {
+        let tests = [
+            (ChannelBinding::None, "pencil", Ok("user")),
+            (ChannelBinding::None, "wrong", Err(())),
+            (
+                ChannelBinding::TlsExporter(b"cb".to_vec()),
+                "pencil",
+                Ok("user"),
+            ),
+        ];
+
+        for (channel_binding, password, expected) in tests {
+            let mechanism = ScramHash::Sha256.mechanism(channel_binding.is_plus());
+
+            let mut client =
+                SaslClient::scram(ScramHash::Sha256, "user", password, channel_binding.clone())
+                    .unwrap();
+            let mut server = SaslServer::new(&mechanism, channel_binding).unwrap();
+
+            let CommandBody::Authenticate {
+                initial_response: Some(initial_response),
+                ..
+            } = client.authenticate(true)
+            else {
+                panic!("Expected initial response");
+            };
+
+            let mut step = server.start(&Users, Some(initial_response.declassify().as_ref()));
+
+            let result = loop {
+                match step {
+                    Step::Challenge(challenge) => {
+                        let Ok(data) = client.challenge(&challenge) else {
+                            break Err(());
+                        };
+                        step = server.step(&Users, &data);
+                    }
+                    Step::Success { identity } => {
+                        assert!(client.finish().is_ok());
+                        break Ok(identity);
+                    }
+                    Step::Failure(_) => break Err(()),
+                }
+            };
+
+            assert_eq!(result.as_deref(), expected);
+        }
+    }
+
+    #[test]
+    fn test_scram_unknown_user() {
+        // Unknown users are only rejected after the proof.
+        let mut client =
+            SaslClient::scram(ScramHash::Sha256, "unknown", "pencil", ChannelBinding::None)
+                .unwrap();
+        let mut server =
+            SaslServer::new(&AuthMechanism::ScramSha256, ChannelBinding::None).unwrap();
+
+        let Step::Challenge(challenge) = server.start(&Users, None) else {
+            panic!("Expected challenge");
+        };
+        let data = client.challenge(&challenge).unwrap();
+        let Step::Challenge(challenge) = server.step(&Users, &data) else {
+            panic!("Expected challenge");
+        };
+        let data = client.challenge(&challenge).unwrap();
+        assert_eq!(
+            server.step(&Users, &data),
+            Step::Failure(SaslServerError::InvalidCredentials)
+        );
+    }
+
+    #[test]
+    fn test_scram_downgrade() {
+        // The client supports channel binding but the server offered a `-PLUS` mechanism.
+        let mut server = SaslServer::new(
+            &AuthMechanism::ScramSha256,
+            ChannelBinding::TlsExporter(b"cb".to_vec()),
+        )
+        .unwrap();
+        assert_eq!(
+            server.start(&Users, Some(b"y,,n=user,r=rOprNGfwEbeRWgbNEkqO")),
+            Step::Failure(SaslServerError::ChannelBinding)
+        );
+
+        assert_eq!(
+            SaslServer::new(&AuthMechanism::ScramSha256Plus, ChannelBinding::None).err(),
+            Some(SaslServerError::UnsupportedMechanism)
+        );
+    }
+
+    #[test]
+    fn test_scram_plus_mismatch() {
+        let channel_binding = ChannelBinding::TlsExporter(b"cb".to_vec());
+
+        // `-PLUS` without channel binding
+        let mut server =
+            SaslServer::new(&AuthMechanism::ScramSha256Plus, channel_binding.clone()).unwrap();
+        assert_eq!(
+            server.start(&Users, Some(b"n,,n=user,r=rOprNGfwEbeRWgbNEkqO")),
+            Step::Failure(SaslServerError::ChannelBinding)
+        );
+
+        // Channel binding without `-PLUS`
+        let mut server =
+            SaslServer::new(&AuthMechanism::ScramSha256, channel_binding.clone()).unwrap();
+        assert_eq!(
+            server.start(
+                &Users,
+                Some(b"p=tls-exporter,,n=user,r=rOprNGfwEbeRWgbNEkqO")
+            ),
+            Step::Failure(SaslServerError::ChannelBinding)
+        );
+
+        // `-PLUS` with channel binding
+        let mut server = SaslServer::new(&AuthMechanism::ScramSha256Plus, channel_binding).unwrap();
+        assert!(matches!(
+            server.start(
+                &Users,
+                Some(b"p=tls-exporter,,n=user,r=rOprNGfwEbeRWgbNEkqO")
+            ),
+            Step::Challenge(_)
+        ));
+    }
+
+    #[test]
+    fn test_cancel() {
+        for mechanism in [
+            AuthMechanism::Plain,
+            AuthMechanism::Login,
+            AuthMechanism::XOAuth2,
+            AuthMechanism::ScramSha256,
+        ] {
+            let mut server = SaslServer::new(&mechanism, ChannelBinding::None).unwrap();
+            assert!(matches!(server.start(&Users, None), Step::Challenge(_)));
+            assert_eq!(
+                server.step(&Users, &AuthenticateData::Cancel),
+                Step::Failure(SaslServerError::Cancelled)
+            );
+            assert_eq!(
+                server.step(&Users, &continue_data(b"")),
+                Step::Failure(SaslServerError::UnexpectedData)
+            );
+        }
+
+        let tag = Tag::try_from("A").unwrap();
+        assert_eq!(
+            SaslServerError::Cancelled.status(tag.clone()),
+            Status::bad(Some(tag.clone()), None, "Authentication cancelled").unwrap()
+        );
+        assert_eq!(
+            SaslServerError::InvalidCredentials.status(tag.clone()),
+            Status::no(
+                Some(tag),
+                Some(Code::AuthenticationFailed),
+                "Authentication failed"
+            )
+            .unwrap()
+        );
+    }
+}
@@ -4,12 +4,16 @@ use bounded_static_derive::ToStatic;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::GmailLabel;
 use crate::{
     core::{AString, Atom, Vec1},
     datetime::NaiveDate,
     sequence::SequenceSet,
 };
 
+pub mod evaluate;
+
 /// The defined search keys.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
@@ -87,6 +91,13 @@ pub enum SearchKey<'a> {
     /// Messages that do not match the specified search key.
     Not(Box<SearchKey<'a>>),
 
+    /// Messages that match the specified search key using fuzzy matching (RFC 6203).
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the SEARCH=FUZZY capability.
+    /// </div>
+    Fuzzy(Box<SearchKey<'a>>),
+
     /// Messages that do not have the \Recent flag set.  This is
     /// functionally equivalent to "NOT RECENT" (as opposed to "NOT
     /// NEW").
@@ -158,6 +169,26 @@ pub enum SearchKey<'a> {
 
     /// Messages that do not have the \Seen flag set.
     Unseen,
+
+    /// Messages matching a query in Gmail's web search syntax, e.g., `has:attachment in:unread`.
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailRaw(AString<'a>),
+
+    /// Messages with the specified Gmail message ID.
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailMessageId(u64),
+
+    /// Messages in the conversation with the specified Gmail thread ID.
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailThreadId(u64),
+
+    /// Messages with the specified Gmail label.
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_gmail")))]
+    GmailLabels(GmailLabel<'a>),
 }
 
 impl<'a> SearchKey<'a> {
@@ -0,0 +1,530 @@
+//! Evaluation of search keys against messages.
+//!
+//! This module implements the matching rules of [RFC 3501, section 6.4.4] for an in-memory
+//! representation of messages. Implement [`Searchable`] for your message type and call
+//! [`SearchKey::matches`].
+//!
+//! ```rust
+//! use std::{borrow::Cow, num::NonZeroU32};
+//!
+//! use imap_types::{
+//!     datetime::DateTime,
+//!     flag::Flag,
+//!     search::{
+//!         evaluate::{Largest, Searchable},
+//!         SearchKey,
+//!     },
+//! };
+//!
+//! struct Message {
+//!     uid: NonZeroU32,
+//!     flags: Vec<Flag<'static>>,
+//!     internal_date: DateTime,
+//!     headers: Vec<(String, String)>,
+//!     body: String,
+//! }
+//!
+//! impl Searchable for Message {
+//!     fn seq(&self) -> NonZeroU32 {
+//!         NonZeroU32::new(1).unwrap()
+//!     }
+//!
+//!     fn uid(&self) -> NonZeroU32 {
+//!         self.uid
+//!     }
+//!
+//!     fn flags(&self) -> Cow<[Flag<'_>]> {
+//!         Cow::Borrowed(&self.flags)
+//!     }
+//!
+//!     fn internal_date(&self) -> DateTime {
+//!         self.internal_date.clone()
+//!     }
+//!
+//!     fn size(&self) -> u32 {
+//!         self.body.len() as u32
+//!     }
+//!
+//!     fn header_fields(&self) -> Vec<(Cow<str>, Cow<str>)> {
+//!         self.headers
+//!             .iter()
+//!             .map(|(name, value)| (Cow::from(name.as_str()), Cow::from(value.as_str())))
+//!             .collect()
+//!     }
+//!
+//!     fn body(&self) -> Cow<str> {
+//!         Cow::Borrowed(&self.body)
+//!     }
+//! }
+//!
+//! let message = Message {
+//!     uid: NonZeroU32::new(42).unwrap(),
+//!     flags: vec![Flag::Seen],
+//!     internal_date: DateTime::try_from(
+//!         chrono::DateTime::parse_from_rfc3339("2024-02-01T12:00:00+01:00").unwrap(),
+//!     )
+//!     .unwrap(),
+//!     headers: vec![("Subject".into(), "Hello, World!".into())],
+//!     body: "How are you?".into(),
+//! };
+//!
+//! let largest = Largest {
+//!     seq: NonZeroU32::new(1).unwrap(),
+//!     uid: NonZeroU32::new(42).unwrap(),
+//! };
+//!
+//! let key = SearchKey::And(
+//!     vec![
+//!         SearchKey::Seen,
+//!         SearchKey::Subject("world".try_into().unwrap()),
+//!     ]
+//!     .try_into()
+//!     .unwrap(),
+//! );
+//!
+//! assert!(key.matches(&message, &largest));
+//! assert!(!SearchKey::Unseen.matches(&message, &largest));
+//! ```
+//!
+//! [RFC 3501, section 6.4.4]: https://datatracker.ietf.org/doc/html/rfc3501#section-6.4.4
+
+use std::{borrow::Cow, num::NonZeroU32};
+
+use crate::{datetime::DateTime, flag::Flag, search::SearchKey};
+
+/// A message that can be searched.
+pub trait Searchable {
+    /// Message sequence number.
+    fn seq(&self) -> NonZeroU32;
+
+    /// Unique identifier.
+    fn uid(&self) -> NonZeroU32;
+
+    /// Flags set on the message (without `\Recent`).
+    fn flags(&self) -> Cow<[Flag<'_>]>;
+
+    /// Whether the message is `\Recent`.
+    fn is_recent(&self) -> bool {
+        false
+    }
+
+    /// Internal date.
+    fn internal_date(&self) -> DateTime;
+
+    /// Size in octets ([RFC 2822] size).
+    ///
+    /// [RFC 2822]: https://datatracker.ietf.org/doc/html/rfc2822
+    fn size(&self) -> u32;
+
+    /// All header fields as `(name, value)` pairs in message order.
+    ///
+    /// Values should be unfolded and, if possible, decoded.
+    fn header_fields(&self) -> Vec<(Cow<str>, Cow<str>)>;
+
+    /// Values of all header fields named `name` (compared case-insensitively).
+    fn header(&self, name: &str) -> Vec<Cow<str>> {
+        self.header_fields()
+            .into_iter()
+            .filter(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    /// Body (without header), if possible decoded.
+    fn body(&self) -> Cow<str>;
+}
+
+/// Largest sequence number and UID in the mailbox.
+///
+/// Used to resolve `*` in sequence sets.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Largest {
+    pub seq: NonZeroU32,
+    pub uid: NonZeroU32,
+}
+
+impl<'a> SearchKey<'a> {
+    /// Returns whether `message` matches the search key.
+    ///
+    /// * Dates (`BEFORE`, `ON`, `SINCE`) use the internal date, disregarding time and timezone.
+    /// * `SENT*` keys use the date of the `Date` header field, disregarding time and timezone.
+    ///   Messages without a (parseable) `Date` header field never match.
+    /// * String keys match case-insensitively as substrings.
+    /// * `FUZZY` is evaluated as its (exact) inner key.
+    ///
+    /// Gmail-specific keys can't be evaluated and never match.
+    pub fn matches<M>(&self, message: &M, largest: &Largest) -> bool
+    where
+        M: Searchable + ?Sized,
+    {
+        match self {
+            Self::And(keys) => keys
+                .as_ref()
+                .iter()
+                .all(|key| key.matches(message, largest)),
+            Self::Or(left, right) => {
+                left.matches(message, largest) || right.matches(message, largest)
+            }
+            Self::Not(key) => !key.matches(message, largest),
+            Self::Fuzzy(key) => key.matches(message, largest),
+            Self::SequenceSet(sequence_set) => sequence_set.contains(message.seq(), largest.seq),
+            Self::Uid(sequence_set) => sequence_set.contains(message.uid(), largest.uid),
+            Self::All => true,
+            // Flags
+            Self::Answered => has_flag(message, &Flag::Answered),
+            Self::Deleted => has_flag(message, &Flag::Deleted),
+            Self::Draft => has_flag(message, &Flag::Draft),
+            Self::Flagged => has_flag(message, &Flag::Flagged),
+            Self::Seen => has_flag(message, &Flag::Seen),
+            Self::Keyword(keyword) => has_flag(message, &Flag::Keyword(keyword.clone())),
+            Self::Unanswered => !has_flag(message, &Flag::Answered),
+            Self::Undeleted => !has_flag(message, &Flag::Deleted),
+            Self::Undraft => !has_flag(message, &Flag::Draft),
+            Self::Unflagged => !has_flag(message, &Flag::Flagged),
+            Self::Unseen => !has_flag(message, &Flag::Seen),
+            Self::Unkeyword(keyword) => !has_flag(message, &Flag::Keyword(keyword.clone())),
+            Self::Recent => message.is_recent(),
+            Self::New => message.is_recent() && !has_flag(message, &Flag::Seen),
+            Self::Old => !message.is_recent(),
+            // Size
+            Self::Larger(size) => message.size() > *size,
+            Self::Smaller(size) => message.size() < *size,
+            // Internal date
+            Self::Before(date) => internal_date(message) < *date.as_ref(),
+            Self::On(date) => internal_date(message) == *date.as_ref(),
+            Self::Since(date) => internal_date(message) >= *date.as_ref(),
+            // Date header
+            Self::SentBefore(date) => {
+                sent_date(message).map_or(false, |sent| sent < *date.as_ref())
+            }
+            Self::SentOn(date) => sent_date(message).map_or(false, |sent| sent == *date.as_ref()),
+            Self::SentSince(date) => {
+                sent_date(message).map_or(false, |sent| sent >= *date.as_ref())
+            }
+            // Strings
+            Self::Bcc(value) => header_contains(message, "Bcc", value.as_ref()),
+            Self::Cc(value) => header_contains(message, "Cc", value.as_ref()),
+            Self::From(value) => header_contains(message, "From", value.as_ref()),
+            Self::Subject(value) => header_contains(message, "Subject", value.as_ref()),
+            Self::To(value) => header_contains(message, "To", value.as_ref()),
+            Self::Header(name, value) => header_contains(
+                message,
+                &String::from_utf8_lossy(name.as_ref()),
+                value.as_ref(),
+            ),
+            Self::Body(value) => contains(&message.body(), value.as_ref()),
+            Self::Text(value) => {
+                message.header_fields().iter().any(|(name, field)| {
+                    contains(name, value.as_ref()) || contains(field, value.as_ref())
+                }) || contains(&message.body(), value.as_ref())
+            }
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailRaw(_)
+            | Self::GmailMessageId(_)
+            | Self::GmailThreadId(_)
+            | Self::GmailLabels(_) => false,
+        }
+    }
+}
+
+fn has_flag<M>(message: &M, flag: &Flag) -> bool
+where
+    M: Searchable + ?Sized,
+{
+    message
+        .flags()
+        .iter()
+        .any(|candidate| match (candidate, flag) {
+            (Flag::Keyword(a), Flag::Keyword(b)) => a.as_ref().eq_ignore_ascii_case(b.as_ref()),
+            _ => candidate == flag,
+        })
+}
+
+fn internal_date<M>(message: &M) -> chrono::NaiveDate
+where
+    M: Searchable + ?Sized,
+{
+    message.internal_date().as_ref().date_naive()
+}
+
+fn sent_date<M>(message: &M) -> Option<chrono::NaiveDate>
+where
+    M: Searchable + ?Sized,
+{
+    message
+        .header("Date")
+        .first()
+        .and_then(|value| parse_rfc2822_date(value))
+}
+
+fn header_contains<M>(message: &M, name: &str, needle: &[u8]) -> bool
+where
+    M: Searchable + ?Sized,
+{
+    message
+        .header(name)
+        .iter()
+        .any(|value| contains(value, needle))
+}
+
+/// Case-insensitive substring search.
+///
+/// An empty `needle` is contained in every `haystack`.
+fn contains(haystack: &str, needle: &[u8]) -> bool {
+    let needle = String::from_utf8_lossy(needle).to_lowercase();
+
+    haystack.to_lowercase().contains(&needle)
+}
+
+/// Parse the date part of an [RFC 2822] `date-time`, ignoring time and timezone.
+///
+/// Accepts `[day-of-week ","] day month year ...` including obsolete two- and three-digit years.
+///
+/// [RFC 2822]: https://datatracker.ietf.org/doc/html/rfc2822#section-3.3
+fn parse_rfc2822_date(value: &str) -> Option<chrono::NaiveDate> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let value = match value.split_once(',') {
+        Some((_, rest)) => rest,
+        None => value,
+    };
+    let mut tokens = value.split_whitespace();
+
+    let day = tokens.next()?;
+    let month = tokens.next()?.to_ascii_lowercase();
+    let year = tokens.next()?;
+
+    if day.is_empty() || day.len() > 2 || year.len() < 2 || year.len() > 4 {
+        return None;
+    }
+
+    let day = day.parse::<u32>().ok()?;
+    let month = MONTHS.iter().position(|candidate| *candidate == month)? as u32 + 1;
+    let year = match (year.len(), year.parse::<i32>().ok()?) {
+        // RFC 2822, section 4.3 (obsolete date and time)
+        (2, year @ 0..=49) => year + 2000,
+        (2 | 3, year) => year + 1900,
+        (_, year) => year,
+    };
+
+    chrono::NaiveDate::from_ymd_opt(year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::Atom, datetime::NaiveDate};
+
+    struct Message {
+        seq: u32,
+        uid: u32,
+        flags: Vec<Flag<'static>>,
+        recent: bool,
+        internal_date: &'static str,
+        size: u32,
+        headers: Vec<(&'static str, &'static str)>,
+        body: &'static str,
+    }
+
+    impl Searchable for Message {
+        fn seq(&self) -> NonZeroU32 {
+            NonZeroU32::new(self.seq).unwrap()
+        }
+
+        fn uid(&self) -> NonZeroU32 {
+            NonZeroU32::new(self.uid).unwrap()
+        }
+
+        fn flags(&self) -> Cow<[Flag<'_>]> {
+            Cow::Borrowed(&self.flags)
+        }
+
+        fn is_recent(&self) -> bool {
+            self.recent
+        }
+
+        fn internal_date(&self) -> DateTime {
+            DateTime::try_from(chrono::DateTime::parse_from_rfc3339(self.internal_date).unwrap())
+                .unwrap()
+        }
+
+        fn size(&self) -> u32 {
+            self.size
+        }
+
+        fn header_fields(&self) -> Vec<(Cow<str>, Cow<str>)> {
+            self.headers
+                .iter()
+                .map(|(name, value)| (Cow::Borrowed(*name), Cow::Borrowed(*value)))
+                .collect()
+        }
+
+        fn body(&self) -> Cow<str> {
+            Cow::Borrowed(self.body)
+        }
+    }
+
+    fn message() -> Message {
+        Message {
+            seq: 2,
+            uid: 20,
+            flags: vec![
+                Flag::Seen,
+                Flag::Keyword(Atom::try_from("$Forwarded").unwrap()),
+            ],
+            recent: false,
+            // 2024-02-01 in UTC, but the internal date is in `+01:00`.
+            internal_date: "2024-02-02T00:30:00+01:00",
+            size: 1000,
+            headers: vec![
+                ("From", "Alice <alice@example.org>"),
+                ("To", "Bob <bob@example.org>"),
+                ("Subject", "Quarterly Report"),
+                ("Date", "Wed, 31 Jan 2024 23:59:00 -0800 (PST)"),
+                ("X-Priority", ""),
+            ],
+            body: "Please find the NUMBERS attached.",
+        }
+    }
+
+    fn largest() -> Largest {
+        Largest {
+            seq: NonZeroU32::new(3).unwrap(),
+            uid: NonZeroU32::new(30).unwrap(),
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::try_from(chrono::NaiveDate::from_ymd_opt(year, month, day).unwrap()).unwrap()
+    }
+
+    fn string(value: &'static str) -> crate::core::AString<'static> {
+        value.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_matches() {
+        let message = message();
+
+        let tests = [
+            (SearchKey::All, true),
+            (SearchKey::SequenceSet("2".try_into().unwrap()), true),
+            (SearchKey::SequenceSet("*".try_into().unwrap()), false),
+            (SearchKey::Uid("15:*".try_into().unwrap()), true),
+            (SearchKey::Uid("*".try_into().unwrap()), false),
+            // Flags
+            (SearchKey::Seen, true),
+            (SearchKey::Unseen, false),
+            (SearchKey::Answered, false),
+            (SearchKey::Unanswered, true),
+            (
+                SearchKey::Keyword(Atom::try_from("$forwarded").unwrap()),
+                true,
+            ),
+            (
+                SearchKey::Unkeyword(Atom::try_from("$FORWARDED").unwrap()),
+                false,
+            ),
+            (SearchKey::Keyword(Atom::try_from("$Junk").unwrap()), false),
+            (SearchKey::Recent, false),
+            (SearchKey::New, false),
+            (SearchKey::Old, true),
+            // Size
+            (SearchKey::Larger(999), true),
+            (SearchKey::Larger(1000), false),
+            (SearchKey::Smaller(1001), true),
+            (SearchKey::Smaller(1000), false),
+            // Internal date (disregarding time and timezone)
+            (SearchKey::On(date(2024, 2, 2)), true),
+            (SearchKey::On(date(2024, 2, 1)), false),
+            (SearchKey::Since(date(2024, 2, 2)), true),
+            (SearchKey::Since(date(2024, 2, 3)), false),
+            (SearchKey::Before(date(2024, 2, 2)), false),
+            (SearchKey::Before(date(2024, 2, 3)), true),
+            // Date header (disregarding time and timezone)
+            (SearchKey::SentOn(date(2024, 1, 31)), true),
+            (SearchKey::SentOn(date(2024, 2, 1)), false),
+            (SearchKey::SentSince(date(2024, 1, 31)), true),
+            (SearchKey::SentBefore(date(2024, 1, 31)), false),
+            (SearchKey::SentBefore(date(2024, 2, 1)), true),
+            // Strings
+            (SearchKey::From(string("ALICE")), true),
+            (SearchKey::To(string("alice")), false),
+            (SearchKey::Cc(string("")), false),
+            (SearchKey::Subject(string("report")), true),
+            (SearchKey::Body(string("numbers")), true),
+            (SearchKey::Body(string("quarterly")), false),
+            (SearchKey::Text(string("quarterly")), true),
+            (SearchKey::Text(string("attached")), true),
+            (SearchKey::Header(string("x-priority"), string("")), true),
+            (SearchKey::Header(string("X-Spam"), string("")), false),
+            (SearchKey::Header(string("subject"), string("port")), true),
+            // Combinators
+            (SearchKey::Not(Box::new(SearchKey::Seen)), false),
+            (
+                SearchKey::Or(Box::new(SearchKey::Answered), Box::new(SearchKey::Seen)),
+                true,
+            ),
+            (
+                SearchKey::And(
+                    vec![SearchKey::Seen, SearchKey::Answered]
+                        .try_into()
+                        .unwrap(),
+                ),
+                false,
+            ),
+            (SearchKey::Fuzzy(Box::new(SearchKey::Seen)), true),
+        ];
+
+        for (key, expected) in tests {
+            assert_eq!(key.matches(&message, &largest()), expected, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn test_recent() {
+        let mut message = message();
+        message.recent = true;
+        message.flags.clear();
+
+        assert!(SearchKey::Recent.matches(&message, &largest()));
+        assert!(SearchKey::New.matches(&message, &largest()));
+        assert!(!SearchKey::Old.matches(&message, &largest()));
+    }
+
+    #[test]
+    fn test_missing_date_header() {
+        let mut message = message();
+        message.headers.retain(|(name, _)| *name != "Date");
+
+        assert!(!SearchKey::SentOn(date(2024, 1, 31)).matches(&message, &largest()));
+        assert!(!SearchKey::SentBefore(date(2099, 1, 1)).matches(&message, &largest()));
+        assert!(!SearchKey::SentSince(date(1970, 1, 1)).matches(&message, &largest()));
+    }
+
+    #[test]
+    fn test_parse_rfc2822_date() {
+        let tests = [
+            ("Wed, 31 Jan 2024 23:59:00 -0800", Some((2024, 1, 31))),
+            ("1 feb 2024 00:00 +0000", Some((2024, 2, 1))),
+            ("  Fri ,  21 Nov 1997 09:55:06 -0600", Some((1997, 11, 21))),
+            ("21 Nov 97 09:55:06 GMT", Some((1997, 11, 21))),
+            ("21 Nov 03 09:55:06 GMT", Some((2003, 11, 21))),
+            ("21 Nov 103 09:55:06 GMT", Some((2003, 11, 21))),
+            ("30 Feb 2024 00:00 +0000", None),
+            ("31 Foo 2024 00:00 +0000", None),
+            ("2024-01-31T00:00:00Z", None),
+            ("", None),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(
+                parse_rfc2822_date(test),
+                expected.map(|(y, m, d)| chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap()),
+                "{test}"
+            );
+        }
+    }
+}
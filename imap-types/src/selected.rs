@@ -0,0 +1,552 @@
+//! Client-side model of the selected mailbox.
+//!
+//! A client must keep track of the messages in the selected mailbox: Message sequence numbers
+//! change whenever a message is expunged, UIDs are only valid for a given `UIDVALIDITY`, and flags
+//! are updated by unilateral `FETCH` responses.
+//!
+//! [`SelectedMailbox`] does this bookkeeping. Feed it every response received while a mailbox is
+//! selected (including the responses to `SELECT` or `EXAMINE`) using [`SelectedMailbox::update`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::num::NonZeroU32;
+//!
+//! use imap_types::{
+//!     fetch::MessageDataItem,
+//!     response::{Data, Response},
+//!     selected::SelectedMailbox,
+//! };
+//!
+//! let mut mailbox = SelectedMailbox::new();
+//!
+//! // * 3 EXISTS
+//! mailbox.update(&Response::Data(Data::Exists(3)));
+//!
+//! // * 1 FETCH (UID 10)
+//! // * 2 FETCH (UID 20)
+//! // * 3 FETCH (UID 30)
+//! for (seq, uid) in [(1, 10), (2, 20), (3, 30)] {
+//!     let uid = NonZeroU32::new(uid).unwrap();
+//!     mailbox.update(&Response::Data(
+//!         Data::fetch(seq, vec![MessageDataItem::Uid(uid)]).unwrap(),
+//!     ));
+//! }
+//!
+//! // * 2 EXPUNGE
+//! mailbox.update(&Response::Data(Data::Expunge(NonZeroU32::new(2).unwrap())));
+//!
+//! assert_eq!(mailbox.exists(), 2);
+//! assert_eq!(mailbox.seq(NonZeroU32::new(30).unwrap()), NonZeroU32::new(2));
+//! ```
+
+use std::{collections::BTreeMap, num::NonZeroU32};
+
+use crate::{
+    extensions::tagged_ext::{TaggedExtComp, TaggedExtItemVal, TaggedExtVal},
+    fetch::MessageDataItem,
+    flag::{Flag, FlagFetch, FlagPerm},
+    response::{Code, Data, Response, Status, StatusBody, Tagged},
+    ToStatic,
+};
+
+/// Client-side model of the selected mailbox.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectedMailbox {
+    exists: u32,
+    /// What is known about messages, keyed by message sequence number.
+    ///
+    /// Note: Stored sparsely as the server may announce (or fetch) arbitrary sequence numbers.
+    messages: BTreeMap<NonZeroU32, Message>,
+    /// What is known about messages only reported by UID (UIDONLY, RFC 9586), keyed by UID.
+    uid_messages: BTreeMap<NonZeroU32, Message>,
+    recent: u32,
+    flags: Vec<Flag<'static>>,
+    permanent_flags: Option<Vec<FlagPerm<'static>>>,
+    uid_validity: Option<NonZeroU32>,
+    uid_next: Option<NonZeroU32>,
+    highest_modseq: Option<u64>,
+}
+
+/// What is known about a message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    /// UID (if known).
+    pub uid: Option<NonZeroU32>,
+    /// Flags (if known).
+    pub flags: Option<Vec<FlagFetch<'static>>>,
+    /// Modification sequence (RFC 7162, if known).
+    pub modseq: Option<u64>,
+}
+
+impl SelectedMailbox {
+    /// Create an empty model.
+    ///
+    /// Use a new model for every `SELECT` or `EXAMINE`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the model with a received response.
+    ///
+    /// Responses that don't affect the selected mailbox are ignored.
+    pub fn update(&mut self, response: &Response) {
+        match response {
+            Response::Data(data) => self.update_data(data),
+            Response::Status(
+                Status::Untagged(StatusBody {
+                    code: Some(code), ..
+                })
+                | Status::Tagged(Tagged {
+                    body:
+                        StatusBody {
+                            code: Some(code), ..
+                        },
+                    ..
+                }),
+            ) => self.update_code(code),
+            _ => {}
+        }
+    }
+
+    /// Update the model with received data.
+    pub fn update_data(&mut self, data: &Data) {
+        match data {
+            Data::Exists(exists) => {
+                // Note: The number of messages can only decrease through EXPUNGE. We follow the
+                // server anyway to stay in sync.
+                self.exists = *exists;
+                self.messages.retain(|seq, _| seq.get() <= *exists);
+            }
+            Data::Recent(recent) => {
+                self.recent = *recent;
+            }
+            Data::Flags(flags) => {
+                self.flags = flags.to_static();
+            }
+            // "The message sequence number for each successive message in the mailbox is
+            // immediately decremented by 1 [...]" (RFC 3501)
+            Data::Expunge(seq) => {
+                if seq.get() <= self.exists {
+                    let mut above = self.messages.split_off(seq);
+                    above.remove(seq);
+
+                    self.messages
+                        .extend(above.into_iter().filter_map(|(seq, message)| {
+                            Some((NonZeroU32::new(seq.get() - 1)?, message))
+                        }));
+                    self.exists -= 1;
+                }
+            }
+            Data::Fetch { seq, items } => {
+                // The server must announce new messages via EXISTS first. We follow the server
+                // anyway to stay in sync.
+                self.exists = self.exists.max(seq.get());
+
+                let message = self.messages.entry(*seq).or_default();
+                update_message(message, items.as_ref(), &mut self.highest_modseq);
+            }
+            // "The UIDFETCH response [...] is identical to the FETCH response, except that it
+            // uses a UID instead of a message sequence number" (RFC 9586)
+            Data::UidFetch { uid, items } => {
+                let message = match self.seq(*uid) {
+                    Some(seq) => self.messages.entry(seq).or_default(),
+                    None => self.uid_messages.entry(*uid).or_default(),
+                };
+
+                message.uid = Some(*uid);
+                update_message(message, items.as_ref(), &mut self.highest_modseq);
+            }
+            _ => {}
+        }
+    }
+
+    /// Update the model with a received response code.
+    pub fn update_code(&mut self, code: &Code) {
+        match code {
+            Code::UidValidity(uid_validity) => {
+                // All known UIDs (and modification sequences) are invalid after a change of
+                // UIDVALIDITY (RFC 3501, section 2.3.1.1).
+                if self
+                    .uid_validity
+                    .map_or(false, |known| known != *uid_validity)
+                {
+                    for message in self.messages.values_mut() {
+                        message.uid = None;
+                        message.modseq = None;
+                    }
+
+                    self.uid_messages.clear();
+
+                    self.uid_next = None;
+                    self.highest_modseq = None;
+                }
+
+                self.uid_validity = Some(*uid_validity);
+            }
+            Code::UidNext(uid_next) => {
+                self.uid_next = Some(*uid_next);
+            }
+            Code::PermanentFlags(flags) => {
+                self.permanent_flags = Some(flags.to_static());
+            }
+            Code::Other(other) => {
+                let highest_modseq = std::str::from_utf8(other.inner())
+                    .ok()
+                    .and_then(|other| other.split_once(' '))
+                    .filter(|(name, _)| name.eq_ignore_ascii_case("HIGHESTMODSEQ"))
+                    .and_then(|(_, value)| value.parse().ok());
+
+                if let Some(highest_modseq) = highest_modseq {
+                    self.highest_modseq = Some(highest_modseq);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Number of messages in the mailbox.
+    pub fn exists(&self) -> u32 {
+        self.exists
+    }
+
+    /// Number of messages with the `\Recent` flag (as reported by the last RECENT response).
+    pub fn recent(&self) -> u32 {
+        self.recent
+    }
+
+    /// Flags defined in the mailbox (as reported by the last FLAGS response).
+    pub fn flags(&self) -> &[Flag<'static>] {
+        &self.flags
+    }
+
+    /// Flags that can be changed permanently (if reported).
+    pub fn permanent_flags(&self) -> Option<&[FlagPerm<'static>]> {
+        self.permanent_flags.as_deref()
+    }
+
+    /// UIDVALIDITY (if reported).
+    pub fn uid_validity(&self) -> Option<NonZeroU32> {
+        self.uid_validity
+    }
+
+    /// UIDNEXT (if reported).
+    pub fn uid_next(&self) -> Option<NonZeroU32> {
+        self.uid_next
+    }
+
+    /// Highest modification sequence (RFC 7162) reported or seen so far.
+    pub fn highest_modseq(&self) -> Option<u64> {
+        self.highest_modseq
+    }
+
+    /// Returns what is known about the message with sequence number `seq`.
+    ///
+    /// Returns `None` when nothing is known about the message (or it doesn't exist).
+    pub fn message(&self, seq: NonZeroU32) -> Option<&Message> {
+        self.messages.get(&seq)
+    }
+
+    /// Returns the UID of the message with sequence number `seq` (if known).
+    pub fn uid(&self, seq: NonZeroU32) -> Option<NonZeroU32> {
+        self.message(seq)?.uid
+    }
+
+    /// Returns what is known about the message with `uid`.
+    ///
+    /// This includes messages only reported by UID, e.g., via UIDFETCH (RFC 9586).
+    pub fn message_by_uid(&self, uid: NonZeroU32) -> Option<&Message> {
+        match self.seq(uid) {
+            Some(seq) => self.message(seq),
+            None => self.uid_messages.get(&uid),
+        }
+    }
+
+    /// Returns the sequence number of the message with `uid` (if known).
+    pub fn seq(&self, uid: NonZeroU32) -> Option<NonZeroU32> {
+        self.messages
+            .iter()
+            .find(|(_, message)| message.uid == Some(uid))
+            .map(|(seq, _)| *seq)
+    }
+
+    /// Iterate over all known messages (ordered by sequence number).
+    pub fn messages(&self) -> impl Iterator<Item = (NonZeroU32, &Message)> {
+        self.messages.iter().map(|(seq, message)| (*seq, message))
+    }
+}
+
+/// Update `message` with the data items of a FETCH (or UIDFETCH) response.
+fn update_message(
+    message: &mut Message,
+    items: &[MessageDataItem],
+    highest_modseq: &mut Option<u64>,
+) {
+    for item in items {
+        match item {
+            MessageDataItem::Uid(uid) => message.uid = Some(*uid),
+            MessageDataItem::Flags(flags) => message.flags = Some(flags.to_static()),
+            MessageDataItem::Other(other)
+                if other.inner().label.inner().eq_ignore_ascii_case("MODSEQ") =>
+            {
+                if let Some(modseq) = modseq(&other.inner().value) {
+                    message.modseq = Some(modseq);
+                    *highest_modseq = (*highest_modseq).max(Some(modseq));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `MODSEQ (<mod-sequence-value>)`
+fn modseq(value: &TaggedExtItemVal) -> Option<u64> {
+    match value {
+        TaggedExtItemVal::TaggedExtVal(TaggedExtVal::Comp(comps)) => match comps.as_slice() {
+            [TaggedExtComp::AString(value)] => {
+                std::str::from_utf8(value.as_ref()).ok()?.parse().ok()
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{AString, Vec1},
+        extensions::tagged_ext::{TaggedExt, TaggedExtLabel},
+        fetch::MessageDataItemOther,
+    };
+
+    fn nz(value: u32) -> NonZeroU32 {
+        NonZeroU32::new(value).unwrap()
+    }
+
+    fn fetch(seq: u32, items: Vec<MessageDataItem<'static>>) -> Response<'static> {
+        Response::Data(Data::Fetch {
+            seq: nz(seq),
+            items: Vec1::try_from(items).unwrap(),
+        })
+    }
+
+    fn modseq_item(modseq: u64) -> MessageDataItem<'static> {
+        MessageDataItem::Other(
+            MessageDataItemOther::try_from(TaggedExt {
+                label: TaggedExtLabel::try_from("MODSEQ").unwrap(),
+                value: TaggedExtVal::Comp(vec![TaggedExtComp::AString(
+                    AString::try_from(modseq.to_string()).unwrap(),
+                )]),
+            })
+            .unwrap(),
+        )
+    }
+
+    fn code(code: Code<'static>) -> Response<'static> {
+        Response::Status(Status::ok(None, Some(code), "...").unwrap())
+    }
+
+    /// Select a mailbox with UIDs 10, 20, ..., 50.
+    fn select() -> SelectedMailbox {
+        let mut mailbox = SelectedMailbox::new();
+
+        mailbox.update(&Response::Data(Data::Exists(5)));
+        mailbox.update(&Response::Data(Data::Recent(1)));
+        mailbox.update(&code(Code::UidValidity(nz(1))));
+        mailbox.update(&code(Code::UidNext(nz(51))));
+
+        for seq in 1..=5 {
+            mailbox.update(&fetch(
+                seq,
+                vec![
+                    MessageDataItem::Uid(nz(seq * 10)),
+                    MessageDataItem::Flags(vec![FlagFetch::Flag(Flag::Seen)]),
+                ],
+            ));
+        }
+
+        mailbox
+    }
+
+    fn uids(mailbox: &SelectedMailbox) -> Vec<Option<u32>> {
+        (1..=mailbox.exists())
+            .map(|seq| mailbox.uid(nz(seq)).map(NonZeroU32::get))
+            .collect()
+    }
+
+    #[test]
+    fn test_select() {
+        let mailbox = select();
+
+        assert_eq!(mailbox.exists(), 5);
+        assert_eq!(mailbox.recent(), 1);
+        assert_eq!(mailbox.uid_validity(), Some(nz(1)));
+        assert_eq!(mailbox.uid_next(), Some(nz(51)));
+        assert_eq!(mailbox.uid(nz(3)), Some(nz(30)));
+        assert_eq!(mailbox.seq(nz(40)), Some(nz(4)));
+        assert_eq!(mailbox.seq(nz(41)), None);
+        assert_eq!(
+            mailbox.message(nz(1)).unwrap().flags,
+            Some(vec![FlagFetch::Flag(Flag::Seen)])
+        );
+        assert_eq!(mailbox.message(nz(6)), None);
+    }
+
+    #[test]
+    fn test_expunge_renumbering() {
+        // Expunge the last three messages "lower to higher" ...
+        let mut mailbox = select();
+        for _ in 0..3 {
+            mailbox.update(&Response::Data(Data::Expunge(nz(3))));
+        }
+        assert_eq!(uids(&mailbox), [Some(10), Some(20)]);
+
+        // ... and "higher to lower".
+        let mut mailbox = select();
+        for seq in [5, 4, 3] {
+            mailbox.update(&Response::Data(Data::Expunge(nz(seq))));
+        }
+        assert_eq!(uids(&mailbox), [Some(10), Some(20)]);
+
+        // Expunge in between and fetch with the new sequence numbers.
+        let mut mailbox = select();
+        mailbox.update(&Response::Data(Data::Expunge(nz(2))));
+        mailbox.update(&Response::Data(Data::Expunge(nz(3))));
+        mailbox.update(&fetch(3, vec![MessageDataItem::Flags(vec![])]));
+        assert_eq!(uids(&mailbox), [Some(10), Some(30), Some(50)]);
+        assert_eq!(mailbox.seq(nz(50)), Some(nz(3)));
+        assert_eq!(mailbox.message(nz(3)).unwrap().flags, Some(vec![]));
+
+        // Out of range
+        mailbox.update(&Response::Data(Data::Expunge(nz(42))));
+        assert_eq!(mailbox.exists(), 3);
+    }
+
+    #[test]
+    fn test_new_messages() {
+        let mut mailbox = select();
+        mailbox.update(&Response::Data(Data::Exists(7)));
+        mailbox.update(&fetch(7, vec![MessageDataItem::Uid(nz(70))]));
+
+        assert_eq!(
+            uids(&mailbox),
+            [
+                Some(10),
+                Some(20),
+                Some(30),
+                Some(40),
+                Some(50),
+                None,
+                Some(70)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_huge_sequence_numbers() {
+        // Sequence numbers chosen by the server must not be allocated eagerly.
+        let mut mailbox = SelectedMailbox::new();
+        mailbox.update(&Response::Data(Data::Exists(u32::MAX)));
+        assert_eq!(mailbox.exists(), u32::MAX);
+        assert_eq!(mailbox.messages().count(), 0);
+
+        mailbox.update(&fetch(4_000_000_000, vec![MessageDataItem::Uid(nz(1))]));
+        mailbox.update(&fetch(u32::MAX, vec![MessageDataItem::Uid(nz(2))]));
+        assert_eq!(mailbox.messages().count(), 2);
+        assert_eq!(mailbox.seq(nz(2)), Some(nz(u32::MAX)));
+
+        mailbox.update(&Response::Data(Data::Expunge(nz(1))));
+        assert_eq!(mailbox.exists(), u32::MAX - 1);
+        assert_eq!(mailbox.seq(nz(1)), Some(nz(3_999_999_999)));
+        assert_eq!(mailbox.seq(nz(2)), Some(nz(u32::MAX - 1)));
+
+        mailbox.update(&Response::Data(Data::Exists(5)));
+        assert_eq!(mailbox.messages().count(), 0);
+
+        // Fetching beyond EXISTS
+        let mut mailbox = SelectedMailbox::new();
+        mailbox.update(&fetch(4_000_000_000, vec![MessageDataItem::Uid(nz(1))]));
+        assert_eq!(mailbox.exists(), 4_000_000_000);
+        assert_eq!(mailbox.uid(nz(4_000_000_000)), Some(nz(1)));
+    }
+
+    #[test]
+    fn test_uid_validity_reset() {
+        let mut mailbox = select();
+        mailbox.update(&fetch(1, vec![modseq_item(42)]));
+        assert_eq!(mailbox.highest_modseq(), Some(42));
+
+        // Same UIDVALIDITY
+        mailbox.update(&code(Code::UidValidity(nz(1))));
+        assert_eq!(mailbox.uid(nz(1)), Some(nz(10)));
+
+        // New UIDVALIDITY
+        mailbox.update(&code(Code::UidValidity(nz(2))));
+        assert_eq!(mailbox.uid_validity(), Some(nz(2)));
+        assert_eq!(mailbox.uid_next(), None);
+        assert_eq!(mailbox.highest_modseq(), None);
+        assert_eq!(uids(&mailbox), [None; 5]);
+        assert_eq!(
+            mailbox.message(nz(1)).unwrap().flags,
+            Some(vec![FlagFetch::Flag(Flag::Seen)])
+        );
+    }
+
+    #[test]
+    fn test_modseq() {
+        let mut mailbox = select();
+        mailbox.update(&fetch(2, vec![modseq_item(12345)]));
+        mailbox.update(&fetch(1, vec![modseq_item(100)]));
+        assert_eq!(mailbox.message(nz(2)).unwrap().modseq, Some(12345));
+        assert_eq!(mailbox.highest_modseq(), Some(12345));
+
+        mailbox.update(&code(Code::Other(crate::response::CodeOther::unvalidated(
+            b"HIGHESTMODSEQ 4294967296".as_ref(),
+        ))));
+        assert_eq!(mailbox.highest_modseq(), Some(4294967296));
+    }
+
+    #[test]
+    fn test_uid_fetch() {
+        let mut mailbox = select();
+
+        // Known UID
+        mailbox.update(&Response::Data(
+            Data::uid_fetch(30, vec![MessageDataItem::Flags(vec![]), modseq_item(7)]).unwrap(),
+        ));
+        let message = mailbox.message(nz(3)).unwrap();
+        assert_eq!(message.flags, Some(vec![]));
+        assert_eq!(message.modseq, Some(7));
+        assert_eq!(mailbox.message_by_uid(nz(30)), Some(message));
+        assert_eq!(mailbox.highest_modseq(), Some(7));
+
+        // Unknown UID
+        mailbox.update(&Response::Data(
+            Data::uid_fetch(
+                60,
+                vec![
+                    MessageDataItem::Flags(vec![FlagFetch::Flag(Flag::Deleted)]),
+                    modseq_item(8),
+                ],
+            )
+            .unwrap(),
+        ));
+        assert_eq!(mailbox.exists(), 5);
+        assert_eq!(mailbox.seq(nz(60)), None);
+        assert_eq!(
+            mailbox.message_by_uid(nz(60)),
+            Some(&Message {
+                uid: Some(nz(60)),
+                flags: Some(vec![FlagFetch::Flag(Flag::Deleted)]),
+                modseq: Some(8),
+            })
+        );
+        assert_eq!(mailbox.highest_modseq(), Some(8));
+
+        // New UIDVALIDITY
+        mailbox.update(&code(Code::UidValidity(nz(2))));
+        assert_eq!(mailbox.message_by_uid(nz(60)), None);
+    }
+}
@@ -498,6 +498,160 @@ impl<'a> SequenceSet {
     }
 }
 
+// ----- Set algebra -----
+
+impl SequenceSet {
+    /// Sort and merge all sequences.
+    ///
+    /// When `largest` is given, `*` is resolved. Otherwise, `*` is kept and treated as a value
+    /// greater than any number, i.e., `5:*` covers everything from `5` on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    ///
+    /// use imap_types::sequence::SequenceSet;
+    ///
+    /// let seq = SequenceSet::try_from("7,1:3,*,2,4,10:*").unwrap();
+    ///
+    /// assert_eq!(seq.compact(None), SequenceSet::try_from("1:4,7,10:*").unwrap());
+    /// assert_eq!(
+    ///     seq.compact(NonZeroU32::new(12)),
+    ///     SequenceSet::try_from("1:4,7,10:12").unwrap()
+    /// );
+    /// ```
+    pub fn compact(&self, largest: Option<NonZeroU32>) -> SequenceSet {
+        Self::from_ranges(self.ranges(largest)).unwrap()
+    }
+
+    /// Union of `self` and `other`.
+    ///
+    /// Note: `*` is kept as described in [`Self::compact`]. Use `compact(Some(largest))` on both
+    /// sets first to get an exact result.
+    pub fn union(&self, other: &SequenceSet) -> SequenceSet {
+        Self::from_ranges(self.ranges(None).union(&other.ranges(None))).unwrap()
+    }
+
+    /// Intersection of `self` and `other` (or `None` if empty).
+    ///
+    /// `*` is resolved to `largest` first.
+    pub fn intersection(&self, other: &SequenceSet, largest: NonZeroU32) -> Option<SequenceSet> {
+        Self::from_ranges(
+            self.ranges(Some(largest))
+                .intersection(&other.ranges(Some(largest))),
+        )
+    }
+
+    /// All values of `self` not contained in `other` (or `None` if empty).
+    ///
+    /// `*` is resolved to `largest` first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    ///
+    /// use imap_types::sequence::SequenceSet;
+    ///
+    /// let all = SequenceSet::try_from("1:*").unwrap();
+    /// let seen = SequenceSet::try_from("1:10").unwrap();
+    ///
+    /// assert_eq!(all.difference(&seen, NonZeroU32::new(5).unwrap()), None);
+    /// assert_eq!(
+    ///     all.difference(&seen, NonZeroU32::new(12).unwrap()),
+    ///     Some(SequenceSet::try_from("11:12").unwrap())
+    /// );
+    /// ```
+    pub fn difference(&self, other: &SequenceSet, largest: NonZeroU32) -> Option<SequenceSet> {
+        Self::from_ranges(
+            self.ranges(Some(largest))
+                .difference(&other.ranges(Some(largest))),
+        )
+    }
+
+    /// Returns whether all values of `self` are contained in `other`.
+    ///
+    /// `*` is resolved to `largest` first.
+    pub fn is_subset(&self, other: &SequenceSet, largest: NonZeroU32) -> bool {
+        self.ranges(Some(largest))
+            .difference(&other.ranges(Some(largest)))
+            .is_empty()
+    }
+
+    /// Returns whether `value` is contained in the set.
+    pub fn contains(&self, value: NonZeroU32, largest: NonZeroU32) -> bool {
+        self.ranges(Some(largest)).contains(u64::from(value.get()))
+    }
+
+    /// Returns the number of distinct values in the set.
+    pub fn count(&self, largest: NonZeroU32) -> u64 {
+        self.ranges(Some(largest)).count()
+    }
+
+    /// Compact the set (see [`Self::compact`]) and split it into sets whose encoded length (in
+    /// bytes) is at most `max_length`.
+    ///
+    /// A single sequence is never split. Thus, a chunk exceeds `max_length` only when
+    /// `max_length` is smaller than the longest sequence, i.e., `4294967294:4294967295`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use imap_types::sequence::SequenceSet;
+    ///
+    /// let seq = SequenceSet::try_from("1,3,5,7:9,11:*").unwrap();
+    ///
+    /// assert_eq!(
+    ///     seq.chunks(8),
+    ///     vec![
+    ///         SequenceSet::try_from("1,3,5").unwrap(),
+    ///         SequenceSet::try_from("7:9,11:*").unwrap(),
+    ///     ]
+    /// );
+    /// ```
+    pub fn chunks(&self, max_length: usize) -> Vec<SequenceSet> {
+        self.ranges(None)
+            .chunks(max_length)
+            .into_iter()
+            .map(|ranges| Self::from_ranges(ranges).unwrap())
+            .collect()
+    }
+
+    fn ranges(&self, largest: Option<NonZeroU32>) -> Ranges {
+        let bound = |value: &SeqOrUid| match (value, largest) {
+            (SeqOrUid::Value(value), _) => u64::from(value.get()),
+            (SeqOrUid::Asterisk, Some(largest)) => u64::from(largest.get()),
+            (SeqOrUid::Asterisk, None) => ASTERISK,
+        };
+
+        Ranges::new(self.0.as_ref().iter().map(|sequence| match sequence {
+            Sequence::Single(value) => (bound(value), bound(value)),
+            Sequence::Range(from, to) => (bound(from), bound(to)),
+        }))
+    }
+
+    fn from_ranges(ranges: Ranges) -> Option<SequenceSet> {
+        let value = |value: u64| match u32::try_from(value).ok().and_then(NonZeroU32::new) {
+            Some(value) => SeqOrUid::Value(value),
+            None => SeqOrUid::Asterisk,
+        };
+
+        let sequences = ranges
+            .iter()
+            .map(|(from, to)| {
+                if from == to {
+                    Sequence::Single(value(from))
+                } else {
+                    Sequence::Range(value(from), value(to))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Vec1::try_from(sequences).ok().map(SequenceSet)
+    }
+}
+
 impl SeqOrUid {
     pub fn expand(&self, largest: NonZeroU32) -> NonZeroU32 {
         match self {
@@ -608,6 +762,161 @@ fn cleanup(remaining: VecDeque<(u32, u32)>) -> VecDeque<(u32, u32)> {
     stack
 }
 
+/// `*` when not resolved, i.e., greater than any number.
+const ASTERISK: u64 = u32::MAX as u64 + 1;
+
+/// Sorted, non-overlapping, and non-adjacent inclusive ranges.
+///
+/// Used to implement set operations without expanding sequence sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Ranges(Vec<(u64, u64)>);
+
+impl Ranges {
+    pub(crate) fn new<I>(ranges: I) -> Self
+    where
+        I: IntoIterator<Item = (u64, u64)>,
+    {
+        let mut ranges: Vec<_> = ranges
+            .into_iter()
+            .map(|(a, b)| if a <= b { (a, b) } else { (b, a) })
+            .collect();
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+
+        for (from, to) in ranges {
+            match merged.last_mut() {
+                Some(last) if from <= last.1 + 1 => last.1 = max(last.1, to),
+                _ => merged.push((from, to)),
+            }
+        }
+
+        Self(merged)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn union(&self, other: &Ranges) -> Ranges {
+        Ranges::new(self.iter().chain(other.iter()))
+    }
+
+    pub(crate) fn intersection(&self, other: &Ranges) -> Ranges {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(&(a_from, a_to)), Some(&(b_from, b_to))) = (self.0.get(i), other.0.get(j)) {
+            let from = max(a_from, b_from);
+            let to = a_to.min(b_to);
+
+            if from <= to {
+                result.push((from, to));
+            }
+
+            if a_to < b_to {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Ranges(result)
+    }
+
+    pub(crate) fn difference(&self, other: &Ranges) -> Ranges {
+        let mut result = Vec::new();
+        let mut j = 0;
+
+        for &(mut from, to) in &self.0 {
+            // Skip ranges that end before this range.
+            while other
+                .0
+                .get(j)
+                .map_or(false, |&(_, other_to)| other_to < from)
+            {
+                j += 1;
+            }
+
+            for &(other_from, other_to) in other.0[j..].iter().take_while(|(f, _)| *f <= to) {
+                if other_from > from {
+                    result.push((from, other_from - 1));
+                }
+
+                from = other_to + 1;
+            }
+
+            if from <= to {
+                result.push((from, to));
+            }
+        }
+
+        Ranges(result)
+    }
+
+    pub(crate) fn contains(&self, value: u64) -> bool {
+        let index = self.0.partition_point(|&(_, to)| to < value);
+
+        self.0.get(index).map_or(false, |&(from, _)| from <= value)
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.iter().map(|(from, to)| to - from + 1).sum()
+    }
+
+    /// Split into chunks with an encoded length of at most `max_length`.
+    pub(crate) fn chunks(&self, max_length: usize) -> Vec<Ranges> {
+        fn digits(mut value: u64) -> usize {
+            if value == ASTERISK {
+                return 1;
+            }
+
+            let mut digits = 1;
+
+            while value >= 10 {
+                value /= 10;
+                digits += 1;
+            }
+
+            digits
+        }
+
+        let mut chunks = Vec::new();
+        let mut chunk = Vec::new();
+        let mut length = 0;
+
+        for (from, to) in self.iter() {
+            let range_length = if from == to {
+                digits(from)
+            } else {
+                digits(from) + 1 + digits(to)
+            };
+
+            if !chunk.is_empty() && length + 1 + range_length > max_length {
+                chunks.push(Ranges(std::mem::take(&mut chunk)));
+                length = 0;
+            }
+
+            length += if chunk.is_empty() {
+                range_length
+            } else {
+                1 + range_length
+            };
+            chunk.push((from, to));
+        }
+
+        if !chunk.is_empty() {
+            chunks.push(Ranges(chunk));
+        }
+
+        chunks
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU32;
@@ -887,4 +1196,155 @@ mod tests {
             assert_eq!(naive, clean);
         }
     }
+
+    #[test]
+    fn test_compact() {
+        let tests = [
+            ("1", None, "1"),
+            ("3,2,1", None, "1:3"),
+            ("5:1,3:7,9", None, "1:7,9"),
+            ("*,1:4294967295", None, "1:*"),
+            ("*:5,10", None, "5:*"),
+            ("*:5,10", Some(7), "5:7,10"),
+            ("*,2", Some(1), "1:2"),
+        ];
+
+        for (test, largest, expected) in tests {
+            let seq = SequenceSet::try_from(test).unwrap();
+            let largest = largest.map(|largest| NonZeroU32::new(largest).unwrap());
+
+            assert_eq!(
+                seq.compact(largest),
+                SequenceSet::try_from(expected).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let tests = [
+            ("1:10", "5:15", 100, "1:15", Some("5:10"), Some("1:4")),
+            ("1:10", "11:20", 100, "1:20", None, Some("1:10")),
+            (
+                "1:10,20:30",
+                "5,25:*",
+                100,
+                "1:10,20:*",
+                Some("5,25:30"),
+                Some("1:4,6:10,20:24"),
+            ),
+            (
+                "1:4294967295",
+                "2:4294967294",
+                100,
+                "1:4294967295",
+                Some("2:4294967294"),
+                Some("1,4294967295"),
+            ),
+            (
+                "1:*",
+                "1:4294967295",
+                4294967295,
+                "1:*",
+                Some("1:4294967295"),
+                None,
+            ),
+            ("5", "1:*", 100, "1:*", Some("5"), None),
+            ("5", "1:*", 3, "1:*", None, Some("5")),
+            // `*` is smaller than the subtracted range.
+            ("1:*", "1:10", 5, "1:*", Some("1:5"), None),
+            ("1:*", "1:10", 12, "1:*", Some("1:10"), Some("11:12")),
+        ];
+
+        for (a, b, largest, union, intersection, difference) in tests {
+            let a = SequenceSet::try_from(a).unwrap();
+            let b = SequenceSet::try_from(b).unwrap();
+            let largest = NonZeroU32::new(largest).unwrap();
+            let seq = |seq: &str| SequenceSet::try_from(seq).unwrap();
+
+            assert_eq!(a.union(&b), seq(union));
+            assert_eq!(a.intersection(&b, largest), intersection.map(seq));
+            assert_eq!(a.difference(&b, largest), difference.map(seq));
+            assert_eq!(a.is_subset(&b, largest), difference.is_none());
+        }
+    }
+
+    #[test]
+    fn test_contains_and_count() {
+        let largest = NonZeroU32::new(100).unwrap();
+        let seq = SequenceSet::try_from("1:4294967295").unwrap();
+        assert_eq!(seq.count(largest), u64::from(u32::MAX));
+        assert!(seq.contains(NonZeroU32::new(4242424242).unwrap(), largest));
+
+        let seq = SequenceSet::try_from("10:20,50,90:*").unwrap();
+        assert_eq!(seq.count(largest), 11 + 1 + 11);
+
+        for (value, expected) in [
+            (1, false),
+            (10, true),
+            (20, true),
+            (21, false),
+            (50, true),
+            (95, true),
+            (101, false),
+        ] {
+            assert_eq!(
+                seq.contains(NonZeroU32::new(value).unwrap(), largest),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_chunks() {
+        let seq = SequenceSet::try_from("1,3,5,7,9").unwrap();
+        assert_eq!(seq.chunks(1000), vec![seq.clone()]);
+        assert_eq!(
+            seq.chunks(3),
+            vec![
+                SequenceSet::try_from("1,3").unwrap(),
+                SequenceSet::try_from("5,7").unwrap(),
+                SequenceSet::try_from("9").unwrap(),
+            ]
+        );
+
+        // A single sequence is never split.
+        let seq = SequenceSet::try_from("1000:2000,3000").unwrap();
+        assert_eq!(
+            seq.chunks(2),
+            vec![
+                SequenceSet::try_from("1000:2000").unwrap(),
+                SequenceSet::try_from("3000").unwrap(),
+            ]
+        );
+
+        // Many UIDs
+        let seq = SequenceSet::try_from(
+            (1..=10_000u32)
+                .step_by(2)
+                .map(|uid| uid.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+                .as_str(),
+        )
+        .unwrap();
+        let chunks = seq.chunks(1000);
+        for chunk in &chunks {
+            let encoded = chunk
+                .0
+                .as_ref()
+                .iter()
+                .map(|seq| match seq {
+                    Sequence::Single(SeqOrUid::Value(value)) => value.to_string(),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            assert!(encoded.len() <= 1000);
+        }
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.count(MAX)).sum::<u64>(),
+            5_000
+        );
+    }
 }
@@ -7,39 +7,39 @@
 //! and the server will respond with a BAD or NO (depending upon server implementation) command completion result." ([RFC 3501](https://www.rfc-editor.org/rfc/rfc3501.html))
 //!
 //! ```text
-//!           +----------------------+
-//!           |connection established|
-//!           +----------------------+
-//!                      ||
-//!                      \/
-//!    +--------------------------------------+
-//!    |          server greeting             |
-//!    +--------------------------------------+
-//!              || (1)       || (2)        || (3)
-//!              \/           ||            ||
-//!    +-----------------+    ||            ||
-//!    |Not Authenticated|    ||            ||
-//!    +-----------------+    ||            ||
-//!     || (7)   || (4)       ||            ||
-//!     ||       \/           \/            ||
-//!     ||     +----------------+           ||
-//!     ||     | Authenticated  |<=++       ||
-//!     ||     +----------------+  ||       ||
-//!     ||       || (7)   || (5)   || (6)   ||
-//!     ||       ||       \/       ||       ||
-//!     ||       ||    +--------+  ||       ||
-//!     ||       ||    |Selected|==++       ||
-//!     ||       ||    +--------+           ||
-//!     ||       ||       || (7)            ||
-//!     \/       \/       \/                \/
-//!    +--------------------------------------+
-//!    |               Logout                 |
-//!    +--------------------------------------+
-//!                      ||
-//!                      \/
-//!        +-------------------------------+
-//!        |both sides close the connection|
-//!        +-------------------------------+
+//!              +----------------------+
+//!              |connection established|
+//!              +----------------------+
+//!                         ||
+//!                         \/
+//!       +--------------------------------------+
+//!       |          server greeting             |
+//!       +--------------------------------------+
+//!                 || (1)       || (2)        || (3)
+//!                 \/           ||            ||
+//!       +-----------------+    ||            ||
+//!  ++==>|Not Authenticated|    ||            ||
+//!  ||   +-----------------+    ||            ||
+//!  ||    || (7)   || (4)       ||            ||
+//!  ||    ||       \/           \/            ||
+//!  ||    ||     +----------------+           ||
+//!  ++====||=====| Authenticated  |<=++       ||
+//!  ||    ||     +----------------+  ||       ||
+//!  ||    ||       || (7)   || (5)   || (6)   ||
+//!  ||    ||       ||       \/       ||       ||
+//!  ||    ||       ||    +--------+  ||       ||
+//!  ++====||=======||====|Selected|==++       ||
+//!  (8)   ||       ||    +--------+           ||
+//!        ||       ||       || (7)            ||
+//!        \/       \/       \/                \/
+//!       +--------------------------------------+
+//!       |               Logout                 |
+//!       +--------------------------------------+
+//!                         ||
+//!                         \/
+//!           +-------------------------------+
+//!           |both sides close the connection|
+//!           +-------------------------------+
 //!
 //! (1) connection without pre-authentication (OK greeting)
 //! (2) pre-authenticated connection (PREAUTH greeting)
@@ -48,13 +48,23 @@
 //! (5) successful SELECT or EXAMINE command
 //! (6) CLOSE command, or failed SELECT or EXAMINE command
 //! (7) LOGOUT command, server shutdown, or connection closed
+//! (8) successful UNAUTHENTICATE command (RFC 8437)
 //! ```
+//!
+//! [`State`] doesn't track itself. Use [`State::from_greeting`] and [`State::next`] to compute
+//! the next state from the exchanged messages. Both functions are sans-IO and can be used by
+//! clients and servers alike.
 
 use bounded_static_derive::ToStatic;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{core::Tag, mailbox::Mailbox};
+use crate::{
+    command::{Command, CommandBody},
+    core::Tag,
+    mailbox::Mailbox,
+    response::{Greeting, GreetingKind, Response, Status, StatusKind, Tagged},
+};
 
 /// State of the IMAP4rev1 connection.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -63,7 +73,8 @@ pub enum State<'a> {
     Greeting,
 
     /// The client MUST supply authentication credentials before most commands will be permitted.
-    /// This state is entered when a connection starts unless the connection has been pre-authenticated.
+    /// This state is entered when a connection starts unless the connection has been pre-authenticated,
+    /// or after a successful UNAUTHENTICATE command (RFC 8437).
     NotAuthenticated,
 
     /// The client is authenticated and MUST select a mailbox to access before commands that affect messages will be permitted.
@@ -91,10 +102,96 @@ pub enum State<'a> {
     IdleSelected(Tag<'a>, Mailbox<'a>),
 }
 
+impl<'a> State<'a> {
+    /// Returns the state entered after the server `greeting`.
+    ///
+    /// See transitions (1), (2), and (3).
+    pub fn from_greeting(greeting: &Greeting) -> Self {
+        match greeting.kind {
+            GreetingKind::Ok => Self::NotAuthenticated,
+            GreetingKind::PreAuth => Self::Authenticated,
+            GreetingKind::Bye => Self::Logout,
+        }
+    }
+
+    /// Returns the state entered after `response` was exchanged during the execution of
+    /// `command`.
+    ///
+    /// Call this for every response (including untagged responses and command continuation
+    /// requests) of the currently executed command. Responses that don't trigger a transition
+    /// yield the current state.
+    ///
+    /// Note: When multiple commands are executed concurrently, only pass the tagged status of the
+    /// command it completes. Commands that change the state must not be pipelined anyway.
+    pub fn next(&self, command: &Command<'a>, response: &Response) -> Self {
+        match response {
+            // A BYE can be sent at any time.
+            Response::Status(Status::Bye(_)) => Self::Logout,
+            // The server accepted IDLE.
+            Response::CommandContinuationRequest(_) => match (self, &command.body) {
+                (Self::Authenticated, CommandBody::Idle) => {
+                    Self::IdleAuthenticated(command.tag.clone())
+                }
+                (Self::Selected(mailbox), CommandBody::Idle) => {
+                    Self::IdleSelected(command.tag.clone(), mailbox.clone())
+                }
+                _ => self.clone(),
+            },
+            Response::Status(Status::Tagged(Tagged { tag, body })) if *tag == command.tag => {
+                self.completed(command, body.kind)
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns the state entered after `command` was completed with `kind`.
+    fn completed(&self, command: &Command<'a>, kind: StatusKind) -> Self {
+        let ok = kind == StatusKind::Ok;
+
+        match (self, &command.body) {
+            // (4)
+            (
+                Self::NotAuthenticated,
+                CommandBody::Login { .. } | CommandBody::Authenticate { .. },
+            ) if ok => Self::Authenticated,
+            // (5)
+            (
+                Self::Authenticated | Self::Selected(_),
+                CommandBody::Select { mailbox, .. } | CommandBody::Examine { mailbox, .. },
+            ) if ok => Self::Selected(mailbox.clone()),
+            // (6) "If the client is permitted to [select] a mailbox while already selected and
+            // the [command] fails, no mailbox is selected." (RFC 3501)
+            (Self::Selected(_), CommandBody::Select { .. } | CommandBody::Examine { .. }) => {
+                Self::Authenticated
+            }
+            // (6)
+            (Self::Selected(_), CommandBody::Close | CommandBody::Unselect) if ok => {
+                Self::Authenticated
+            }
+            // IDLE was terminated (or rejected after the command continuation request).
+            (Self::IdleAuthenticated(tag), CommandBody::Idle) if *tag == command.tag => {
+                Self::Authenticated
+            }
+            (Self::IdleSelected(tag, mailbox), CommandBody::Idle) if *tag == command.tag => {
+                Self::Selected(mailbox.clone())
+            }
+            // (7)
+            (_, CommandBody::Logout) if ok => Self::Logout,
+            // (8)
+            (Self::Authenticated | Self::Selected(_), CommandBody::Unauthenticate) if ok => {
+                Self::NotAuthenticated
+            }
+            _ => self.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{core::Tag, mailbox::Mailbox, IntoStatic, ToStatic};
+    use crate::{
+        core::Tag, mailbox::Mailbox, response::CommandContinuationRequest, IntoStatic, ToStatic,
+    };
 
     #[test]
     fn test_conversion() {
@@ -118,4 +215,199 @@ mod tests {
             }
         }
     }
+
+    fn tagged(tag: &str, kind: StatusKind) -> Response<'static> {
+        let tag = Some(Tag::try_from(tag.to_owned()).unwrap());
+
+        Response::Status(match kind {
+            StatusKind::Ok => Status::ok(tag, None, "...").unwrap(),
+            StatusKind::No => Status::no(tag, None, "...").unwrap(),
+            StatusKind::Bad => Status::bad(tag, None, "...").unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_from_greeting() {
+        let tests = [
+            (Greeting::ok(None, "...").unwrap(), State::NotAuthenticated),
+            (
+                Greeting::preauth(None, "...").unwrap(),
+                State::Authenticated,
+            ),
+            (Greeting::bye(None, "...").unwrap(), State::Logout),
+        ];
+
+        for (greeting, expected) in tests {
+            assert_eq!(State::from_greeting(&greeting), expected);
+        }
+    }
+
+    #[test]
+    fn test_next() {
+        let inbox = Mailbox::Inbox;
+        let other = Mailbox::try_from("other").unwrap();
+        let a = Tag::try_from("A").unwrap();
+
+        let tests = [
+            // LOGIN and AUTHENTICATE
+            (
+                State::NotAuthenticated,
+                CommandBody::login("alice", "password").unwrap(),
+                tagged("A", StatusKind::Ok),
+                State::Authenticated,
+            ),
+            (
+                State::NotAuthenticated,
+                CommandBody::login("alice", "password").unwrap(),
+                tagged("A", StatusKind::No),
+                State::NotAuthenticated,
+            ),
+            (
+                State::NotAuthenticated,
+                CommandBody::authenticate(crate::auth::AuthMechanism::Plain),
+                Response::CommandContinuationRequest(CommandContinuationRequest::base64(
+                    b"".as_ref(),
+                )),
+                State::NotAuthenticated,
+            ),
+            (
+                State::NotAuthenticated,
+                CommandBody::authenticate(crate::auth::AuthMechanism::Plain),
+                tagged("A", StatusKind::Ok),
+                State::Authenticated,
+            ),
+            // Tag mismatch
+            (
+                State::NotAuthenticated,
+                CommandBody::login("alice", "password").unwrap(),
+                tagged("B", StatusKind::Ok),
+                State::NotAuthenticated,
+            ),
+            // SELECT and EXAMINE
+            (
+                State::Authenticated,
+                CommandBody::select("inbox").unwrap(),
+                tagged("A", StatusKind::Ok),
+                State::Selected(inbox.clone()),
+            ),
+            (
+                State::Authenticated,
+                CommandBody::examine("inbox").unwrap(),
+                tagged("A", StatusKind::No),
+                State::Authenticated,
+            ),
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::examine("other").unwrap(),
+                tagged("A", StatusKind::Ok),
+                State::Selected(other.clone()),
+            ),
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::select("other").unwrap(),
+                tagged("A", StatusKind::No),
+                State::Authenticated,
+            ),
+            // CLOSE and UNSELECT
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::Close,
+                tagged("A", StatusKind::Ok),
+                State::Authenticated,
+            ),
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::Unselect,
+                tagged("A", StatusKind::Ok),
+                State::Authenticated,
+            ),
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::Unselect,
+                tagged("A", StatusKind::Bad),
+                State::Selected(inbox.clone()),
+            ),
+            // IDLE
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::Idle,
+                Response::CommandContinuationRequest(
+                    CommandContinuationRequest::basic(None, "idling").unwrap(),
+                ),
+                State::IdleSelected(a.clone(), inbox.clone()),
+            ),
+            (
+                State::IdleSelected(a.clone(), inbox.clone()),
+                CommandBody::Idle,
+                tagged("A", StatusKind::Ok),
+                State::Selected(inbox.clone()),
+            ),
+            (
+                State::Authenticated,
+                CommandBody::Idle,
+                Response::CommandContinuationRequest(
+                    CommandContinuationRequest::basic(None, "idling").unwrap(),
+                ),
+                State::IdleAuthenticated(a.clone()),
+            ),
+            (
+                State::IdleAuthenticated(a.clone()),
+                CommandBody::Idle,
+                tagged("A", StatusKind::Bad),
+                State::Authenticated,
+            ),
+            (
+                State::Authenticated,
+                CommandBody::Idle,
+                tagged("A", StatusKind::No),
+                State::Authenticated,
+            ),
+            // LOGOUT
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::Logout,
+                Response::Status(Status::bye(None, "...").unwrap()),
+                State::Logout,
+            ),
+            (
+                State::Authenticated,
+                CommandBody::Logout,
+                tagged("A", StatusKind::Ok),
+                State::Logout,
+            ),
+            // Unilateral BYE
+            (
+                State::IdleAuthenticated(a.clone()),
+                CommandBody::Idle,
+                Response::Status(Status::bye(None, "...").unwrap()),
+                State::Logout,
+            ),
+            // UNAUTHENTICATE
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::Unauthenticate,
+                tagged("A", StatusKind::Ok),
+                State::NotAuthenticated,
+            ),
+            (
+                State::Authenticated,
+                CommandBody::Unauthenticate,
+                tagged("A", StatusKind::No),
+                State::Authenticated,
+            ),
+            // Untagged data
+            (
+                State::Selected(inbox.clone()),
+                CommandBody::Noop,
+                Response::Status(Status::ok(None, None, "...").unwrap()),
+                State::Selected(inbox),
+            ),
+        ];
+
+        for (state, body, response, expected) in tests {
+            let command = Command::new(a.clone(), body).unwrap();
+
+            assert_eq!(state.next(&command, &response), expected);
+        }
+    }
 }
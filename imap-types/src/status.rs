@@ -2,10 +2,16 @@ use std::num::NonZeroU32;
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
+use bounded_static::IntoBoundedStatic;
 use bounded_static_derive::ToStatic;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    error::{ValidationError, ValidationErrorKind},
+    extensions::tagged_ext::{TaggedExt, TaggedExtItem},
+};
+
 /// Status data item name used to request a status data item.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -66,4 +72,67 @@ pub enum StatusDataItem {
 
     /// The amount of storage space that can be reclaimed by performing EXPUNGE on the mailbox.
     DeletedStorage(u64),
+
+    /// A status data item of an unknown extension (RFC 4466).
+    Other(StatusDataItemOther),
+}
+
+/// A status data item of an unknown extension.
+///
+/// It's guaranteed that this type can't represent any status data item from [`StatusDataItem`].
+///
+/// Note: [`StatusDataItem`] is used in a `Cow<'a, [StatusDataItem]>` and can't borrow. Thus, the
+/// inner value is always owned.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "TaggedExtItem<'static>"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct StatusDataItemOther(pub(crate) TaggedExtItem<'static>);
+
+impl StatusDataItemOther {
+    pub fn validate(value: &TaggedExtItem) -> Result<(), ValidationError> {
+        // Labels of status data items known to `StatusDataItem`.
+        match value.label.inner().to_ascii_uppercase().as_str() {
+            "MESSAGES" | "RECENT" | "UIDNEXT" | "UIDVALIDITY" | "UNSEEN" | "DELETED"
+            | "DELETED-STORAGE" => Err(ValidationError::new(ValidationErrorKind::Invalid)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn inner(&self) -> &TaggedExtItem<'static> {
+        &self.0
+    }
+
+    /// Constructs an unknown status data item without validation.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `value` is valid according to [`Self::validate`]. Failing to do
+    /// so may create invalid/unparsable IMAP messages, or even produce unintended protocol flows.
+    /// Do not call this constructor with untrusted data.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated(value: TaggedExtItem) -> Self {
+        #[cfg(debug_assertions)]
+        Self::validate(&value).unwrap();
+
+        Self(value.into_static())
+    }
+}
+
+impl<'a> TryFrom<TaggedExtItem<'a>> for StatusDataItemOther {
+    type Error = ValidationError;
+
+    fn try_from(value: TaggedExtItem<'a>) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+
+        Ok(Self(value.into_static()))
+    }
+}
+
+impl<'a> TryFrom<TaggedExt<'a>> for StatusDataItemOther {
+    type Error = ValidationError;
+
+    fn try_from(value: TaggedExt<'a>) -> Result<Self, Self::Error> {
+        Self::try_from(TaggedExtItem::from(value))
+    }
 }
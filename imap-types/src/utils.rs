@@ -2,6 +2,8 @@
 
 use std::borrow::Cow;
 
+pub(crate) mod mime;
+
 /// Converts bytes into a ready-to-be-printed form.
 pub fn escape_byte_string<B>(bytes: B) -> String
 where
@@ -107,6 +109,16 @@ pub mod indicators {
     pub fn is_list_char(i: u8) -> bool {
         is_atom_char(i) || is_list_wildcards(i) || is_resp_specials(i)
     }
+
+    /// `tagged-label-fchar = ALPHA / "-" / "_" / "."`
+    pub fn is_tagged_label_fchar(i: u8) -> bool {
+        i.is_ascii_alphabetic() || matches!(i, b'-' | b'_' | b'.')
+    }
+
+    /// `tagged-label-char = tagged-label-fchar / DIGIT / ":"`
+    pub fn is_tagged_label_char(i: u8) -> bool {
+        is_tagged_label_fchar(i) || i.is_ascii_digit() || i == b':'
+    }
 }
 
 pub fn escape_quoted(unescaped: &str) -> Cow<str> {
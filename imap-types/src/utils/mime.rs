@@ -0,0 +1,283 @@
+//! MIME header helpers, i.e., RFC 2047 encoded-words and RFC 2231 parameter values.
+
+use std::borrow::Cow;
+
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+
+use crate::core::IString;
+
+/// Decode [RFC 2047] encoded-words.
+///
+/// Only `UTF-8`, `US-ASCII`, and `ISO-8859-1` are supported. Encoded-words in other charsets
+/// (or malformed ones) are kept as is.
+///
+/// [RFC 2047]: https://datatracker.ietf.org/doc/html/rfc2047
+pub(crate) fn decode_encoded_words(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    let mut after_encoded_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+
+        match decode_encoded_word(candidate) {
+            Some((decoded, remaining)) => {
+                // Whitespace between adjacent encoded-words is ignored.
+                if !(after_encoded_word && before.chars().all(|c| c.is_ascii_whitespace())) {
+                    output.push_str(before);
+                }
+                output.push_str(&decoded);
+                rest = remaining;
+                after_encoded_word = true;
+            }
+            None => {
+                output.push_str(before);
+                output.push_str("=?");
+                rest = &candidate[2..];
+                after_encoded_word = false;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// ```abnf
+/// encoded-word = "=?" charset "?" encoding "?" encoded-text "?="
+/// ```
+fn decode_encoded_word(input: &str) -> Option<(String, &str)> {
+    let (charset, rest) = input.strip_prefix("=?")?.split_once('?')?;
+    let (encoding, rest) = rest.split_once('?')?;
+    let end = rest.find("?=")?;
+    let (text, remaining) = (&rest[..end], &rest[end + 2..]);
+
+    if charset.is_empty() || text.contains(|c: char| c.is_ascii_whitespace()) {
+        return None;
+    }
+
+    let bytes = match encoding {
+        "B" | "b" => base64.decode(text).ok()?,
+        "Q" | "q" => decode_q(text)?,
+        _ => return None,
+    };
+
+    // RFC 2231 allows a language suffix, e.g., `US-ASCII*EN`.
+    let charset = charset.split('*').next()?.to_ascii_lowercase();
+
+    let decoded = match charset.as_str() {
+        "utf-8" | "utf8" => String::from_utf8(bytes).ok()?,
+        "us-ascii" if bytes.is_ascii() => String::from_utf8(bytes).ok()?,
+        "iso-8859-1" | "latin1" => bytes.into_iter().map(char::from).collect(),
+        _ => return None,
+    };
+
+    Some((decoded, remaining))
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'_' => output.push(b' '),
+            b'=' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                output.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            byte => output.push(byte),
+        }
+    }
+
+    Some(output)
+}
+
+/// Value of the parameter `name` (compared case-insensitively).
+///
+/// Supports RFC 2231 (`name*`, `name*0`, `name*0*`, ...) and RFC 2047 encoded-words.
+pub(crate) fn parameter(parameters: &[(IString, IString)], name: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+    let mut segments = Vec::new();
+
+    for (key, value) in parameters {
+        let key = lossy(key);
+        let rest = match key.get(..name.len()) {
+            Some(head) if head.eq_ignore_ascii_case(name) => &key[name.len()..],
+            _ => continue,
+        };
+
+        match rest {
+            "" => plain = Some(lossy(value)),
+            "*" => extended = Some(lossy(value)),
+            rest => {
+                let rest = match rest.strip_prefix('*') {
+                    Some(rest) => rest,
+                    None => continue,
+                };
+                let (number, encoded) = match rest.strip_suffix('*') {
+                    Some(number) => (number, true),
+                    None => (rest, false),
+                };
+
+                if let Ok(number) = number.parse::<u32>() {
+                    segments.push((number, encoded, lossy(value)));
+                }
+            }
+        }
+    }
+
+    if let Some(extended) = extended {
+        return Some(decode_rfc2231([(true, &*extended)]));
+    }
+
+    if !segments.is_empty() {
+        segments.sort_by_key(|(number, _, _)| *number);
+
+        return Some(decode_rfc2231(
+            segments
+                .iter()
+                .map(|(_, encoded, value)| (*encoded, &**value)),
+        ));
+    }
+
+    plain.map(|plain| decode_encoded_words(&plain))
+}
+
+fn lossy<'a>(value: &'a IString) -> Cow<'a, str> {
+    String::from_utf8_lossy(value.as_ref())
+}
+
+/// Decode (and concatenate) RFC 2231 parameter value segments.
+///
+/// Every segment is `(encoded, value)`. The first encoded segment may start with
+/// `charset'language'`. Only bytes from `%XX` escapes are decoded using the charset.
+fn decode_rfc2231<'v>(segments: impl IntoIterator<Item = (bool, &'v str)>) -> String {
+    let mut charset = None;
+    let mut output = String::new();
+    // Bytes of `%XX` escapes that were not decoded yet.
+    let mut escaped = Vec::new();
+
+    for (index, (encoded, value)) in segments.into_iter().enumerate() {
+        let mut rest = value;
+
+        if encoded && index == 0 {
+            if let Some((name, tail)) = rest.split_once('\'') {
+                if let Some((_language, tail)) = tail.split_once('\'') {
+                    charset = Some(name.to_ascii_lowercase());
+                    rest = tail;
+                }
+            }
+        }
+
+        while let Some(c) = rest.chars().next() {
+            let hex = match (encoded, c, rest.get(1..3)) {
+                (true, '%', Some(hex)) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                    u8::from_str_radix(hex, 16).ok()
+                }
+                _ => None,
+            };
+
+            match hex {
+                Some(byte) => {
+                    escaped.push(byte);
+                    rest = &rest[3..];
+                }
+                None => {
+                    decode_charset(&mut output, &mut escaped, charset.as_deref());
+                    output.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+    }
+
+    decode_charset(&mut output, &mut escaped, charset.as_deref());
+
+    output
+}
+
+/// Decode `bytes` using `charset`, append the result to `output`, and clear `bytes`.
+fn decode_charset(output: &mut String, bytes: &mut Vec<u8>, charset: Option<&str>) {
+    match charset {
+        Some("iso-8859-1" | "latin1") => output.extend(bytes.iter().map(|byte| char::from(*byte))),
+        _ => output.push_str(&String::from_utf8_lossy(bytes)),
+    }
+
+    bytes.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_encoded_words() {
+        let tests = [
+            ("plain", "plain"),
+            ("=?utf-8?b?w6TDtsO8?=", "äöü"),
+            ("=?US-ASCII*EN?Q?a_b?=", "a b"),
+            ("a =?utf-8?q?b?= c", "a b c"),
+            ("=?utf-8?q?a?=  =?utf-8?q?b?=", "ab"),
+            ("=?unknown?q?a?=", "=?unknown?q?a?="),
+            ("=?utf-8?x?a?=", "=?utf-8?x?a?="),
+            ("=?utf-8?q?a b?=", "=?utf-8?q?a b?="),
+            ("=?utf-8?q?=ZZ?=", "=?utf-8?q?=ZZ?="),
+            ("=?", "=?"),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(decode_encoded_words(test), expected, "{test:?}");
+        }
+    }
+
+    #[test]
+    fn test_parameter() {
+        let tests: &[(&[(&str, &str)], Option<&str>)] = &[
+            (&[("NAME", "a.txt")], Some("a.txt")),
+            (&[("name", "=?utf-8?q?=C3=A4?=.txt")], Some("ä.txt")),
+            (&[("NAME*", "iso-8859-1'en'%E4.txt")], Some("ä.txt")),
+            (
+                &[("NAME", "fallback"), ("NAME*", "utf-8''%C3%A4")],
+                Some("ä"),
+            ),
+            (
+                &[("NAME*1", ".txt"), ("NAME*0*", "utf-8''%C3%A4")],
+                Some("ä.txt"),
+            ),
+            // Escapes split across segments
+            (&[("NAME*0*", "utf-8''%C3"), ("NAME*1*", "%A4")], Some("ä")),
+            (&[("NAME*0", "a"), ("NAME*1", "b")], Some("ab")),
+            // Only escaped bytes are decoded using the charset.
+            (
+                &[("NAME*0*", "iso-8859-1''%E4-€"), ("NAME*1", "-ü")],
+                Some("ä-€-ü"),
+            ),
+            (&[("NAME*", "utf-8''100%")], Some("100%")),
+            (&[("NAME*", "utf-8''%+1")], Some("%+1")),
+            (&[("NAMES", "a")], None),
+            (&[("FILENAME", "a")], None),
+            (&[], None),
+        ];
+
+        for (parameters, expected) in tests {
+            let parameters: Vec<_> = parameters
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        IString::try_from(*key).unwrap(),
+                        IString::try_from(*value).unwrap(),
+                    )
+                })
+                .collect();
+
+            assert_eq!(
+                parameter(&parameters, "name").as_deref(),
+                *expected,
+                "{parameters:?}"
+            );
+        }
+    }
+}